@@ -0,0 +1,175 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::game::fleet::ShipKind;
+
+// credits earned by a colony: (population * scale) ^ power / divisor - upkeep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RevenueCurve {
+    pub scale: f32,
+    pub power: f32,
+    pub divisor: f32,
+    pub upkeep: f32,
+}
+
+impl RevenueCurve {
+    pub fn revenue(&self, population: f32) -> f32 {
+        (population * self.scale).powf(self.power) / self.divisor - self.upkeep
+    }
+}
+
+// resources that can be extracted from a star: (resources * scale) ^ power
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceCurve {
+    pub scale: f32,
+    pub power: f32,
+}
+
+impl ResourceCurve {
+    pub fn available(&self, resources: f32) -> f32 {
+        (resources * self.scale).powf(self.power)
+    }
+
+    pub fn remaining(&self, available: f32) -> f32 {
+        available.powf(1.0 / self.power) / self.scale
+    }
+}
+
+// population a colony can reach, growing with the number of turns it has been owned
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PopulationCap {
+    pub base: f32,
+    pub turns_per_inhabitant: f32,
+}
+
+impl PopulationCap {
+    pub fn max_population(&self, turns_owned: f32) -> f32 {
+        self.base + turns_owned / self.turns_per_inhabitant
+    }
+}
+
+// work a colony puts in building ships each turn:
+// base + population * per_inhabitant + available resources * per_resource
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProductionRate {
+    pub base: f32,
+    pub per_inhabitant: f32,
+    pub per_resource: f32,
+}
+
+impl ProductionRate {
+    pub fn work(&self, population: f32, resources: f32) -> f32 {
+        self.base + population * self.per_inhabitant + resources * self.per_resource
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShipCost {
+    pub credits: f32,
+    pub resources: f32,
+}
+
+// conditions are good on stars of the same color as the starting star of their owner
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "3F1B8E0C-5D2A-4C71-9A43-6B0E2F7D91C4"]
+pub struct Balance {
+    pub good_revenue: RevenueCurve,
+    pub poor_revenue: RevenueCurve,
+    pub good_resources: ResourceCurve,
+    pub poor_resources: ResourceCurve,
+    pub good_population: PopulationCap,
+    pub poor_population: PopulationCap,
+    // population grows this many times slower on a star of another size than the starting star
+    pub other_size_growth_divisor: f32,
+    pub good_harvest: f32,
+    pub poor_harvest: f32,
+    pub colony_ship: ShipCost,
+    pub fighter: ShipCost,
+    pub scout: ShipCost,
+    pub bomber: ShipCost,
+    pub troop_transport: ShipCost,
+    pub defense_platform: ShipCost,
+    // a ship needs as much work as its credits and resources costs added
+    pub production: ProductionRate,
+    // a player with savings below this loses the game
+    pub deficit_limit: f32,
+}
+
+// the balance file is the only place where the values are written, the game starts with it
+// until the asset is loaded
+impl Default for Balance {
+    fn default() -> Self {
+        ron::from_str(include_str!("../../assets/economy.balance"))
+            .expect("the economy.balance asset is invalid")
+    }
+}
+
+impl Balance {
+    pub fn revenue(&self, good: bool) -> &RevenueCurve {
+        if good {
+            &self.good_revenue
+        } else {
+            &self.poor_revenue
+        }
+    }
+
+    pub fn resources(&self, good: bool) -> &ResourceCurve {
+        if good {
+            &self.good_resources
+        } else {
+            &self.poor_resources
+        }
+    }
+
+    pub fn population(&self, good: bool) -> &PopulationCap {
+        if good {
+            &self.good_population
+        } else {
+            &self.poor_population
+        }
+    }
+
+    pub fn harvest(&self, good: bool) -> f32 {
+        if good {
+            self.good_harvest
+        } else {
+            self.poor_harvest
+        }
+    }
+
+    pub fn ship(&self, kind: ShipKind) -> &ShipCost {
+        match kind {
+            ShipKind::Colony => &self.colony_ship,
+            ShipKind::Fighter => &self.fighter,
+            ShipKind::Scout => &self.scout,
+            ShipKind::Bomber => &self.bomber,
+            ShipKind::Troops => &self.troop_transport,
+            ShipKind::Platform => &self.defense_platform,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BalanceLoader;
+
+impl AssetLoader for BalanceLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            load_context
+                .set_default_asset(LoadedAsset::new(ron::de::from_bytes::<Balance>(bytes)?));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance"]
+    }
+}
@@ -1,7 +1,9 @@
 use bevy::{asset::Asset, ecs::all_tuples, prelude::*};
 use bevy_asset_loader::prelude::{AssetCollection, LoadingState, LoadingStateAppExt};
 
-use super::names::Names;
+use crate::game::fleet::ShipKind;
+
+use super::{balance::Balance, names::Names};
 
 pub trait CloneWeak {
     fn clone_weak(&self) -> Self;
@@ -85,6 +87,27 @@ pub struct ShipAssets {
     pub colony_ship: Handle<Image>,
     #[asset(path = "ships/enemyBlue1.png")]
     pub fighter: Handle<Image>,
+    #[asset(path = "ships/enemyGreen1.png")]
+    pub scout: Handle<Image>,
+    #[asset(path = "ships/enemyBlack1.png")]
+    pub bomber: Handle<Image>,
+    #[asset(path = "ships/enemyGreen4.png")]
+    pub troop_transport: Handle<Image>,
+    #[asset(path = "ships/enemyBlack4.png")]
+    pub defense_platform: Handle<Image>,
+}
+
+impl ShipAssets {
+    pub fn ship(&self, kind: ShipKind) -> Handle<Image> {
+        match kind {
+            ShipKind::Colony => self.colony_ship.clone_weak(),
+            ShipKind::Fighter => self.fighter.clone_weak(),
+            ShipKind::Scout => self.scout.clone_weak(),
+            ShipKind::Bomber => self.bomber.clone_weak(),
+            ShipKind::Troops => self.troop_transport.clone_weak(),
+            ShipKind::Platform => self.defense_platform.clone_weak(),
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -104,6 +127,8 @@ pub struct UiAssets {
 struct RawGalaxyAssets {
     #[asset(path = "star.names")]
     star_names: Handle<Names>,
+    #[asset(path = "economy.balance")]
+    balance: Handle<Balance>,
     #[asset(path = "top-hat.png")]
     hat: Handle<Image>,
     #[asset(path = "mask.png")]
@@ -118,6 +143,7 @@ pub struct GalaxyAssets {
     pub orange_star: Handle<ColorMaterial>,
     pub unknown: Handle<ColorMaterial>,
     pub star_names: Handle<Names>,
+    pub balance: Handle<Balance>,
     pub hat: Handle<Image>,
     pub mask: Handle<Image>,
 }
@@ -185,6 +211,7 @@ fn done(world: &mut World) {
                 orange_star: materials.add(ColorMaterial::from(Color::rgb(over, 0.5, 0.0))),
                 unknown: materials.add(ColorMaterial::from(Color::rgb(0.3, 0.3, 0.3))),
                 star_names: raw.star_names,
+                balance: raw.balance,
                 hat: raw.hat,
                 mask: raw.mask,
             });
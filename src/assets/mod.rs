@@ -1,3 +1,4 @@
+pub mod balance;
 pub mod loader;
 pub mod names;
 
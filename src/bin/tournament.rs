@@ -0,0 +1,10 @@
+// runs games between bots and writes the results to a CSV file
+// usage: tournament [settings.ron] [results.csv]
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    spaceviata::tournament()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
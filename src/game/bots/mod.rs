@@ -0,0 +1,245 @@
+use std::fmt;
+
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap, Instant},
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fleet::{fleet_states, update_fleets, FleetQuery, ShipKind},
+    simulation::{self, FleetState, Orders},
+    turns::{TurnState, Turns},
+    Controller, FleetsToSpawn, Universe,
+};
+
+mod personalities;
+
+// players on other computers get this long to give their orders once the ones here are done
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(120);
+// and programs playing external slots this long
+const EXTERNAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct BotTurnStatus {
+    pub current: usize,
+    pub last_colony_ship_spawned: Vec<u32>,
+}
+
+#[derive(Resource, Default)]
+pub struct ReceivedOrders {
+    // orders of players that are not in front of this screen, with the turn they were given for
+    pub orders: HashMap<usize, (u32, Orders)>,
+    // remote players who never joined or left, a bot plays for them but they keep their prices
+    pub absent: Vec<usize>,
+}
+
+pub fn start_bots(mut bot_turn_status: ResMut<BotTurnStatus>) {
+    bot_turn_status.current = 0;
+}
+
+// what a bot knows when playing its turn, no more than a human would see
+pub struct BotView {
+    pub universe: Universe,
+    pub fleets: Vec<FleetState>,
+    // fleets of the other players around stars where the bot has a colony or ships
+    pub others: Vec<FleetState>,
+    pub player: usize,
+    pub turn: u32,
+    pub last_colony_ship_spawned: u32,
+}
+
+impl BotView {
+    pub fn new(
+        universe: &Universe,
+        fleets: &[FleetState],
+        player: usize,
+        turn: u32,
+        last_colony_ship_spawned: u32,
+    ) -> Self {
+        let (fleets, others) = universe
+            .fleets_seen_by(fleets, player)
+            .into_iter()
+            .partition(|fleet| fleet.owner == player);
+        BotView {
+            universe: universe.seen_by(player),
+            fleets,
+            others,
+            player,
+            turn,
+            last_colony_ship_spawned,
+        }
+    }
+}
+
+pub trait BotStrategy {
+    // fleet moves and ships to buy for this turn
+    fn decide(&self, view: &BotView, rand: &mut dyn RngCore) -> Orders;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Personality {
+    #[default]
+    Balanced,
+    Expansionist,
+    Aggressive,
+    Turtling,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [
+        Personality::Balanced,
+        Personality::Expansionist,
+        Personality::Aggressive,
+        Personality::Turtling,
+    ];
+
+    pub fn strategy(&self) -> &'static dyn BotStrategy {
+        match self {
+            Personality::Balanced => &personalities::Balanced,
+            Personality::Expansionist => &personalities::Expansionist,
+            Personality::Aggressive => &personalities::Aggressive,
+            Personality::Turtling => &personalities::Turtling,
+        }
+    }
+
+    pub fn next(&self) -> Personality {
+        let index = Personality::ALL.iter().position(|p| p == self).unwrap();
+        Personality::ALL[(index + 1) % Personality::ALL.len()]
+    }
+}
+
+impl fmt::Display for Personality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Personality::Balanced => "Balanced",
+                Personality::Expansionist => "Expansionist",
+                Personality::Aggressive => "Aggressive",
+                Personality::Turtling => "Turtling",
+            }
+        )
+    }
+}
+
+// also plays the bots of a tournament
+pub fn bot_orders(
+    universe: &Universe,
+    states: &[FleetState],
+    player: usize,
+    turn: u32,
+    last_colony_ship_spawned: &mut u32,
+    rand: &mut dyn RngCore,
+) -> Orders {
+    let view = BotView::new(universe, states, player, turn, *last_colony_ship_spawned);
+    let orders = universe.players[player]
+        .personality
+        .strategy()
+        .decide(&view, rand);
+    if orders
+        .builds
+        .iter()
+        .any(|build| build.kind == ShipKind::Colony)
+    {
+        *last_colony_ship_spawned = turn;
+    }
+    orders
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_bots_turn(
+    mut commands: Commands,
+    mut status: ResMut<BotTurnStatus>,
+    mut universe: ResMut<Universe>,
+    mut state: ResMut<State<TurnState>>,
+    mut fleets_to_spawn: ResMut<FleetsToSpawn>,
+    mut fleets: FleetQuery,
+    turns: Res<Turns>,
+    mut received: ResMut<ReceivedOrders>,
+    mut waiting: Local<Option<(u32, usize, Instant)>>,
+) {
+    let current_bot = status.current;
+    let mut states = fleet_states(&fleets);
+
+    let orders = match universe.players[current_bot].controller {
+        // human players already gave their orders
+        Controller::Human => None,
+        Controller::Bot => Some(bot_orders(
+            &universe,
+            &states,
+            current_bot,
+            turns.count,
+            &mut status.last_colony_ship_spawned[current_bot],
+            &mut rand::thread_rng(),
+        )),
+        Controller::Remote | Controller::External => match received.orders.remove(&current_bot) {
+            Some((turn, orders)) if turn == turns.count => Some(orders),
+            _ if received.absent.contains(&current_bot) => Some(bot_orders(
+                &universe,
+                &states,
+                current_bot,
+                turns.count,
+                &mut status.last_colony_ship_spawned[current_bot],
+                &mut rand::thread_rng(),
+            )),
+            _ => {
+                // wait for the orders to arrive, for a while
+                let started = match *waiting {
+                    Some((turn, player, started))
+                        if turn == turns.count && player == current_bot =>
+                    {
+                        started
+                    }
+                    _ => {
+                        let started = Instant::now();
+                        *waiting = Some((turns.count, current_bot, started));
+                        started
+                    }
+                };
+                let timeout = match universe.players[current_bot].controller {
+                    Controller::Remote => REMOTE_TIMEOUT,
+                    _ => EXTERNAL_TIMEOUT,
+                };
+                if started.elapsed() < timeout {
+                    return;
+                }
+                warn!(
+                    "player {} didn't give orders in time, a bot plays their turn",
+                    current_bot
+                );
+                Some(bot_orders(
+                    &universe,
+                    &states,
+                    current_bot,
+                    turns.count,
+                    &mut status.last_colony_ship_spawned[current_bot],
+                    &mut rand::thread_rng(),
+                ))
+            }
+        },
+    };
+
+    if let Some(orders) = orders {
+        let (spawned, rejected) =
+            simulation::apply_orders(&mut universe, &mut states, current_bot, &orders);
+        for error in rejected {
+            warn!("order from player {} rejected: {}", current_bot, error);
+        }
+        // merged into other fleets
+        for (entity, ..) in &fleets {
+            if !states.iter().any(|state| state.id == entity.to_bits()) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        update_fleets(&mut fleets, &states);
+        fleets_to_spawn.0.extend(spawned);
+    }
+
+    status.current += 1;
+    if status.current == universe.pirates {
+        let _ = state.set(TurnState::Enemy);
+    }
+}
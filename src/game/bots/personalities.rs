@@ -0,0 +1,544 @@
+use rand::{seq::IteratorRandom, Rng, RngCore};
+
+use crate::game::{
+    fleet::{turns_between, FleetSize, Order, ShipKind},
+    galaxy::Star,
+    research::Tech,
+    simulation::{Build, FleetState, Orders},
+    StarState, Universe,
+};
+
+use super::{BotStrategy, BotView};
+
+fn rate_star_colony(
+    player: usize,
+    good: &Star,
+    turns: u32,
+    state: &StarState,
+    rating: &Star,
+) -> u32 {
+    turns
+        + match state {
+            StarState::Owned(i) if *i == player => 500,
+            StarState::Owned(_) => 500000,
+            StarState::Unknown => 100,
+            StarState::Uninhabited => {
+                let color = if good.color != rating.color { 15 } else { 0 };
+                let size = if good.size != rating.size { 15 } else { 0 };
+                color + size
+            }
+        }
+}
+
+fn rate_star_fighter(player: usize, turns: u32, fleet_size: &FleetSize, state: &StarState) -> u32 {
+    turns
+        + match state {
+            StarState::Owned(i) if *i == player => 500,
+            StarState::Owned(_) => {
+                if fleet_size.0 < 10 {
+                    500000
+                } else {
+                    50
+                }
+            }
+            StarState::Unknown => 50,
+            StarState::Uninhabited => 100,
+        }
+}
+
+// scouts only look for what hasn't been seen yet
+fn rate_star_scout(turns: u32, state: &StarState) -> Option<u32> {
+    matches!(state, StarState::Unknown).then_some(turns)
+}
+
+fn rate_star_bomber(player: usize, turns: u32, fleet_size: &FleetSize, state: &StarState) -> u32 {
+    turns
+        + match state {
+            StarState::Owned(i) if *i == player => 500,
+            StarState::Owned(_) => {
+                if fleet_size.0 < 5 {
+                    500000
+                } else {
+                    20
+                }
+            }
+            StarState::Unknown => 300,
+            StarState::Uninhabited => 400,
+        }
+}
+
+// transports wait until there are enough of them to hold an average colony
+fn rate_star_troops(
+    player: usize,
+    turns: u32,
+    fleet_size: &FleetSize,
+    state: &StarState,
+) -> Option<u32> {
+    match state {
+        StarState::Owned(i) if *i != player && fleet_size.0 >= 4 => Some(turns),
+        _ => None,
+    }
+}
+
+// the ships every personality uses the same way
+fn rate_star_support(
+    player: usize,
+    fleet: &FleetState,
+    turns: u32,
+    state: &StarState,
+) -> Option<u32> {
+    let fleet_size = FleetSize(fleet.size);
+    match fleet.kind {
+        ShipKind::Scout => rate_star_scout(turns, state),
+        ShipKind::Bomber => Some(rate_star_bomber(player, turns, &fleet_size, state)),
+        ShipKind::Troops => rate_star_troops(player, turns, &fleet_size, state),
+        ShipKind::Colony | ShipKind::Fighter | ShipKind::Platform => None,
+    }
+}
+
+// send every fleet orbiting a star to the best rated one, lowest is best
+fn move_fleets(
+    view: &BotView,
+    orders: &mut Orders,
+    // given the turns needed to reach the star
+    rate: impl Fn(&FleetState, u32, &StarState, &Star) -> Option<u32>,
+) {
+    let universe = &view.universe;
+    for fleet in &view.fleets {
+        let Order::Orbit(n) = fleet.order else {
+            continue;
+        };
+        if fleet.kind.is_stationary() {
+            continue;
+        }
+        let current_position = universe.galaxy[n].position;
+        let speed = universe.speed(view.player, fleet.kind);
+        let best = universe
+            .galaxy
+            .iter()
+            .zip(universe.players[view.player].vision.iter())
+            .enumerate()
+            .filter(|(i, _)| *i != n)
+            .filter_map(|(index, (star, state))| {
+                let turns = turns_between(current_position, star.position, speed);
+                rate(fleet, turns, state, star).map(|rating| (index, rating))
+            })
+            .min_by_key(|(_, rating)| *rating);
+        if let Some((to, _)) = best {
+            orders.moves.push((fleet.id, to));
+        }
+    }
+}
+
+// credits and resources left to spend this turn
+struct Budget<'a> {
+    savings: f32,
+    resources: f32,
+    universe: &'a Universe,
+    player: usize,
+}
+
+impl<'a> Budget<'a> {
+    fn new(view: &'a BotView) -> Self {
+        let player = &view.universe.players[view.player];
+        Budget {
+            savings: player.savings,
+            resources: player.resources,
+            universe: &view.universe,
+            player: view.player,
+        }
+    }
+
+    fn can_buy(&self, kind: ShipKind, size: u32) -> bool {
+        let price = self.universe.price(self.player, kind, size);
+        self.universe.can_build(self.player, kind)
+            && self.savings > price.credits
+            && self.resources > price.resources
+    }
+
+    fn buy(&mut self, orders: &mut Orders, star: usize, kind: ShipKind, size: u32) {
+        let price = self.universe.price(self.player, kind, size);
+        self.savings -= price.credits;
+        self.resources -= price.resources;
+        orders.builds.push(Build { star, kind, size });
+    }
+}
+
+// colonies with a long queue would build new ships too late
+fn random_colony(view: &BotView, rand: &mut dyn RngCore) -> Option<usize> {
+    view.universe
+        .star_details
+        .iter()
+        .enumerate()
+        .filter(|(i, details)| details.owner == view.player && view.universe.queue(*i).len() < 3)
+        .map(|(i, _)| i)
+        .choose(rand)
+}
+
+fn can_colonize(view: &BotView) -> bool {
+    view.universe.players[view.player]
+        .vision
+        .iter()
+        .any(|state| !matches!(state, StarState::Owned(_)))
+}
+
+fn can_explore(view: &BotView) -> bool {
+    view.universe.players[view.player]
+        .vision
+        .contains(&StarState::Unknown)
+}
+
+// ships still in a queue count, they will be there soon
+fn ships_of(view: &BotView, kind: ShipKind) -> u32 {
+    view.fleets
+        .iter()
+        .filter(|fleet| fleet.kind == kind)
+        .map(|fleet| fleet.size)
+        .sum::<u32>()
+        + (0..view.universe.galaxy.len())
+            .flat_map(|star| view.universe.queue(star))
+            .filter(|item| item.owner == view.player && item.kind == kind)
+            .map(|item| item.size)
+            .sum::<u32>()
+}
+
+// a colony without a defense platform yet, built or ordered
+fn undefended_colony(view: &BotView) -> Option<usize> {
+    view.universe
+        .star_details
+        .iter()
+        .enumerate()
+        .filter(|(_, details)| details.owner == view.player)
+        .map(|(i, _)| i)
+        .find(|star| {
+            !view
+                .fleets
+                .iter()
+                .any(|fleet| fleet.kind == ShipKind::Platform && fleet.order == Order::Orbit(*star))
+                && !view
+                    .universe
+                    .queue(*star)
+                    .iter()
+                    .any(|item| item.kind == ShipKind::Platform)
+        })
+}
+
+// a colony with ships of other players around it
+fn threatened_colony(view: &BotView) -> Option<usize> {
+    view.others
+        .iter()
+        .filter_map(|fleet| fleet.order.waiting_at())
+        .find(|star| view.universe.star_details[*star].owner == view.player)
+}
+
+// picks the next tech in the order the personality prefers, funded with a share of the revenue
+fn research(view: &BotView, orders: &mut Orders, preferred: &[Tech], share: f32) {
+    let universe = &view.universe;
+    if universe.research(view.player).current.is_none() {
+        orders.research = preferred
+            .iter()
+            .chain(Tech::ALL.iter())
+            .find(|tech| universe.can_research(view.player, **tech))
+            .copied();
+    }
+    orders.funding = Some((universe.player_revenue(view.player) * share).max(0.0));
+}
+
+fn fighters_for_turn(turn: u32, factor: f32, rand: &mut dyn RngCore) -> u32 {
+    rand.gen_range(1..(((turn as f32).ln() * factor) as u32 + 2))
+}
+
+// the original bot, expanding while building fighters to explore and attack
+pub struct Balanced;
+
+impl BotStrategy for Balanced {
+    fn decide(&self, view: &BotView, rand: &mut dyn RngCore) -> Orders {
+        let mut orders = Orders::default();
+        let player = view.player;
+        let starting_star = &view.universe.galaxy[view.universe.players[player].start];
+
+        move_fleets(view, &mut orders, |fleet, turns, state, star| {
+            match fleet.kind {
+                ShipKind::Colony => {
+                    Some(rate_star_colony(player, starting_star, turns, state, star))
+                }
+                ShipKind::Fighter => Some(rate_star_fighter(
+                    player,
+                    turns,
+                    &FleetSize(fleet.size),
+                    state,
+                )),
+                _ => rate_star_support(player, fleet, turns, state),
+            }
+        });
+
+        research(
+            view,
+            &mut orders,
+            &[
+                Tech::Scouting,
+                Tech::Weapons,
+                Tech::Bombers,
+                Tech::Propulsion,
+            ],
+            0.3,
+        );
+
+        let Some(star) = random_colony(view, rand) else {
+            // nowhere left to build ships
+            return orders;
+        };
+
+        let mut budget = Budget::new(view);
+        // enough revenue to create a new colony
+        // enough credits & resources to build a colony ship
+        // didn't create one very recently
+        // there is an not owned star available
+        if view.universe.player_revenue(player) > 2.0
+            && budget.can_buy(ShipKind::Colony, 1)
+            && view.turn - view.last_colony_ship_spawned > 2
+            && can_colonize(view)
+        {
+            budget.buy(&mut orders, star, ShipKind::Colony, 1);
+        }
+
+        let nb_fighter = fighters_for_turn(view.turn, 10.0, rand);
+        if budget.can_buy(ShipKind::Fighter, nb_fighter) {
+            let star = random_colony(view, rand).unwrap_or(star);
+            budget.buy(&mut orders, star, ShipKind::Fighter, nb_fighter);
+        }
+
+        if can_explore(view)
+            && ships_of(view, ShipKind::Scout) == 0
+            && budget.can_buy(ShipKind::Scout, 1)
+        {
+            budget.buy(&mut orders, star, ShipKind::Scout, 1);
+        }
+        // a few bombers once the game is settled
+        if view.turn > 30 && rand.gen_bool(0.2) {
+            let nb_bomber = fighters_for_turn(view.turn, 2.0, rand);
+            if budget.can_buy(ShipKind::Bomber, nb_bomber) {
+                budget.buy(&mut orders, star, ShipKind::Bomber, nb_bomber);
+            }
+        }
+
+        orders
+    }
+}
+
+// colonizes as fast as possible, fighters are mostly scouts
+pub struct Expansionist;
+
+impl BotStrategy for Expansionist {
+    fn decide(&self, view: &BotView, rand: &mut dyn RngCore) -> Orders {
+        let mut orders = Orders::default();
+        let player = view.player;
+        let starting_star = &view.universe.galaxy[view.universe.players[player].start];
+
+        move_fleets(view, &mut orders, |fleet, turns, state, star| {
+            match (fleet.kind, state) {
+                (ShipKind::Colony, _) => {
+                    Some(rate_star_colony(player, starting_star, turns, state, star))
+                }
+                // explore to find more stars to colonize
+                (ShipKind::Fighter, StarState::Unknown) => Some(turns),
+                (ShipKind::Fighter, _) => Some(rate_star_fighter(
+                    player,
+                    turns,
+                    &FleetSize(fleet.size),
+                    state,
+                )),
+                _ => rate_star_support(player, fleet, turns, state),
+            }
+        });
+
+        research(
+            view,
+            &mut orders,
+            &[
+                Tech::Scouting,
+                Tech::Adaptation,
+                Tech::Propulsion,
+                Tech::Logistics,
+            ],
+            0.2,
+        );
+
+        let Some(star) = random_colony(view, rand) else {
+            return orders;
+        };
+
+        let mut budget = Budget::new(view);
+        if view.universe.player_revenue(player) > 0.5
+            && view.turn - view.last_colony_ship_spawned > 1
+            && can_colonize(view)
+        {
+            for _ in 0..2 {
+                if budget.can_buy(ShipKind::Colony, 1) {
+                    let star = random_colony(view, rand).unwrap_or(star);
+                    budget.buy(&mut orders, star, ShipKind::Colony, 1);
+                }
+            }
+        }
+
+        // cheap scouts find the next stars to settle
+        if can_explore(view)
+            && ships_of(view, ShipKind::Scout) < 3
+            && budget.can_buy(ShipKind::Scout, 1)
+        {
+            budget.buy(&mut orders, star, ShipKind::Scout, 1);
+        }
+
+        let nb_fighter = fighters_for_turn(view.turn, 3.0, rand);
+        if budget.can_buy(ShipKind::Fighter, nb_fighter) {
+            budget.buy(&mut orders, star, ShipKind::Fighter, nb_fighter);
+        }
+
+        orders
+    }
+}
+
+// builds large fleets and goes after the colonies of other players
+pub struct Aggressive;
+
+impl BotStrategy for Aggressive {
+    fn decide(&self, view: &BotView, rand: &mut dyn RngCore) -> Orders {
+        let mut orders = Orders::default();
+        let player = view.player;
+        let starting_star = &view.universe.galaxy[view.universe.players[player].start];
+
+        move_fleets(view, &mut orders, |fleet, turns, state, star| {
+            match (fleet.kind, state) {
+                (ShipKind::Colony, _) => {
+                    Some(rate_star_colony(player, starting_star, turns, state, star))
+                }
+                (ShipKind::Fighter, StarState::Owned(i)) if *i == player => Some(turns + 500),
+                (ShipKind::Fighter, StarState::Owned(_)) if fleet.size >= 5 => Some(turns),
+                (ShipKind::Fighter, StarState::Owned(_)) => Some(turns + 500000),
+                (ShipKind::Fighter, StarState::Unknown) => Some(turns + 50),
+                (ShipKind::Fighter, StarState::Uninhabited) => Some(turns + 150),
+                _ => rate_star_support(player, fleet, turns, state),
+            }
+        });
+
+        research(
+            view,
+            &mut orders,
+            &[
+                Tech::Weapons,
+                Tech::Bombers,
+                Tech::Invasion,
+                Tech::Propulsion,
+            ],
+            0.3,
+        );
+
+        let Some(star) = random_colony(view, rand) else {
+            return orders;
+        };
+
+        let mut budget = Budget::new(view);
+        if view.universe.player_revenue(player) > 3.0
+            && budget.can_buy(ShipKind::Colony, 1)
+            && view.turn - view.last_colony_ship_spawned > 5
+            && can_colonize(view)
+        {
+            budget.buy(&mut orders, star, ShipKind::Colony, 1);
+        }
+
+        // one big fleet rather than many small ones
+        let mut nb_fighter = fighters_for_turn(view.turn, 15.0, rand);
+        while nb_fighter > 0 && !budget.can_buy(ShipKind::Fighter, nb_fighter) {
+            nb_fighter /= 2;
+        }
+        if nb_fighter > 0 {
+            budget.buy(&mut orders, star, ShipKind::Fighter, nb_fighter);
+        }
+
+        // bombers soften colonies up, then transports take them
+        if view.turn > 20 {
+            let nb_bomber = nb_fighter / 3;
+            if nb_bomber > 0 && budget.can_buy(ShipKind::Bomber, nb_bomber) {
+                budget.buy(&mut orders, star, ShipKind::Bomber, nb_bomber);
+            }
+            if rand.gen_bool(0.3) && budget.can_buy(ShipKind::Troops, 4) {
+                budget.buy(&mut orders, star, ShipKind::Troops, 4);
+            }
+        }
+
+        orders
+    }
+}
+
+// stays close to home and keeps its fighters to defend its colonies
+pub struct Turtling;
+
+impl BotStrategy for Turtling {
+    fn decide(&self, view: &BotView, rand: &mut dyn RngCore) -> Orders {
+        let mut orders = Orders::default();
+        let player = view.player;
+        let starting_star = &view.universe.galaxy[view.universe.players[player].start];
+
+        move_fleets(view, &mut orders, |fleet, turns, state, star| {
+            match fleet.kind {
+                // only settle nearby stars
+                ShipKind::Colony => Some(
+                    rate_star_colony(player, starting_star, turns, state, star)
+                        + turns_between(
+                            starting_star.position,
+                            star.position,
+                            view.universe.speed(player, fleet.kind),
+                        ) * 5,
+                ),
+                // only a very large fleet leaves its post
+                ShipKind::Fighter if fleet.size >= 30 => Some(rate_star_fighter(
+                    player,
+                    turns,
+                    &FleetSize(fleet.size),
+                    state,
+                )),
+                ShipKind::Fighter => None,
+                _ => rate_star_support(player, fleet, turns, state),
+            }
+        });
+
+        research(
+            view,
+            &mut orders,
+            &[Tech::Fortification, Tech::Weapons, Tech::Adaptation],
+            0.4,
+        );
+
+        let Some(star) = random_colony(view, rand) else {
+            return orders;
+        };
+
+        let mut budget = Budget::new(view);
+        // keep some credits aside for bad days
+        budget.savings -= 20.0 * view.universe.difficulty;
+        if view.universe.player_revenue(player) > 2.0
+            && budget.can_buy(ShipKind::Colony, 1)
+            && view.turn - view.last_colony_ship_spawned > 4
+            && can_colonize(view)
+        {
+            budget.buy(&mut orders, star, ShipKind::Colony, 1);
+        }
+
+        // every colony gets a platform before more fighters
+        match undefended_colony(view) {
+            Some(colony) if budget.can_buy(ShipKind::Platform, 1) => {
+                budget.buy(&mut orders, colony, ShipKind::Platform, 1)
+            }
+            _ => (),
+        }
+
+        // reinforcements go where enemies were seen
+        let nb_fighter = fighters_for_turn(view.turn, 5.0, rand);
+        if budget.can_buy(ShipKind::Fighter, nb_fighter) {
+            let star = threatened_colony(view).unwrap_or(star);
+            budget.buy(&mut orders, star, ShipKind::Fighter, nb_fighter);
+        }
+
+        orders
+    }
+}
@@ -0,0 +1,316 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::fleet::ShipKind;
+
+pub const ROUNDS: u32 = 5;
+// the owner of the star fights from prepared positions
+const DEFENDER_BONUS: f32 = 1.5;
+// attack of a single inhabitant defending their colony
+const POPULATION_ATTACK: f32 = 0.02;
+// inhabitants killed by a point of damage, also how many make a target as large as a ship
+const POPULATION_PER_DAMAGE: f32 = 10.0;
+
+impl ShipKind {
+    pub fn attack(&self) -> f32 {
+        match self {
+            ShipKind::Colony | ShipKind::Scout | ShipKind::Troops => 0.0,
+            ShipKind::Fighter => 1.0,
+            ShipKind::Bomber => 0.3,
+            ShipKind::Platform => 1.5,
+        }
+    }
+
+    // damage dealt straight to the population of an enemy colony, whatever ships defend it
+    pub fn bombing(&self) -> f32 {
+        match self {
+            ShipKind::Bomber => 3.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn defense(&self) -> f32 {
+        match self {
+            ShipKind::Colony => 0.5,
+            ShipKind::Scout => 0.3,
+            ShipKind::Fighter => 1.0,
+            ShipKind::Bomber | ShipKind::Troops => 0.8,
+            ShipKind::Platform => 3.0,
+        }
+    }
+
+    // inhabitants a ship can hold down when landing on an enemy colony
+    pub fn troops(&self) -> f32 {
+        match self {
+            ShipKind::Troops => 25.0,
+            _ => 0.0,
+        }
+    }
+}
+
+pub struct Combatant {
+    pub fleet: u64,
+    pub owner: usize,
+    pub kind: ShipKind,
+    pub size: u32,
+    // from the techs of the owner
+    pub attack_modifier: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Side {
+    pub player: usize,
+    pub defender: bool,
+    pub ships: u32,
+    pub ships_lost: u32,
+    pub population: f32,
+    pub population_lost: f32,
+}
+
+#[derive(Debug)]
+pub struct BattleReport {
+    pub rounds: u32,
+    pub sides: Vec<Side>,
+    // ships left in each fleet once the battle is over
+    pub survivors: Vec<(u64, u32)>,
+    pub population: f32,
+}
+
+// every side fires at all the others at the same time, for a few rounds or until a single one
+// is left; no battle happens when nobody there can attack
+pub fn resolve(
+    owner: usize,
+    population: f32,
+    fleets: &[Combatant],
+    rand: &mut StdRng,
+) -> Option<BattleReport> {
+    let mut sides = fleets.iter().fold(vec![], |mut sides: Vec<Side>, fleet| {
+        match sides.iter_mut().find(|side| side.player == fleet.owner) {
+            Some(side) => side.ships += fleet.size,
+            None => sides.push(Side {
+                player: fleet.owner,
+                defender: fleet.owner == owner,
+                ships: fleet.size,
+                ships_lost: 0,
+                population: 0.0,
+                population_lost: 0.0,
+            }),
+        }
+        sides
+    });
+    if owner != usize::MAX && population > 0.0 {
+        match sides.iter_mut().find(|side| side.player == owner) {
+            Some(side) => side.population = population,
+            None => sides.push(Side {
+                player: owner,
+                defender: true,
+                ships: 0,
+                ships_lost: 0,
+                population,
+                population_lost: 0.0,
+            }),
+        }
+    }
+
+    let mut sizes = fleets.iter().map(|fleet| fleet.size).collect::<Vec<_>>();
+    let mut population = population;
+    let attack = |sizes: &[u32], population: f32, side: &Side| {
+        fleets
+            .iter()
+            .zip(sizes)
+            .filter(|(fleet, _)| fleet.owner == side.player)
+            .map(|(fleet, size)| fleet.kind.attack() * fleet.attack_modifier * *size as f32)
+            .sum::<f32>()
+            + if side.defender {
+                population * POPULATION_ATTACK
+            } else {
+                0.0
+            }
+    };
+    let bombing = |sizes: &[u32], side: &Side| {
+        fleets
+            .iter()
+            .zip(sizes)
+            .filter(|(fleet, _)| fleet.owner == side.player)
+            .map(|(fleet, size)| fleet.kind.bombing() * *size as f32)
+            .sum::<f32>()
+    };
+    let presence = |sizes: &[u32], population: f32, side: &Side| {
+        fleets
+            .iter()
+            .zip(sizes)
+            .filter(|(fleet, _)| fleet.owner == side.player)
+            .map(|(_, size)| *size as f32)
+            .sum::<f32>()
+            + if side.defender {
+                population / POPULATION_PER_DAMAGE
+            } else {
+                0.0
+            }
+    };
+
+    let mut rounds = 0;
+    while rounds < ROUNDS {
+        let standing = (0..sides.len())
+            .filter(|i| presence(&sizes, population, &sides[*i]) > 0.0)
+            .collect::<Vec<_>>();
+        if standing.len() < 2
+            || standing.iter().all(|i| {
+                attack(&sizes, population, &sides[*i]) + bombing(&sizes, &sides[*i]) == 0.0
+            })
+        {
+            break;
+        }
+
+        // damage is dealt by everyone before anything is destroyed
+        let mut damage = vec![0.0; sides.len()];
+        let mut bombed = vec![0.0; sides.len()];
+        for side in &standing {
+            let firepower = attack(&sizes, population, &sides[*side]) * rand.gen_range(0.5..1.5);
+            let targets = standing
+                .iter()
+                .filter(|enemy| *enemy != side)
+                .map(|enemy| presence(&sizes, population, &sides[*enemy]))
+                .sum::<f32>();
+            for enemy in standing.iter().filter(|enemy| *enemy != side) {
+                damage[*enemy] +=
+                    firepower * presence(&sizes, population, &sides[*enemy]) / targets;
+            }
+            if let Some(colony) = standing
+                .iter()
+                .find(|enemy| *enemy != side && sides[**enemy].defender && population > 0.0)
+            {
+                bombed[*colony] += bombing(&sizes, &sides[*side]) * rand.gen_range(0.5..1.5);
+            }
+        }
+
+        for ((side, damage), bombed) in sides.iter_mut().zip(damage).zip(bombed) {
+            let damage = if side.defender {
+                damage / DEFENDER_BONUS
+            } else {
+                damage
+            };
+            // ships shield the population of their colony until they are all gone
+            let hull = fleets
+                .iter()
+                .zip(&sizes)
+                .filter(|(fleet, _)| fleet.owner == side.player)
+                .map(|(fleet, size)| fleet.kind.defense() * *size as f32)
+                .sum::<f32>();
+            if hull > 0.0 {
+                let share = (damage / hull).min(1.0);
+                for (fleet, size) in fleets.iter().zip(sizes.iter_mut()) {
+                    if fleet.owner != side.player {
+                        continue;
+                    }
+                    let hits = *size as f32 * share;
+                    let lost = (hits.floor() as u32 + (rand.gen::<f32>() < hits.fract()) as u32)
+                        .min(*size);
+                    *size -= lost;
+                    side.ships_lost += lost;
+                }
+            }
+            if side.defender && (damage > hull || bombed > 0.0) {
+                let killed = (((damage - hull).max(0.0) + bombed / DEFENDER_BONUS)
+                    * POPULATION_PER_DAMAGE)
+                    .min(population);
+                population -= killed;
+                side.population_lost += killed;
+            }
+        }
+        rounds += 1;
+    }
+
+    if rounds == 0 {
+        return None;
+    }
+    Some(BattleReport {
+        rounds,
+        sides,
+        survivors: fleets
+            .iter()
+            .zip(sizes)
+            .map(|(fleet, size)| (fleet.fleet, size))
+            .collect(),
+        population,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn fighters(fleet: u64, owner: usize, size: u32) -> Combatant {
+        Combatant {
+            fleet,
+            owner,
+            kind: ShipKind::Fighter,
+            size,
+            attack_modifier: 1.0,
+        }
+    }
+
+    fn survivors(report: &BattleReport, owner: usize, fleets: &[Combatant]) -> u32 {
+        fleets
+            .iter()
+            .zip(&report.survivors)
+            .filter(|(fleet, _)| fleet.owner == owner)
+            .map(|(_, (_, size))| size)
+            .sum()
+    }
+
+    #[test]
+    fn same_seed_same_battle() {
+        let fleets = [fighters(0, 0, 12), fighters(1, 1, 15)];
+        let battle = |seed| {
+            let report = resolve(0, 30.0, &fleets, &mut StdRng::seed_from_u64(seed)).unwrap();
+            format!("{:?}", report)
+        };
+        assert_eq!(battle(42), battle(42));
+    }
+
+    #[test]
+    fn defender_bonus_tips_an_even_fight() {
+        let fleets = [fighters(0, 0, 20), fighters(1, 1, 20)];
+        // the same battle with the same dice, at a star of player 0 and at a star of nobody
+        let (mut at_home, mut neutral) = ((0, 0), (0, 0));
+        for seed in 0..50 {
+            let home = resolve(0, 0.0, &fleets, &mut StdRng::seed_from_u64(seed)).unwrap();
+            at_home.0 += survivors(&home, 0, &fleets);
+            at_home.1 += survivors(&home, 1, &fleets);
+            let away = resolve(usize::MAX, 0.0, &fleets, &mut StdRng::seed_from_u64(seed)).unwrap();
+            neutral.0 += survivors(&away, 0, &fleets);
+            neutral.1 += survivors(&away, 1, &fleets);
+        }
+        assert!(at_home.0 > neutral.0);
+        assert!(at_home.1 < neutral.1);
+        assert!(at_home.0 > at_home.1);
+    }
+
+    #[test]
+    fn population_alone_loses_to_fighters() {
+        let fleets = [fighters(0, 1, 20)];
+        let report = resolve(0, 50.0, &fleets, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(report.population, 0.0);
+        assert!(survivors(&report, 1, &fleets) > 0);
+        let colony = report.sides.iter().find(|side| side.player == 0).unwrap();
+        assert!(colony.defender);
+        assert_eq!(colony.population_lost, 50.0);
+    }
+
+    #[test]
+    fn survivors_keep_the_order_of_the_fleets() {
+        let fleets = [fighters(7, 0, 10), fighters(3, 1, 10), fighters(5, 0, 4)];
+        let report = resolve(usize::MAX, 0.0, &fleets, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(
+            report
+                .survivors
+                .iter()
+                .map(|(fleet, _)| *fleet)
+                .collect::<Vec<_>>(),
+            vec![7, 3, 5]
+        );
+    }
+}
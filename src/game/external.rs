@@ -0,0 +1,295 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+
+use super::{
+    bots::{run_bots_turn, BotTurnStatus, ReceivedOrders},
+    fleet::{fleet_states, FleetQuery, Order, ShipKind},
+    galaxy::{StarColor, StarSize},
+    research::Research,
+    simulation::{FleetState, Orders},
+    turns::{TurnState, Turns},
+    Controller, StarState, Universe,
+};
+
+// command line of the program playing external bot slots
+const BOT_COMMAND: &str = "SPACEVIATA_BOT";
+
+pub fn bot_command() -> Option<String> {
+    std::env::var(BOT_COMMAND)
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+}
+
+#[derive(Serialize)]
+struct VisibleStar<'a> {
+    index: usize,
+    position: Vec2,
+    // unknown stars are only a position
+    name: Option<&'a str>,
+    color: Option<StarColor>,
+    size: Option<StarSize>,
+    state: StarState,
+    // only known for colonies of the player
+    population: Option<f32>,
+    resources: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct VisibleFleet<'a> {
+    id: u64,
+    owner: usize,
+    kind: ShipKind,
+    size: u32,
+    order: &'a Order,
+}
+
+// sent each turn on a single line, the program answers with a `TurnReply` on a single line
+#[derive(Serialize)]
+struct TurnRequest<'a> {
+    turn: u32,
+    player: usize,
+    savings: f32,
+    resources: f32,
+    difficulty: f32,
+    research: &'a Research,
+    stars: Vec<VisibleStar<'a>>,
+    fleets: Vec<VisibleFleet<'a>>,
+}
+
+#[derive(Deserialize)]
+struct TurnReply {
+    // replies for another turn than the current one came too late and are dropped
+    turn: u32,
+    orders: Orders,
+}
+
+impl<'a> TurnRequest<'a> {
+    // from the galaxy and the fleets as seen by the player
+    fn new(universe: &'a Universe, fleets: &'a [FleetState], player: usize, turn: u32) -> Self {
+        let vision = &universe.players[player].vision;
+        TurnRequest {
+            turn,
+            player,
+            savings: universe.players[player].savings,
+            resources: universe.players[player].resources,
+            difficulty: universe.difficulty,
+            research: universe.research(player),
+            stars: universe
+                .galaxy
+                .iter()
+                .zip(vision.iter())
+                .zip(universe.star_details.iter())
+                .enumerate()
+                .map(|(index, ((star, state), details))| {
+                    let seen = *state != StarState::Unknown;
+                    let known = *state == StarState::Owned(player);
+                    VisibleStar {
+                        index,
+                        position: star.position,
+                        name: seen.then_some(star.name.as_str()),
+                        color: seen.then_some(star.color),
+                        size: seen.then_some(star.size),
+                        state: *state,
+                        population: known.then_some(details.population),
+                        resources: known.then_some(details.resources),
+                    }
+                })
+                .collect(),
+            fleets: fleets
+                .iter()
+                .map(|fleet| VisibleFleet {
+                    id: fleet.id,
+                    owner: fleet.owner,
+                    kind: fleet.kind,
+                    size: fleet.size,
+                    order: &fleet.order,
+                })
+                .collect(),
+        }
+    }
+}
+
+struct ExternalBot {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// one program per external bot slot
+#[derive(Resource)]
+pub struct ExternalBots {
+    bots: HashMap<usize, ExternalBot>,
+    asked: Option<(u32, usize)>,
+    incoming: Mutex<Receiver<(usize, Option<TurnReply>)>>,
+}
+
+impl ExternalBots {
+    // players whose program couldn't be started are left out
+    pub fn start(command: &str, players: &[usize]) -> (Self, Vec<usize>) {
+        let (sender, incoming) = channel();
+        let mut bots = HashMap::default();
+        let mut failed = vec![];
+        for player in players {
+            match spawn(command, *player, sender.clone()) {
+                Ok(bot) => {
+                    bots.insert(*player, bot);
+                }
+                Err(error) => {
+                    warn!("error starting bot for player {}: {}", player, error);
+                    failed.push(*player);
+                }
+            }
+        }
+        (
+            ExternalBots {
+                bots,
+                asked: None,
+                incoming: Mutex::new(incoming),
+            },
+            failed,
+        )
+    }
+}
+
+fn spawn(
+    command: &str,
+    player: usize,
+    sender: Sender<(usize, Option<TurnReply>)>,
+) -> anyhow::Result<ExternalBot> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("empty command"))?;
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    let stdin = child.stdin.take().unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    thread::spawn(move || {
+        for line in stdout.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match serde_json::from_str(&line) {
+                Ok(reply) => {
+                    let _ = sender.send((player, Some(reply)));
+                }
+                Err(error) => {
+                    warn!("invalid orders from bot {}: {}", player, error);
+                    break;
+                }
+            }
+        }
+        let _ = sender.send((player, None));
+    });
+    Ok(ExternalBot { child, stdin })
+}
+
+pub struct Plugin;
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(TurnState::Bots).with_system(ask_bot.before(run_bots_turn)),
+        )
+        .add_system_set(SystemSet::on_update(GameState::Game).with_system(receive_orders))
+        .add_system_set(SystemSet::on_exit(GameState::Game).with_system(tear_down));
+    }
+}
+
+fn tear_down(mut commands: Commands) {
+    commands.remove_resource::<ExternalBots>();
+}
+
+// bots play one after the other, each one gets its view when its turn comes
+fn ask_bot(
+    bots: Option<ResMut<ExternalBots>>,
+    status: Res<BotTurnStatus>,
+    mut universe: ResMut<Universe>,
+    turns: Res<Turns>,
+    fleets: FleetQuery,
+) {
+    let Some(mut bots) = bots else {
+        return;
+    };
+    let player = status.current;
+    if universe.players.get(player).map(|player| player.controller) != Some(Controller::External)
+        || bots.asked == Some((turns.count, player))
+    {
+        return;
+    }
+    bots.asked = Some((turns.count, player));
+
+    let seen = universe.seen_by(player);
+    let visible = universe.fleets_seen_by(&fleet_states(&fleets), player);
+    let request = TurnRequest::new(&seen, &visible, player, turns.count);
+    let sent = serde_json::to_string(&request)
+        .map_err(anyhow::Error::from)
+        .and_then(|line| {
+            let bot = bots
+                .bots
+                .get_mut(&player)
+                .ok_or_else(|| anyhow::anyhow!("not running"))?;
+            writeln!(bot.stdin, "{}", line)?;
+            bot.stdin.flush()?;
+            Ok(())
+        });
+    if let Err(error) = sent {
+        warn!(
+            "error sending turn to bot {}, playing it instead: {}",
+            player, error
+        );
+        bots.bots.remove(&player);
+        universe.players[player].controller = Controller::Bot;
+    }
+}
+
+fn receive_orders(
+    bots: Option<ResMut<ExternalBots>>,
+    mut received: ResMut<ReceivedOrders>,
+    mut universe: ResMut<Universe>,
+    turns: Res<Turns>,
+) {
+    let Some(mut bots) = bots else {
+        return;
+    };
+    let messages = bots.incoming.lock().unwrap().try_iter().collect::<Vec<_>>();
+    for (player, reply) in messages {
+        match reply {
+            Some(reply) if reply.turn != turns.count => {
+                warn!(
+                    "dropping orders from bot {} for turn {}, it's turn {}",
+                    player, reply.turn, turns.count
+                );
+            }
+            Some(reply) => {
+                received.orders.insert(player, (reply.turn, reply.orders));
+            }
+            None => {
+                warn!("bot {} stopped, playing it instead", player);
+                bots.bots.remove(&player);
+                universe.players[player].controller = Controller::Bot;
+            }
+        }
+    }
+}
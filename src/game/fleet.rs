@@ -1,24 +1,208 @@
 use std::{f32::consts::PI, fmt};
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{assets::loader::ShipAssets, GameState};
 
 use super::{
+    galaxy::Star,
+    simulation::FleetState,
+    turns::CurrentPlayer,
+    ui::SelectedStar,
     world::{CameraController, RATIO_ZOOM_DISTANCE},
     z_levels, FleetsToSpawn, Universe,
 };
 
-#[derive(Component)]
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Order {
     Orbit(usize),
-    Move { from: usize, to: usize, step: u32 },
+    Move {
+        from: usize,
+        to: usize,
+        step: u32,
+        // where to go next once arrived, in order
+        waypoints: Vec<usize>,
+        // set when turned around in flight, the leg then starts from there instead of `from`
+        departure: Option<Vec2>,
+    },
+}
+
+impl Order {
+    // every star of the route, starting from where the fleet is or comes from
+    pub fn route(&self) -> Vec<usize> {
+        match self {
+            Order::Orbit(around) => vec![*around],
+            Order::Move {
+                from,
+                to,
+                waypoints,
+                ..
+            } => [*from, *to]
+                .into_iter()
+                .chain(waypoints.iter().copied())
+                .collect(),
+        }
+    }
+
+    pub fn last_stop(&self) -> usize {
+        *self.route().last().unwrap()
+    }
+
+    // adds a leg at the end of the route
+    pub fn then(self, star: usize) -> Order {
+        if self.last_stop() == star {
+            return self;
+        }
+        match self {
+            Order::Orbit(from) => Order::Move {
+                from,
+                to: star,
+                step: 0,
+                waypoints: vec![],
+                departure: None,
+            },
+            Order::Move {
+                from,
+                to,
+                step,
+                mut waypoints,
+                departure,
+            } => {
+                waypoints.push(star);
+                Order::Move {
+                    from,
+                    to,
+                    step,
+                    waypoints,
+                    departure,
+                }
+            }
+        }
+    }
+
+    // a fleet that left its star, and can only be turned around
+    pub fn in_flight(&self) -> bool {
+        match self {
+            Order::Orbit(_) => false,
+            Order::Move {
+                step, departure, ..
+            } => *step > 0 || departure.is_some(),
+        }
+    }
+
+    // the star the fleet is still at, even if ordered to leave it this turn
+    pub fn waiting_at(&self) -> Option<usize> {
+        match self {
+            Order::Orbit(around) => Some(*around),
+            Order::Move { from, .. } if !self.in_flight() => Some(*from),
+            Order::Move { .. } => None,
+        }
+    }
+
+    pub fn departure(&self, galaxy: &[Star]) -> Vec2 {
+        match self {
+            Order::Orbit(around) => galaxy[*around].position,
+            Order::Move {
+                from, departure, ..
+            } => departure.unwrap_or(galaxy[*from].position),
+        }
+    }
+
+    // turns needed for the current leg, for ships going at `speed`
+    pub fn leg_turns(&self, galaxy: &[Star], speed: f32) -> u32 {
+        match self {
+            Order::Orbit(_) => 0,
+            Order::Move { to, .. } => {
+                turns_between(self.departure(galaxy), galaxy[*to].position, speed)
+            }
+        }
+    }
+
+    pub fn position(&self, galaxy: &[Star], speed: f32) -> Vec2 {
+        match self {
+            Order::Orbit(around) => galaxy[*around].position,
+            Order::Move { to, step, .. } => self.departure(galaxy).lerp(
+                galaxy[*to].position,
+                (*step as f32 / self.leg_turns(galaxy, speed) as f32).min(1.0),
+            ),
+        }
+    }
+
+    // heads to another star from where the fleet is now, dropping the rest of its route
+    pub fn redirect(&self, star: usize, galaxy: &[Star], speed: f32) -> Order {
+        match self {
+            Order::Orbit(_) => self.clone().then(star),
+            Order::Move { from, .. } if !self.in_flight() => Order::Orbit(*from).then(star),
+            Order::Move { from, .. } => Order::Move {
+                from: *from,
+                to: star,
+                step: 0,
+                waypoints: vec![],
+                departure: Some(self.position(galaxy, speed)),
+            },
+        }
+    }
+
+    // turns left before the end of the route
+    pub fn eta(&self, galaxy: &[Star], speed: f32) -> u32 {
+        let step = match self {
+            Order::Orbit(_) => 0,
+            Order::Move { step, .. } => *step,
+        };
+        (self.leg_turns(galaxy, speed)
+            + self.route()[1..]
+                .windows(2)
+                .map(|leg| turns_between(galaxy[leg[0]].position, galaxy[leg[1]].position, speed))
+                .sum::<u32>())
+        .saturating_sub(step)
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ShipKind {
     Colony,
     Fighter,
+    Scout,
+    Bomber,
+    Troops,
+    Platform,
+}
+
+impl ShipKind {
+    pub const ALL: [ShipKind; 6] = [
+        ShipKind::Colony,
+        ShipKind::Fighter,
+        ShipKind::Scout,
+        ShipKind::Bomber,
+        ShipKind::Troops,
+        ShipKind::Platform,
+    ];
+
+    // defense platforms are built around a star and never leave it
+    pub fn is_stationary(&self) -> bool {
+        *self == ShipKind::Platform
+    }
+
+    // relative to a fighter
+    pub fn speed(&self) -> f32 {
+        match self {
+            ShipKind::Colony => 0.7,
+            ShipKind::Fighter => 1.0,
+            ShipKind::Scout => 1.6,
+            ShipKind::Bomber => 0.9,
+            ShipKind::Troops => 0.8,
+            // never used to travel
+            ShipKind::Platform => 0.5,
+        }
+    }
+}
+
+impl Universe {
+    // with propulsion, once researched
+    pub fn speed(&self, player: usize, kind: ShipKind) -> f32 {
+        kind.speed() * self.speed_modifier(player)
+    }
 }
 
 #[derive(Clone, Copy, Component)]
@@ -32,7 +216,32 @@ pub struct FleetSize(pub u32);
 #[derive(Clone, Copy, Component)]
 pub struct Owner(pub usize);
 
-#[derive(Bundle)]
+// the id of a fleet in a game run by a host on another computer
+#[derive(Component)]
+pub struct RemoteFleet(pub u64);
+
+// split during this turn from a fleet the host knows, not yet known by the host itself
+#[derive(Component)]
+pub struct SplitFrom(pub u64);
+
+// ships moved between fleets known by the host during this turn: from, into, count
+#[derive(Resource, Default)]
+pub struct PendingTransfers(pub Vec<(u64, u64, u32)>);
+
+pub enum Regroup {
+    // ships leaving a fleet as a new one with its own order
+    Split {
+        fleet: Entity,
+        size: u32,
+        order: Order,
+    },
+    Merge {
+        fleet: Entity,
+        into: Entity,
+    },
+}
+
+#[derive(Bundle, Clone)]
 pub struct Fleet {
     pub order: Order,
     pub ship: Ship,
@@ -60,23 +269,51 @@ impl fmt::Display for ShipKind {
             match self {
                 ShipKind::Colony => "Colony Ship",
                 ShipKind::Fighter => "Fighter",
+                ShipKind::Scout => "Scout",
+                ShipKind::Bomber => "Bomber",
+                ShipKind::Troops => "Troop Transport",
+                ShipKind::Platform => "Defense Platform",
             }
         )
     }
 }
 
-impl ShipKind {
-    pub fn cost_credits(&self) -> f32 {
-        match self {
-            ShipKind::Colony => 10.0,
-            ShipKind::Fighter => 3.0,
-        }
-    }
+pub type FleetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Order,
+        &'static Ship,
+        &'static Owner,
+        &'static mut FleetSize,
+    ),
+>;
 
-    pub fn cost_resources(&self) -> f32 {
-        match self {
-            ShipKind::Colony => 5.0,
-            ShipKind::Fighter => 7.0,
+pub fn fleet_states(fleets: &FleetQuery) -> Vec<FleetState> {
+    fleets
+        .iter()
+        .map(|(entity, order, ship, owner, size)| FleetState {
+            id: entity.to_bits(),
+            order: order.clone(),
+            kind: ship.kind,
+            size: size.0,
+            owner: owner.0,
+        })
+        .collect()
+}
+
+// write back the simulation result, only touching what changed so that
+// fleets are not moved around for nothing
+pub fn update_fleets(fleets: &mut FleetQuery, states: &[FleetState]) {
+    for state in states {
+        if let Ok((_, mut order, _, _, mut size)) = fleets.get_mut(Entity::from_bits(state.id)) {
+            if *order != state.order {
+                *order = state.order.clone();
+            }
+            if size.0 != state.size {
+                size.0 = state.size;
+            }
         }
     }
 }
@@ -89,12 +326,15 @@ struct ScreenTag;
 pub struct Plugin;
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
-            SystemSet::on_update(CURRENT_STATE)
-                .with_system(spawn_fleets)
-                .with_system(place_fleets),
-        )
-        .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down));
+        app.init_resource::<PendingTransfers>()
+            .add_event::<Regroup>()
+            .add_system_set(
+                SystemSet::on_update(CURRENT_STATE)
+                    .with_system(spawn_fleets)
+                    .with_system(place_fleets)
+                    .with_system(regroup_fleets),
+            )
+            .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down));
     }
 }
 
@@ -112,37 +352,147 @@ fn spawn_fleets(
     mut fleets: ResMut<FleetsToSpawn>,
     ship_assets: Res<ShipAssets>,
     camera_controller: Res<CameraController>,
+    current: Res<CurrentPlayer>,
 ) {
     for fleet in fleets.0.drain(..) {
-        let Order::Orbit(around) = fleet.order else {
-            continue;
-        };
-        let Owner(owner) = fleet.owner;
-        let kind = fleet.ship.kind;
-        let mut builder = commands.spawn((
+        spawn_fleet(
+            &mut commands,
             fleet,
-            SpatialBundle::from_transform(Transform::from_translation(
-                (universe.galaxy[around].position * camera_controller.zoom_level
-                    / RATIO_ZOOM_DISTANCE)
-                    .extend(z_levels::SHIP),
-            )),
-            ScreenTag,
-        ));
-        if owner == 0 {
-            builder.with_children(|parent| {
-                parent.spawn(SpriteBundle {
-                    transform: Transform::from_scale(Vec3::splat(0.02)),
-                    texture: match kind {
-                        ShipKind::Colony => ship_assets.colony_ship.clone_weak(),
-                        ShipKind::Fighter => ship_assets.fighter.clone_weak(),
+            &universe,
+            &ship_assets,
+            &camera_controller,
+            &current,
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn regroup_fleets(
+    mut commands: Commands,
+    mut events: EventReader<Regroup>,
+    mut fleets: Query<(
+        &Ship,
+        &Owner,
+        &mut FleetSize,
+        Option<&RemoteFleet>,
+        Option<&SplitFrom>,
+    )>,
+    mut transfers: ResMut<PendingTransfers>,
+    (universe, current): (Res<Universe>, Res<CurrentPlayer>),
+    (ship_assets, camera_controller): (Res<ShipAssets>, Res<CameraController>),
+    mut selected_star: ResMut<SelectedStar>,
+    mut refresh: Local<bool>,
+) {
+    // new fleets only exist once the commands are applied, the star panel is shown again after
+    if *refresh {
+        selected_star.set_changed();
+        *refresh = false;
+    }
+    for event in events.iter() {
+        *refresh = true;
+        match event {
+            Regroup::Split { fleet, size, order } => {
+                let Ok((ship, owner, mut fleet_size, remote, split_from)) = fleets.get_mut(*fleet)
+                else {
+                    continue;
+                };
+                if *size == 0 || *size >= fleet_size.0 {
+                    continue;
+                }
+                fleet_size.0 -= size;
+                let host_id = remote
+                    .map(|remote| remote.0)
+                    .or(split_from.map(|split| split.0));
+                let entity = spawn_fleet(
+                    &mut commands,
+                    Fleet {
+                        order: order.clone(),
+                        ship: *ship,
+                        size: FleetSize(*size),
+                        owner: *owner,
                     },
-                    ..default()
-                });
-            });
+                    &universe,
+                    &ship_assets,
+                    &camera_controller,
+                    &current,
+                );
+                if let Some(id) = host_id {
+                    commands.entity(entity).insert(SplitFrom(id));
+                }
+            }
+            Regroup::Merge { fleet, into } => {
+                if fleet == into {
+                    continue;
+                }
+                let Ok((_, _, size, remote, split_from)) = fleets.get(*fleet) else {
+                    continue;
+                };
+                let size = size.0;
+                let from_id = remote
+                    .map(|remote| remote.0)
+                    .or(split_from.map(|split| split.0));
+                let Ok((_, _, mut into_size, remote, split_from)) = fleets.get_mut(*into) else {
+                    continue;
+                };
+                let into_id = remote
+                    .map(|remote| remote.0)
+                    .or(split_from.map(|split| split.0));
+                match (from_id, into_id) {
+                    (Some(from_id), Some(into_id)) if from_id != into_id => {
+                        transfers.0.push((from_id, into_id, size))
+                    }
+                    (Some(_), Some(_)) | (None, None) => (),
+                    // a fleet built this turn doesn't exist yet for the host
+                    _ => continue,
+                }
+                into_size.0 += size;
+                commands.entity(*fleet).despawn_recursive();
+            }
         }
     }
 }
 
+pub fn spawn_fleet(
+    commands: &mut Commands,
+    fleet: Fleet,
+    universe: &Universe,
+    ship_assets: &ShipAssets,
+    camera_controller: &CameraController,
+    current: &CurrentPlayer,
+) -> Entity {
+    let around = match fleet.order {
+        Order::Orbit(around) => around,
+        Order::Move { from, .. } => from,
+    };
+    let Owner(owner) = fleet.owner;
+    let kind = fleet.ship.kind;
+    // every fleet gets a sprite, only the one of the human at the screen are shown
+    commands
+        .spawn((
+            fleet,
+            SpatialBundle {
+                transform: Transform::from_translation(
+                    (universe.galaxy[around].position * camera_controller.zoom_level
+                        / RATIO_ZOOM_DISTANCE)
+                        .extend(z_levels::SHIP),
+                ),
+                visibility: Visibility {
+                    is_visible: owner == current.index,
+                },
+                ..default()
+            },
+            ScreenTag,
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                transform: Transform::from_scale(Vec3::splat(0.02)),
+                texture: ship_assets.ship(kind),
+                ..default()
+            });
+        })
+        .id()
+}
+
 #[derive(Component)]
 struct Orbiting {
     since: f32,
@@ -155,13 +505,14 @@ struct MovingTo {
     from: Vec2,
     to: Vec2,
     step: u32,
+    turns: u32,
     size: f32,
 }
 
 #[allow(clippy::type_complexity)]
 fn place_fleets(
     mut commands: Commands,
-    fleets: Query<(Entity, &Order, &Children, &Ship), Changed<Order>>,
+    fleets: Query<(Entity, &Order, &Children, &Ship, &Owner), Changed<Order>>,
     mut fleets_position: ParamSet<(
         Query<(&mut Transform, &Orbiting)>,
         Query<(&mut Transform, &MovingTo, Changed<MovingTo>)>,
@@ -170,7 +521,7 @@ fn place_fleets(
     universe: Res<Universe>,
     camera_controller: Res<CameraController>,
 ) {
-    for (entity, order, children, ship) in &fleets {
+    for (entity, order, children, ship, owner) in &fleets {
         match order {
             Order::Orbit(around) => {
                 let star_size = universe.galaxy[*around].size;
@@ -189,16 +540,18 @@ fn place_fleets(
                         since: time.elapsed_seconds(),
                         size: star_size.into(),
                         speed: match ship.kind {
-                            ShipKind::Colony => 0.8,
-                            ShipKind::Fighter => 1.2,
+                            ShipKind::Colony | ShipKind::Troops => 0.8,
+                            ShipKind::Fighter | ShipKind::Bomber => 1.2,
+                            ShipKind::Scout => 1.6,
+                            ShipKind::Platform => 0.3,
                         },
                     });
             }
-            Order::Move { from, to, step } => {
+            Order::Move { from, to, step, .. } => {
+                let departure = order.departure(&universe.galaxy);
                 commands.entity(entity).insert(
                     Transform::from_translation(
-                        (universe.galaxy[*from].position * camera_controller.zoom_level
-                            / RATIO_ZOOM_DISTANCE)
+                        (departure * camera_controller.zoom_level / RATIO_ZOOM_DISTANCE)
                             .extend(z_levels::SHIP),
                     )
                     .with_scale(Vec3::splat(camera_controller.zoom_level.powf(0.7))),
@@ -207,9 +560,11 @@ fn place_fleets(
                     .entity(children[0])
                     .remove::<Orbiting>()
                     .insert(MovingTo {
-                        from: universe.galaxy[*from].position,
+                        from: departure,
                         to: universe.galaxy[*to].position,
                         step: *step,
+                        turns: order
+                            .leg_turns(&universe.galaxy, universe.speed(owner.0, ship.kind)),
                         size: universe.galaxy[*from].size.into(),
                     });
             }
@@ -228,7 +583,7 @@ fn place_fleets(
     for (mut transform, moving_to, changed_moving) in &mut fleets_position.p1() {
         if transform.is_changed() || changed_moving || camera_controller.is_changed() {
             let direction = moving_to.to - moving_to.from;
-            let steps = turns_between(moving_to.from, moving_to.to) as f32;
+            let steps = moving_to.turns as f32;
             transform.translation = (((direction * moving_to.step as f32 / steps)
                 * camera_controller.zoom_level
                 / RATIO_ZOOM_DISTANCE)
@@ -240,6 +595,8 @@ fn place_fleets(
     }
 }
 
-pub fn turns_between(from: Vec2, to: Vec2) -> u32 {
-    ((from.distance(to) / 90.0).exp() * 1.7).floor().max(1.0) as u32
+pub fn turns_between(from: Vec2, to: Vec2, speed: f32) -> u32 {
+    ((from.distance(to) / 90.0).exp() * 1.7 / speed)
+        .floor()
+        .max(1.0) as u32
 }
@@ -1,12 +1,21 @@
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
-use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom, Rng};
+use rand::{
+    distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, seq::SliceRandom, Rng,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Default)]
+use super::{bots::Personality, victory::VictoryRules, Controller};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GalaxyKind {
     #[default]
     Spiral,
+    Elliptical,
+    Ring,
+    Clusters,
+    Irregular,
 }
 
 #[derive(Resource)]
@@ -14,13 +23,18 @@ pub struct GalaxyCreator {
     pub nb_players: u32,
     pub size: f32,
     pub density: f32,
-    pub _kind: GalaxyKind,
+    pub kind: GalaxyKind,
     pub generated: Vec<Star>,
     pub names: Vec<String>,
     pub difficulty: f32,
+    pub seed: u64,
+    pub rng: StdRng,
+    pub controllers: Vec<Controller>,
+    pub personalities: Vec<Personality>,
+    pub victory: VictoryRules,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Star {
     pub position: Vec2,
     pub size: StarSize,
@@ -29,14 +43,14 @@ pub struct Star {
     pub name: String,
 }
 
-#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StarColor {
     Blue,
     Yellow,
     Orange,
 }
 
-#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StarSize {
     Dwarf,
     Subgiant,
@@ -53,18 +67,174 @@ impl From<StarSize> for f32 {
     }
 }
 
+impl GalaxyCreator {
+    fn radius(&self) -> f32 {
+        self.size * 100.0
+    }
+
+    fn spiral_position(&mut self) -> Vec2 {
+        let arm_angle = ((360 / self.nb_players) % 360) as f32;
+        let angular_spread = 180 / (self.nb_players * 2);
+        let radius = self.radius();
+        let rand = &mut self.rng;
+
+        let distance_to_center = rand.gen_range(0.03..=1.0_f32).sqrt() * radius;
+        let angle = rand.gen_range(0.0..(angular_spread as f32));
+
+        let spiral_angle = 0.75;
+
+        let arm = (rand.gen::<u32>() % self.nb_players) as f32 * arm_angle;
+
+        let x = distance_to_center
+            * (PI / 180.0 * (arm + distance_to_center * spiral_angle + angle)).cos();
+        let y = distance_to_center
+            * (PI / 180.0 * (arm + distance_to_center * spiral_angle + angle)).sin();
+        Vec2::new(x, y)
+    }
+
+    fn elliptical_position(&mut self) -> Vec2 {
+        let radius = self.radius();
+        let distance_to_center = self.rng.gen_range(0.0..=1.0_f32).sqrt() * radius;
+        let angle = self.rng.gen_range(0.0..(2.0 * PI));
+        Vec2::new(angle.cos() * 1.25, angle.sin() * 0.75) * distance_to_center
+    }
+
+    fn ring_position(&mut self) -> Vec2 {
+        let radius = self.radius();
+        let distance_to_center = self.rng.gen_range(0.45..=1.0_f32).sqrt() * radius * 1.15;
+        let angle = self.rng.gen_range(0.0..(2.0 * PI));
+        Vec2::new(angle.cos(), angle.sin()) * distance_to_center
+    }
+
+    // one cluster in the center, and one for each player around it
+    fn cluster_centers(&self) -> Vec<Vec2> {
+        let radius = self.radius();
+        std::iter::once(Vec2::ZERO)
+            .chain((0..self.nb_players).map(|i| {
+                let angle = 2.0 * PI / self.nb_players as f32 * i as f32;
+                Vec2::new(angle.cos(), angle.sin()) * radius * 0.7
+            }))
+            .collect()
+    }
+
+    fn cluster_radius(&self) -> f32 {
+        (0.35_f32).min(0.8 * 0.7 * (PI / self.nb_players as f32).sin()) * self.radius()
+    }
+
+    fn clusters_position(&mut self) -> Vec2 {
+        let centers = self.cluster_centers();
+        let cluster_radius = self.cluster_radius();
+        let radius = self.radius();
+        let rand = &mut self.rng;
+
+        if rand.gen_bool(0.1) {
+            // a few stars along the bridges between clusters, from each outer cluster to the
+            // center and to its next neighbour
+            let from = rand.gen_range(1..centers.len());
+            let to = if rand.gen_bool(0.5) {
+                0
+            } else {
+                from % (centers.len() - 1) + 1
+            };
+            let along = centers[from].lerp(centers[to], rand.gen_range(0.0..=1.0));
+            let across = (centers[to] - centers[from]).perp().normalize_or_zero();
+            along + across * rand.gen_range(-0.05..=0.05) * radius
+        } else {
+            let center = *centers.choose(rand).unwrap();
+            let distance_to_center = rand.gen_range(0.0..=1.0_f32).sqrt() * cluster_radius;
+            let angle = rand.gen_range(0.0..(2.0 * PI));
+            center + Vec2::new(angle.cos(), angle.sin()) * distance_to_center
+        }
+    }
+
+    fn irregular_position(&mut self) -> Vec2 {
+        let radius = self.radius() * 1.1;
+        loop {
+            let distance_to_center = self.rng.gen_range(0.0..=1.0_f32).sqrt() * radius;
+            let angle = self.rng.gen_range(0.0..(2.0 * PI));
+            let position = Vec2::new(angle.cos(), angle.sin()) * distance_to_center;
+
+            // keep stars where the noise is high, fading out toward the edge
+            let noise = value_noise(self.seed, position / 120.0) * 0.65
+                + value_noise(self.seed.wrapping_add(1), position / 45.0) * 0.35;
+            let falloff = 1.0 - (distance_to_center / radius).powi(2) * 0.5;
+            if noise * falloff > self.rng.gen_range(0.35..0.6) {
+                return position;
+            }
+        }
+    }
+
+    pub fn nb_stars(&self) -> usize {
+        (self.nb_players as f32 * self.size * self.density * 4.0).round() as usize
+    }
+
+    // bigger galaxies have room for more empires
+    pub fn max_players(&self) -> u32 {
+        if self.size >= 5.0 {
+            8
+        } else if self.size >= 3.0 {
+            6
+        } else {
+            5
+        }
+    }
+
+    // where to look for the start of a player, `slot` being its place around the galaxy
+    pub fn start_position(&self, slot: usize) -> Vec2 {
+        let mut angle = PI * 2.0 / self.nb_players as f32 * slot as f32;
+        match self.kind {
+            GalaxyKind::Spiral => {
+                if self.nb_players.is_multiple_of(2) {
+                    angle += PI / (self.nb_players as f32 * 1.5);
+                }
+                Vec2::new(angle.cos(), angle.sin()) * self.radius()
+            }
+            GalaxyKind::Elliptical => {
+                Vec2::new(angle.cos() * 1.25, angle.sin() * 0.75) * self.radius() * 0.8
+            }
+            GalaxyKind::Ring => Vec2::new(angle.cos(), angle.sin()) * self.radius() * 0.98,
+            GalaxyKind::Clusters => self.cluster_centers()[slot % self.nb_players as usize + 1],
+            GalaxyKind::Irregular => Vec2::new(angle.cos(), angle.sin()) * self.radius() * 0.7,
+        }
+    }
+}
+
+fn noise_at(seed: u64, x: i64, y: i64) -> f32 {
+    let mut hash = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    hash ^= hash >> 33;
+    (hash >> 40) as f32 / (1_u64 << 24) as f32
+}
+
+fn value_noise(seed: u64, position: Vec2) -> f32 {
+    let cell = position.floor();
+    let offset = position - cell;
+    let smooth = offset * offset * (Vec2::splat(3.0) - 2.0 * offset);
+    let (x, y) = (cell.x as i64, cell.y as i64);
+
+    let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+    let bottom = lerp(noise_at(seed, x, y), noise_at(seed, x + 1, y), smooth.x);
+    let top = lerp(
+        noise_at(seed, x, y + 1),
+        noise_at(seed, x + 1, y + 1),
+        smooth.x,
+    );
+    lerp(bottom, top, smooth.y)
+}
+
 impl Iterator for GalaxyCreator {
     type Item = Star;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.generated.len() as f32 == self.nb_players as f32 * self.size * self.density * 4.0 {
+        if self.generated.len() >= self.nb_stars() {
             return None;
         }
 
-        let mut rand = rand::thread_rng();
-        let arm_angle = ((360 / self.nb_players) % 360) as f32;
-        let angular_spread = 180 / (self.nb_players * 2);
-
         let mut fail = 0;
 
         let size_choices = [StarSize::Dwarf, StarSize::Subgiant, StarSize::Giant];
@@ -72,19 +242,13 @@ impl Iterator for GalaxyCreator {
         let size_dist = WeightedIndex::new(&size_weights).unwrap();
 
         'distance: loop {
-            let distance_to_center =
-                rand.gen_range(0.03..=1.0_f32).sqrt() * self.size as f32 * 100.0;
-            let angle = rand.gen_range(0.0..(angular_spread as f32));
-
-            let spiral_angle = 0.75;
-
-            let arm = (rand.gen::<u32>() % self.nb_players) as f32 * arm_angle;
-
-            let x = distance_to_center
-                * (PI / 180.0 * (arm + distance_to_center * spiral_angle + angle) as f32).cos();
-            let y = distance_to_center
-                * (PI / 180.0 * (arm + distance_to_center * spiral_angle + angle) as f32).sin();
-            let new_star_position = Vec2::new(x, y);
+            let new_star_position = match self.kind {
+                GalaxyKind::Spiral => self.spiral_position(),
+                GalaxyKind::Elliptical => self.elliptical_position(),
+                GalaxyKind::Ring => self.ring_position(),
+                GalaxyKind::Clusters => self.clusters_position(),
+                GalaxyKind::Irregular => self.irregular_position(),
+            };
 
             for other_star in &self.generated {
                 let distance = new_star_position.distance(other_star.position);
@@ -96,14 +260,20 @@ impl Iterator for GalaxyCreator {
                 }
             }
 
-            let name_to_take = rand.gen_range(0..self.names.len());
-            let name = self.names.remove(name_to_take);
+            let rand = &mut self.rng;
+            // the largest galaxies have more stars than there are names
+            let name = if self.names.is_empty() {
+                format!("Star {}", self.generated.len() + 1)
+            } else {
+                let name_to_take = rand.gen_range(0..self.names.len());
+                self.names.remove(name_to_take)
+            };
 
             let new_star = Star {
                 position: new_star_position,
-                size: size_choices[size_dist.sample(&mut rand)],
+                size: size_choices[size_dist.sample(rand)],
                 color: *[StarColor::Blue, StarColor::Orange, StarColor::Yellow]
-                    .choose(&mut rand)
+                    .choose(rand)
                     .unwrap(),
                 start: None,
                 name,
@@ -113,3 +283,68 @@ impl Iterator for GalaxyCreator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn creator(kind: GalaxyKind, size: f32, density: f32, seed: u64) -> GalaxyCreator {
+        let mut creator = GalaxyCreator {
+            nb_players: 2,
+            size,
+            density,
+            kind,
+            generated: vec![],
+            names: include_str!("../../assets/star.names")
+                .lines()
+                .map(|name| name.to_string())
+                .collect(),
+            difficulty: 1.0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            controllers: vec![],
+            personalities: vec![],
+            victory: VictoryRules::default(),
+        };
+        creator.nb_players = creator.max_players();
+        creator
+    }
+
+    #[test]
+    fn largest_galaxy_has_a_name_for_every_star() {
+        for kind in [
+            GalaxyKind::Spiral,
+            GalaxyKind::Elliptical,
+            GalaxyKind::Ring,
+            GalaxyKind::Clusters,
+            GalaxyKind::Irregular,
+        ] {
+            let mut creator = creator(kind, 5.0, 4.0, 0);
+            let nb_names = creator.names.len();
+            for _ in creator.by_ref() {}
+            assert_eq!(creator.generated.len(), 8 * 5 * 4 * 4);
+            assert!(creator.generated.len() > nb_names);
+            let mut names = creator
+                .generated
+                .iter()
+                .map(|star| star.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), creator.generated.len());
+        }
+    }
+
+    #[test]
+    fn same_seed_same_galaxy() {
+        let galaxy = |seed| {
+            let mut creator = creator(GalaxyKind::Irregular, 3.0, 2.5, seed);
+            for _ in creator.by_ref() {}
+            ron::to_string(&creator.generated).unwrap()
+        };
+        assert_eq!(galaxy(7), galaxy(7));
+        assert_ne!(galaxy(7), galaxy(8));
+    }
+}
@@ -1,20 +1,42 @@
 use bevy::{
-    prelude::{Entity, Resource},
-    utils::Instant,
+    prelude::{Color, Entity, Resource},
+    time::Time,
+    utils::{Duration, Instant},
 };
 
-use self::{fleet::Fleet, galaxy::Star};
+use serde::{Deserialize, Serialize};
+
+use crate::assets::balance::Balance;
+
+use self::{
+    bots::Personality, fleet::Fleet, galaxy::Star, production::Production, research::Research,
+    victory::VictoryRules,
+};
 
 mod bots;
+pub mod combat;
+pub mod external;
 pub mod fleet;
 mod galaxy;
+pub mod network;
+mod pirates;
+mod pricing;
+pub mod production;
+pub mod research;
+pub mod save;
 pub mod setup;
+pub mod simulation;
 pub mod starfield;
+pub mod stats;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tournament;
 pub mod turns;
 pub mod ui;
+pub mod victory;
+mod vision;
 pub mod world;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 struct StarDetails {
     population: f32,
     resources: f32,
@@ -22,39 +44,43 @@ struct StarDetails {
     owned_since: u32,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct Universe {
     galaxy: Vec<Star>,
     players: Vec<Player>,
+    #[serde(skip)]
     star_entities: Vec<Entity>,
     star_details: Vec<StarDetails>,
     difficulty: f32,
+    pirates: usize,
+    seed: u64,
+    balance: Balance,
+    victory: VictoryRules,
+    special_stars: Vec<usize>,
+    // ships being built around each star
+    queues: Vec<Vec<Production>>,
 }
 
 #[derive(Resource)]
 pub struct FleetsToSpawn(pub Vec<Fleet>);
 
 impl Universe {
+    fn good_conditions(&self, star_index: usize, player: usize) -> bool {
+        self.galaxy[star_index].color == self.galaxy[self.players[player].start].color
+    }
+
     fn star_revenue(&self, star_index: usize) -> f32 {
         let details = self.star_details[star_index];
-        let good_conditions = &self.galaxy[self.players[details.owner].start];
-        let star = &self.galaxy[star_index];
-        if star.color == good_conditions.color {
-            (details.population * 1.1).powf(1.4) / 100.0 - 2.0
-        } else {
-            (details.population).powf(0.8) / 100.0 - 2.0
-        }
+        self.balance
+            .revenue(self.good_conditions(star_index, details.owner))
+            .revenue(details.population)
     }
 
     fn star_ressource(&self, star_index: usize) -> f32 {
         let details = self.star_details[star_index];
-        let good_conditions = &self.galaxy[self.players[details.owner].start];
-        let star = &self.galaxy[star_index];
-        if star.color != good_conditions.color {
-            (details.resources * 1.2).powf(1.5)
-        } else {
-            (details.resources).powf(0.8)
-        }
+        self.balance
+            .resources(self.good_conditions(star_index, details.owner))
+            .available(details.resources)
     }
 
     fn player_population(&self, player: usize) -> f32 {
@@ -66,23 +92,41 @@ impl Universe {
     }
 
     fn player_revenue(&self, player: usize) -> f32 {
-        let good_conditions = &self.galaxy[self.players[player].start];
+        (0..self.star_details.len())
+            .filter(|i| self.star_details[*i].owner == player)
+            .map(|i| self.star_revenue(i))
+            .sum::<f32>()
+    }
 
-        self.star_details
+    fn owner_color(&self, owner: usize) -> Color {
+        if owner == self.pirates {
+            return Color::DARK_GRAY;
+        }
+        // spread hues with the golden angle so that neighbours in the palette stay distinct
+        Color::hsl((owner as f32 * 137.508) % 360.0, 0.8, 0.45)
+    }
+
+    fn humans(&self) -> impl Iterator<Item = usize> + '_ {
+        self.players
             .iter()
-            .zip(self.galaxy.iter())
-            .filter(|(details, _)| details.owner == player)
-            .map(|(details, star)| {
-                if star.color == good_conditions.color {
-                    (details.population * 1.1).powf(1.4) / 100.0 - 2.0
-                } else {
-                    (details.population).powf(0.8) / 100.0 - 2.0
-                }
-            })
-            .sum::<f32>()
+            .enumerate()
+            .filter(|(_, player)| player.controller == Controller::Human)
+            .map(|(i, _)| i)
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Controller {
+    Human,
+    #[default]
+    Bot,
+    // a human playing from another computer, through the network
+    Remote,
+    // a program started by the game, talking on its standard input and output
+    External,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Player {
     start: usize,
     vision: Vec<StarState>,
@@ -90,10 +134,13 @@ struct Player {
     resources: f32,
     first_colony_done: bool,
     name: String,
+    controller: Controller,
+    personality: Personality,
+    research: Research,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum StarState {
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum StarState {
     Owned(usize),
     Unknown,
     Uninhabited,
@@ -104,6 +151,12 @@ pub struct CurrentGame {
     start: Instant,
 }
 
+impl CurrentGame {
+    pub fn play_time(&self, time: &Time) -> Duration {
+        time.last_update().unwrap_or(self.start) - self.start
+    }
+}
+
 mod z_levels {
     pub const STARFIELD: f32 = 0.0;
     pub const STAR_SELECTION: f32 = 0.4;
@@ -112,4 +165,5 @@ mod z_levels {
     pub const STAR_NAME: f32 = 0.7;
     pub const SHIP: f32 = 0.8;
     pub const SHIP_DRAGGING: f32 = 1.0;
+    pub const STATS_CHART: f32 = 2.0;
 }
@@ -0,0 +1,673 @@
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::{
+    prelude::*,
+    utils::{Duration, Instant},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{assets::loader::ShipAssets, GameState};
+
+use super::{
+    bots::{BotTurnStatus, ReceivedOrders},
+    fleet::{
+        fleet_states, spawn_fleet, Fleet, FleetQuery, FleetSize, Order, Owner, PendingTransfers,
+        RemoteFleet, Ship, SplitFrom,
+    },
+    galaxy::GalaxyCreator,
+    production::PendingQueueChanges,
+    save::Settings,
+    simulation::{self, FleetState, Orders, Split},
+    stats::StatsHistory,
+    turns::{CurrentPlayer, Message, TurnState, Turns},
+    ui::SelectedStar,
+    world::{CameraController, DrawnView},
+    Controller, FleetsToSpawn, Universe,
+};
+
+pub const PORT: u16 = 7878;
+// slots still free after this long are played by bots
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+// the game to join by default, on this computer unless told otherwise
+pub fn host_address() -> String {
+    std::env::var("SPACEVIATA_HOST").unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
+#[derive(Serialize, Deserialize)]
+enum ToClient {
+    Welcome { player: usize },
+    View(Box<PlayerView>),
+    Full,
+}
+
+#[derive(Serialize, Deserialize)]
+enum ToHost {
+    Orders { turn: u32, orders: Orders },
+}
+
+// everything a player knows of the game, nothing more
+#[derive(Serialize, Deserialize)]
+struct PlayerView {
+    player: usize,
+    turn: u32,
+    settings: Settings,
+    universe: Universe,
+    fleets: Vec<FleetState>,
+    messages: Vec<Message>,
+}
+
+impl PlayerView {
+    fn new(
+        creator: &GalaxyCreator,
+        universe: &Universe,
+        fleets: &[FleetState],
+        player: usize,
+        turn: u32,
+        messages: Vec<Message>,
+    ) -> Self {
+        let mut universe = universe.seen_by(player);
+        for (i, other) in universe.players.iter_mut().enumerate() {
+            other.controller = if i == player {
+                Controller::Human
+            } else {
+                Controller::Remote
+            };
+        }
+
+        PlayerView {
+            player,
+            turn,
+            settings: Settings::new(creator),
+            fleets: universe.fleets_seen_by(fleets, player),
+            universe,
+            messages,
+        }
+    }
+}
+
+// one message per line, written on the calling thread until the channel or the connection
+// closes, which closes the other one
+fn write_messages<T: Serialize>(mut stream: TcpStream, outgoing: Receiver<T>) {
+    for message in outgoing {
+        let sent = ron::to_string(&message)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut line| {
+                line.push('\n');
+                Ok(stream.write_all(line.as_bytes())?)
+            });
+        if let Err(error) = sent {
+            warn!("error sending message: {}", error);
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+// forward every message received, then `None` once the connection is closed
+fn listen<T: DeserializeOwned + 'static>(
+    stream: &TcpStream,
+    forward: impl Fn(Option<T>) + Send + 'static,
+) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match ron::from_str(&line) {
+                Ok(message) => forward(Some(message)),
+                Err(error) => {
+                    warn!("invalid message: {}", error);
+                    break;
+                }
+            }
+        }
+        forward(None);
+    });
+    Ok(())
+}
+
+struct Connection {
+    player: usize,
+    // the connection is closed once dropped
+    outgoing: Mutex<Sender<ToClient>>,
+    sent: Option<u32>,
+}
+
+impl Connection {
+    fn send(&self, message: ToClient) {
+        let _ = self.outgoing.lock().unwrap().send(message);
+    }
+}
+
+// runs the game for players on other computers
+#[derive(Resource)]
+pub struct Host {
+    listener: TcpListener,
+    started: Instant,
+    free: Vec<usize>,
+    connections: Vec<Connection>,
+    sender: Mutex<Sender<(usize, Option<ToHost>)>>,
+    incoming: Mutex<Receiver<(usize, Option<ToHost>)>>,
+}
+
+impl Host {
+    pub fn start(players: Vec<usize>) -> io::Result<Self> {
+        info!("waiting for players on port {}", PORT);
+        Host::bind(("0.0.0.0", PORT), players)
+    }
+
+    fn bind(address: impl ToSocketAddrs, players: Vec<usize>) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let (sender, incoming) = channel();
+        Ok(Host {
+            listener,
+            started: Instant::now(),
+            free: players,
+            connections: vec![],
+            sender: Mutex::new(sender),
+            incoming: Mutex::new(incoming),
+        })
+    }
+
+    fn accept(&mut self, stream: TcpStream) -> anyhow::Result<()> {
+        stream.set_nonblocking(false)?;
+        let (outgoing, to_write) = channel();
+        let writer = stream.try_clone()?;
+        thread::spawn(move || write_messages(writer, to_write));
+        if self.free.is_empty() {
+            let _ = outgoing.send(ToClient::Full);
+            return Ok(());
+        }
+        let player = self.free.remove(0);
+        let _ = outgoing.send(ToClient::Welcome { player });
+        let sender = self.sender.lock().unwrap().clone();
+        listen(&stream, move |message| {
+            let _ = sender.send((player, message));
+        })?;
+        self.connections.push(Connection {
+            player,
+            outgoing: Mutex::new(outgoing),
+            sent: None,
+        });
+        Ok(())
+    }
+}
+
+// plays in a game run by a host on another computer
+#[derive(Resource)]
+pub struct Client {
+    // the connection is closed once dropped
+    outgoing: Mutex<Sender<ToHost>>,
+    incoming: Mutex<Receiver<Option<ToClient>>>,
+}
+
+impl Client {
+    pub fn join(host: String) -> Self {
+        Client::connect((host, PORT))
+    }
+
+    // the connection is made on another thread, `None` is received if it fails
+    fn connect(address: impl ToSocketAddrs + Send + 'static) -> Self {
+        let (outgoing, to_write) = channel();
+        let (sender, incoming) = channel();
+        thread::spawn(move || {
+            let lost = sender.clone();
+            let connected = TcpStream::connect(address).and_then(|stream| {
+                listen(&stream, move |message| {
+                    let _ = sender.send(message);
+                })?;
+                Ok(stream)
+            });
+            match connected {
+                Ok(stream) => write_messages(stream, to_write),
+                Err(error) => {
+                    warn!("error joining game: {}", error);
+                    let _ = lost.send(None);
+                }
+            }
+        });
+        Client {
+            outgoing: Mutex::new(outgoing),
+            incoming: Mutex::new(incoming),
+        }
+    }
+
+    fn send(&self, message: ToHost) {
+        let _ = self.outgoing.lock().unwrap().send(message);
+    }
+}
+
+pub struct Plugin;
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Menu).with_system(join_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(accept_players)
+                    .with_system(receive_orders)
+                    .with_system(send_views)
+                    .with_system(spawn_pending_fleets),
+            )
+            .add_system_set(SystemSet::on_enter(TurnState::Waiting).with_system(send_orders))
+            .add_system_set(SystemSet::on_update(TurnState::Waiting).with_system(receive_view))
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(tear_down));
+    }
+}
+
+fn tear_down(mut commands: Commands, mut received: ResMut<ReceivedOrders>) {
+    received.orders.clear();
+    received.absent.clear();
+    commands.remove_resource::<Host>();
+    commands.remove_resource::<Client>();
+}
+
+fn accept_players(host: Option<ResMut<Host>>, mut received: ResMut<ReceivedOrders>) {
+    let Some(mut host) = host else {
+        return;
+    };
+    if !host.free.is_empty() && host.started.elapsed() > CONNECT_TIMEOUT {
+        warn!("players {:?} never joined, bots play for them", host.free);
+        let free = std::mem::take(&mut host.free);
+        received.absent.extend(free);
+    }
+    loop {
+        match host.listener.accept() {
+            Ok((stream, address)) => {
+                info!("player connected from {}", address);
+                if let Err(error) = host.accept(stream) {
+                    warn!("error accepting player: {}", error);
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("error waiting for players: {}", error);
+                break;
+            }
+        }
+    }
+}
+
+fn receive_orders(
+    host: Option<ResMut<Host>>,
+    mut received: ResMut<ReceivedOrders>,
+    turns: Res<Turns>,
+) {
+    let Some(mut host) = host else {
+        return;
+    };
+    let messages = host.incoming.lock().unwrap().try_iter().collect::<Vec<_>>();
+    for (player, message) in messages {
+        match message {
+            Some(ToHost::Orders { turn, orders }) if turn == turns.count => {
+                received.orders.insert(player, (turn, orders));
+            }
+            Some(ToHost::Orders { turn, .. }) => {
+                warn!("orders from {} for turn {} arrived late", player, turn);
+            }
+            None => {
+                // a bot takes over the empire of players who left, at the prices they had
+                warn!("player {} disconnected, a bot plays for them", player);
+                host.connections
+                    .retain(|connection| connection.player != player);
+                received.absent.push(player);
+            }
+        }
+    }
+}
+
+fn send_views(
+    host: Option<ResMut<Host>>,
+    creator: Res<GalaxyCreator>,
+    universe: Res<Universe>,
+    mut turns: ResMut<Turns>,
+    fleets: FleetQuery,
+) {
+    let Some(mut host) = host else {
+        return;
+    };
+    // turns are counted once resolved, players get their view of each new one
+    let count = turns.count;
+    if host
+        .connections
+        .iter()
+        .all(|connection| connection.sent == Some(count))
+    {
+        return;
+    }
+
+    let states = fleet_states(&fleets);
+    for connection in &mut host.connections {
+        if connection.sent == Some(count) {
+            continue;
+        }
+        let player = connection.player;
+        let mut messages = vec![Message::Turn(count)];
+        messages.append(&mut turns.pending[player]);
+        messages.extend(simulation::outcome(&universe, player, count));
+        let view = PlayerView::new(&creator, &universe, &states, player, count, messages);
+        connection.send(ToClient::View(Box::new(view)));
+        connection.sent = Some(count);
+    }
+}
+
+fn join_game(
+    mut commands: Commands,
+    client: Option<Res<Client>>,
+    mut state: ResMut<State<GameState>>,
+    mut turn_state: ResMut<State<TurnState>>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    let messages = client
+        .incoming
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect::<Vec<_>>();
+    for message in messages {
+        match message {
+            Some(ToClient::Welcome { player }) => {
+                info!("joined the game as player {}", player);
+            }
+            Some(ToClient::View(view)) => {
+                let view = *view;
+                let nb_players = view.universe.players.len();
+                let player = view.player;
+                commands.insert_resource(view.settings.creator(&view.universe));
+                commands.insert_resource(FleetsToSpawn(vec![]));
+                let mut pending = vec![vec![]; nb_players];
+                pending[player] = view.messages;
+                commands.insert_resource(Turns {
+                    count: view.turn,
+                    messages: vec![],
+                    pending,
+                    journal: vec![vec![]; nb_players],
+                });
+                let mut history = StatsHistory::default();
+                history.record(view.turn, &view.universe, &view.fleets);
+                commands.insert_resource(history);
+                commands.insert_resource(CurrentPlayer {
+                    index: player,
+                    waiting: vec![player],
+                    defeated: vec![],
+                    remote: true,
+                });
+                commands.insert_resource(BotTurnStatus {
+                    current: 0,
+                    last_colony_ship_spawned: vec![0; nb_players],
+                });
+                commands.insert_resource(Universe {
+                    star_entities: Vec::with_capacity(view.universe.galaxy.len()),
+                    ..view.universe
+                });
+                commands.init_resource::<SelectedStar>();
+                commands.insert_resource(PendingFleets(view.fleets));
+                let _ = turn_state.overwrite_set(TurnState::Player);
+                let _ = state.set(GameState::Game);
+                return;
+            }
+            Some(ToClient::Full) => {
+                warn!("no place left in this game");
+                commands.remove_resource::<Client>();
+                return;
+            }
+            None => {
+                warn!("connection to the game lost");
+                commands.remove_resource::<Client>();
+                return;
+            }
+        }
+    }
+}
+
+// fleets of a joined game, spawned once the galaxy is there
+#[derive(Resource)]
+struct PendingFleets(Vec<FleetState>);
+
+fn spawn_remote_fleets(
+    commands: &mut Commands,
+    fleets: Vec<FleetState>,
+    universe: &Universe,
+    ship_assets: &ShipAssets,
+    camera_controller: &CameraController,
+    current: &CurrentPlayer,
+) {
+    for fleet in fleets {
+        let entity = spawn_fleet(
+            commands,
+            Fleet {
+                order: fleet.order,
+                ship: Ship { kind: fleet.kind },
+                size: FleetSize(fleet.size),
+                owner: Owner(fleet.owner),
+            },
+            universe,
+            ship_assets,
+            camera_controller,
+            current,
+        );
+        commands.entity(entity).insert(RemoteFleet(fleet.id));
+    }
+}
+
+fn spawn_pending_fleets(
+    mut commands: Commands,
+    pending: Option<ResMut<PendingFleets>>,
+    universe: Res<Universe>,
+    ship_assets: Res<ShipAssets>,
+    camera_controller: Res<CameraController>,
+    current: Res<CurrentPlayer>,
+) {
+    let Some(mut pending) = pending else {
+        return;
+    };
+    spawn_remote_fleets(
+        &mut commands,
+        std::mem::take(&mut pending.0),
+        &universe,
+        &ship_assets,
+        &camera_controller,
+        &current,
+    );
+    commands.remove_resource::<PendingFleets>();
+}
+
+#[allow(clippy::type_complexity)]
+fn send_orders(
+    client: Option<Res<Client>>,
+    (turns, current, universe): (Res<Turns>, Res<CurrentPlayer>, Res<Universe>),
+    fleets: Query<(
+        &Order,
+        &FleetSize,
+        &Owner,
+        Option<&RemoteFleet>,
+        Option<&SplitFrom>,
+    )>,
+    mut transfers: ResMut<PendingTransfers>,
+    mut queue_changes: ResMut<PendingQueueChanges>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    // the research panel changes the local copy of the universe, the host needs to know
+    let research = universe.research(current.index);
+    let mut orders = Orders {
+        transfers: std::mem::take(&mut transfers.0),
+        queue_changes: std::mem::take(&mut queue_changes.0),
+        research: research.current,
+        funding: Some(research.funding),
+        ..default()
+    };
+    for (order, size, owner, remote, split_from) in &fleets {
+        if owner.0 != current.index {
+            continue;
+        }
+        match (remote, split_from, order) {
+            (
+                Some(RemoteFleet(id)),
+                _,
+                Order::Move {
+                    to,
+                    step: 0,
+                    waypoints,
+                    departure,
+                    ..
+                },
+            ) => {
+                if departure.is_some() {
+                    orders.redirects.push((*id, *to));
+                } else {
+                    orders.moves.push((*id, *to));
+                }
+                if !waypoints.is_empty() {
+                    orders.waypoints.push((*id, waypoints.clone()));
+                }
+            }
+            (None, Some(SplitFrom(id)), order) => orders.splits.push(Split {
+                fleet: *id,
+                size: size.0,
+                route: order.route()[1..].to_vec(),
+            }),
+            _ => (),
+        }
+    }
+    // a lost connection is noticed while waiting for the next turn
+    client.send(ToHost::Orders {
+        turn: turns.count,
+        orders,
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn receive_view(
+    mut commands: Commands,
+    client: Option<Res<Client>>,
+    mut universe: ResMut<Universe>,
+    mut turns: ResMut<Turns>,
+    mut current: ResMut<CurrentPlayer>,
+    mut history: ResMut<StatsHistory>,
+    (ship_assets, camera_controller): (Res<ShipAssets>, Res<CameraController>),
+    fleets: Query<Entity, With<Owner>>,
+    mut drawn: ResMut<DrawnView>,
+    mut state: ResMut<State<GameState>>,
+    mut turn_state: ResMut<State<TurnState>>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    let messages = client
+        .incoming
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect::<Vec<_>>();
+    for message in messages {
+        match message {
+            Some(ToClient::View(view)) => {
+                for entity in &fleets {
+                    commands.entity(entity).despawn_recursive();
+                }
+                let star_entities = std::mem::take(&mut universe.star_entities);
+                *universe = Universe {
+                    star_entities,
+                    ..view.universe
+                };
+                history.record(view.turn, &universe, &view.fleets);
+                spawn_remote_fleets(
+                    &mut commands,
+                    view.fleets,
+                    &universe,
+                    &ship_assets,
+                    &camera_controller,
+                    &current,
+                );
+
+                let player = view.player;
+                if view.messages.iter().any(|message| {
+                    matches!(message, Message::Lose { .. } | Message::RivalVictory { .. })
+                }) {
+                    current.defeated.push(player);
+                }
+                turns.count = view.turn;
+                turns.pending[player] = view.messages;
+                current.waiting = vec![player];
+                drawn.0 = None;
+                let _ = turn_state.set(TurnState::Player);
+                return;
+            }
+            Some(ToClient::Welcome { .. }) | Some(ToClient::Full) => (),
+            None => {
+                warn!("connection to the game lost");
+                let _ = state.set(GameState::Menu);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn loopback_round_trip() {
+        let mut host = Host::bind(("127.0.0.1", 0), vec![1]).unwrap();
+        let client = Client::connect(host.listener.local_addr().unwrap());
+
+        let stream = loop {
+            match host.listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(10))
+                }
+                Err(error) => panic!("{}", error),
+            }
+        };
+        host.accept(stream).unwrap();
+        let incoming = client.incoming.lock().unwrap();
+        assert!(matches!(
+            incoming.recv_timeout(TIMEOUT).unwrap(),
+            Some(ToClient::Welcome { player: 1 })
+        ));
+
+        client.send(ToHost::Orders {
+            turn: 3,
+            orders: Orders {
+                moves: vec![(7, 2)],
+                ..Default::default()
+            },
+        });
+        match host.incoming.lock().unwrap().recv_timeout(TIMEOUT).unwrap() {
+            (1, Some(ToHost::Orders { turn: 3, orders })) => assert_eq!(orders.moves, [(7, 2)]),
+            _ => panic!("unexpected message"),
+        }
+
+        host.connections[0].send(ToClient::Full);
+        assert!(matches!(
+            incoming.recv_timeout(TIMEOUT).unwrap(),
+            Some(ToClient::Full)
+        ));
+
+        // the host learns when a player leaves
+        drop(incoming);
+        drop(client);
+        assert!(matches!(
+            host.incoming.lock().unwrap().recv_timeout(TIMEOUT).unwrap(),
+            (1, None)
+        ));
+    }
+}
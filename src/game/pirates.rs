@@ -0,0 +1,157 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
+
+use super::{
+    fleet::{
+        fleet_states, turns_between, update_fleets, Fleet, FleetQuery, FleetSize, Order, Owner,
+        Ship, ShipKind,
+    },
+    simulation::{Event, FleetState},
+    turns::{Message, TurnState, Turns},
+    FleetsToSpawn, StarState, Universe,
+};
+
+// pirates leave new empires alone for the first turns
+const QUIET_TURNS: u32 = 10;
+// pirates only raid colonies that are at most this many turns away
+const RAID_RANGE: u32 = 6;
+// mixed with the seed of the game, battles use the star index in the same bits
+const PIRATES_SEED: u64 = u32::MAX as u64;
+
+pub fn play(universe: &Universe, fleets: &mut [FleetState], turn: u32) -> Vec<Event> {
+    let pirates = universe.pirates;
+    // the same pirates show up in a game, its replays and a tournament on the same seed
+    let rand = &mut StdRng::seed_from_u64(universe.seed ^ ((turn as u64) << 32) ^ PIRATES_SEED);
+    let mut events = vec![];
+
+    let defenders: HashMap<usize, u32> = fleets.iter().fold(HashMap::new(), |mut acc, fleet| {
+        if let Order::Orbit(around) = &fleet.order {
+            if fleet.owner != pirates && fleet.kind.attack() > 0.0 {
+                *acc.entry(*around).or_default() += fleet.size;
+            }
+        }
+        acc
+    });
+
+    for fleet in fleets.iter_mut() {
+        if fleet.owner != pirates || fleet.kind != ShipKind::Fighter {
+            continue;
+        }
+        let Order::Orbit(n) = fleet.order else {
+            continue;
+        };
+        let current_position = universe.galaxy[n].position;
+
+        // look for the closest colony that can't resist the raid
+        let target = universe
+            .star_details
+            .iter()
+            .enumerate()
+            .filter(|(i, details)| *i != n && details.owner != usize::MAX)
+            .filter(|(i, details)| {
+                fleet.size as f32
+                    > defenders.get(i).copied().unwrap_or_default() as f32
+                        + details.population / 10.0
+            })
+            .map(|(i, _)| {
+                (
+                    i,
+                    turns_between(
+                        current_position,
+                        universe.galaxy[i].position,
+                        universe.speed(pirates, fleet.kind),
+                    ),
+                )
+            })
+            .filter(|(_, distance)| *distance <= RAID_RANGE)
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(i, _)| i);
+
+        if let Some(target) = target {
+            fleet.order = Order::Move {
+                from: n,
+                to: target,
+                step: 0,
+                waypoints: vec![],
+                departure: None,
+            };
+            events.push(Event::Message {
+                player: universe.star_details[target].owner,
+                message: Message::PirateRaid {
+                    star_name: universe.galaxy[target].name.clone(),
+                    index: target,
+                    fleet_size: fleet.size,
+                },
+            });
+        }
+    }
+
+    if turn > QUIET_TURNS {
+        let active_raids = fleets.iter().filter(|fleet| fleet.owner == pirates).count();
+        // the pirates are the last player, after all the empires
+        let nb_empires = universe.players.len() - 1;
+        let max_raids = nb_empires + (turn / 20) as usize;
+
+        if active_raids < max_raids && rand.gen_bool(0.25) {
+            // pirates gather around stars that nobody claimed
+            if let Some(lair) = universe
+                .star_details
+                .iter()
+                .enumerate()
+                .filter(|(_, details)| details.owner == usize::MAX)
+                .choose(rand)
+                .map(|(i, _)| i)
+            {
+                let fleet_size = ((2 + rand.gen_range(0..=(turn / 10))) as f32
+                    / universe.difficulty)
+                    .ceil() as u32;
+                events.push(Event::FleetSpawned(Fleet {
+                    order: Order::Orbit(lair),
+                    ship: Ship {
+                        kind: ShipKind::Fighter,
+                    },
+                    size: FleetSize(fleet_size),
+                    owner: Owner(pirates),
+                }));
+                for player in 0..pirates {
+                    if universe.players[player].vision[lair] != StarState::Unknown {
+                        events.push(Event::Message {
+                            player,
+                            message: Message::PiratesSighted {
+                                star_name: universe.galaxy[lair].name.clone(),
+                                index: lair,
+                                fleet_size,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    events
+}
+
+pub fn run_pirates_turn(
+    universe: Res<Universe>,
+    mut state: ResMut<State<TurnState>>,
+    mut fleets_to_spawn: ResMut<FleetsToSpawn>,
+    mut fleets: FleetQuery,
+    mut turns: ResMut<Turns>,
+) {
+    let mut states = fleet_states(&fleets);
+    let events = play(&universe, &mut states, turns.count);
+    update_fleets(&mut fleets, &states);
+
+    for event in events {
+        match event {
+            Event::FleetSpawned(fleet) => fleets_to_spawn.0.push(fleet),
+            Event::Message { player, message } => turns
+                .bypass_change_detection()
+                .send(&universe, player, message),
+            _ => (),
+        }
+    }
+
+    let _ = state.set(TurnState::Player);
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::{fleet::ShipKind, simulation::OrderError, Controller, Universe};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub credits: f32,
+    pub resources: f32,
+}
+
+impl Universe {
+    // with the handicap of bots and the discounts from research
+    pub fn price(&self, player: usize, kind: ShipKind, size: u32) -> Price {
+        let base = self.balance.ship(kind);
+        let modifier = self.price_modifier(player);
+        Price {
+            credits: base.credits * size as f32 * modifier,
+            resources: base.resources * size as f32 * modifier,
+        }
+    }
+
+    fn price_modifier(&self, player: usize) -> f32 {
+        let handicap = match self.players[player].controller {
+            // difficulty is a handicap for the computer opponents
+            Controller::Bot | Controller::External => self.difficulty,
+            Controller::Human | Controller::Remote => 1.0,
+        };
+        handicap * self.research_price_modifier(player)
+    }
+
+    pub fn can_afford(&self, player: usize, price: Price) -> Result<(), OrderError> {
+        if self.players[player].savings < price.credits {
+            Err(OrderError::InsufficientSavings)
+        } else if self.players[player].resources < price.resources {
+            Err(OrderError::InsufficientResources)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn buy(&mut self, player: usize, price: Price) -> Result<(), OrderError> {
+        self.can_afford(player, price)?;
+        self.players[player].savings -= price.credits;
+        self.players[player].resources -= price.resources;
+        Ok(())
+    }
+}
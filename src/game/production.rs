@@ -0,0 +1,163 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fleet::ShipKind,
+    pricing::Price,
+    simulation::{Build, OrderError},
+    Universe,
+};
+
+// ships ordered at a colony, paid for when ordered and built over the following turns
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Production {
+    pub owner: usize,
+    pub kind: ShipKind,
+    pub size: u32,
+    pub paid: Price,
+    // work already put in the ships
+    pub progress: f32,
+}
+
+// changes to the production queue of a star, applied in the order they were made
+#[derive(Clone, Serialize, Deserialize)]
+pub enum QueueChange {
+    Build(Build),
+    // star and position in its queue
+    MoveUp(usize, usize),
+    Cancel(usize, usize),
+}
+
+// queue changes made during this turn that the host doesn't know about yet
+#[derive(Resource, Default)]
+pub struct PendingQueueChanges(pub Vec<QueueChange>);
+
+impl Universe {
+    pub fn queue(&self, star: usize) -> &[Production] {
+        self.queues.get(star).map_or(&[], |queue| queue.as_slice())
+    }
+
+    // sized on first use, new games start without any
+    fn queue_mut(&mut self, star: usize) -> &mut Vec<Production> {
+        if self.queues.len() < self.galaxy.len() {
+            self.queues.resize(self.galaxy.len(), vec![]);
+        }
+        &mut self.queues[star]
+    }
+
+    // work a star puts in its queue each turn
+    pub fn production_rate(&self, star: usize) -> f32 {
+        self.balance.production.work(
+            self.star_details[star].population,
+            self.star_ressource(star),
+        )
+    }
+
+    pub fn work_needed(&self, kind: ShipKind, size: u32) -> f32 {
+        let cost = self.balance.ship(kind);
+        (cost.credits + cost.resources) * size as f32
+    }
+
+    // turns before the ships at this position in the queue are built
+    pub fn turns_left(&self, star: usize, position: usize) -> u32 {
+        let work = self.queue(star)[..=position]
+            .iter()
+            .map(|item| self.work_needed(item.kind, item.size) - item.progress)
+            .sum::<f32>();
+        (work / self.production_rate(star)).ceil().max(1.0) as u32
+    }
+
+    pub fn apply_queue_change(
+        &mut self,
+        player: usize,
+        change: &QueueChange,
+    ) -> Result<(), OrderError> {
+        match change {
+            QueueChange::Build(build) => self.enqueue(player, build),
+            QueueChange::MoveUp(star, position) => {
+                self.check_queue(player, *star, *position)?;
+                if *position > 0 {
+                    self.queue_mut(*star).swap(*position - 1, *position);
+                }
+                Ok(())
+            }
+            QueueChange::Cancel(star, position) => {
+                self.check_queue(player, *star, *position)?;
+                // nothing is lost as long as the ships are not built
+                let item = self.queue_mut(*star).remove(*position);
+                self.refund(&item);
+                Ok(())
+            }
+        }
+    }
+
+    fn refund(&mut self, item: &Production) {
+        self.players[item.owner].savings += item.paid.credits;
+        self.players[item.owner].resources += item.paid.resources;
+    }
+
+    fn check_queue(&self, player: usize, star: usize, position: usize) -> Result<(), OrderError> {
+        match self.star_details.get(star) {
+            None => Err(OrderError::UnknownStar(star)),
+            Some(details) if details.owner != player => Err(OrderError::NotYourColony(star)),
+            Some(_) if position >= self.queue(star).len() => {
+                Err(OrderError::NotInQueue(star, position))
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn enqueue(&mut self, player: usize, build: &Build) -> Result<(), OrderError> {
+        if build.size == 0 {
+            return Err(OrderError::EmptyBuild);
+        }
+        match self.star_details.get(build.star) {
+            None => return Err(OrderError::UnknownStar(build.star)),
+            Some(details) if details.owner != player => {
+                return Err(OrderError::NotYourColony(build.star))
+            }
+            Some(_) => (),
+        }
+        if !self.can_build(player, build.kind) {
+            return Err(OrderError::Locked(build.kind));
+        }
+        let price = self.price(player, build.kind, build.size);
+        self.buy(player, price)?;
+        self.queue_mut(build.star).push(Production {
+            owner: player,
+            kind: build.kind,
+            size: build.size,
+            paid: price,
+            progress: 0.0,
+        });
+        Ok(())
+    }
+
+    // returns what was built this turn, work left over goes to the next ships in the queue
+    pub fn advance_production(&mut self, star: usize) -> Vec<Production> {
+        let owner = self.star_details[star].owner;
+        // a colony that changed hands gives back what its previous owner paid, as a cancel does
+        let (kept, lost): (Vec<_>, Vec<_>) = std::mem::take(self.queue_mut(star))
+            .into_iter()
+            .partition(|item| item.owner == owner);
+        for item in &lost {
+            self.refund(item);
+        }
+        self.queues[star] = kept;
+        if self.queue(star).is_empty() {
+            return vec![];
+        }
+        let mut work = self.production_rate(star);
+        let mut built = vec![];
+        while let Some(item) = self.queues[star].first() {
+            let needed = self.work_needed(item.kind, item.size) - item.progress;
+            if work < needed {
+                self.queues[star][0].progress += work;
+                break;
+            }
+            work -= needed;
+            built.push(self.queues[star].remove(0));
+        }
+        built
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{fleet::ShipKind, simulation::OrderError, Universe};
+
+// research points given each turn by every inhabitant of a player
+const POPULATION_RESEARCH: f32 = 0.01;
+const PROPULSION_SPEED: f32 = 1.25;
+const WEAPONS_ATTACK: f32 = 1.3;
+const LOGISTICS_PRICE: f32 = 0.85;
+// how much higher the population of a star of another color than the starting star can go
+const ADAPTATION_POPULATION: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Tech {
+    Scouting,
+    Propulsion,
+    Weapons,
+    Bombers,
+    Invasion,
+    Fortification,
+    Adaptation,
+    Logistics,
+}
+
+impl Tech {
+    pub const ALL: [Tech; 8] = [
+        Tech::Scouting,
+        Tech::Propulsion,
+        Tech::Weapons,
+        Tech::Bombers,
+        Tech::Invasion,
+        Tech::Fortification,
+        Tech::Adaptation,
+        Tech::Logistics,
+    ];
+
+    // research points needed
+    pub fn cost(&self) -> f32 {
+        match self {
+            Tech::Scouting => 15.0,
+            Tech::Propulsion => 40.0,
+            Tech::Weapons => 30.0,
+            Tech::Bombers => 50.0,
+            Tech::Invasion => 60.0,
+            Tech::Fortification => 40.0,
+            Tech::Adaptation => 50.0,
+            Tech::Logistics => 60.0,
+        }
+    }
+
+    pub fn requires(&self) -> Option<Tech> {
+        match self {
+            Tech::Propulsion => Some(Tech::Scouting),
+            Tech::Bombers | Tech::Invasion => Some(Tech::Weapons),
+            Tech::Logistics => Some(Tech::Propulsion),
+            Tech::Scouting | Tech::Weapons | Tech::Fortification | Tech::Adaptation => None,
+        }
+    }
+
+    // colony ships and fighters can always be built
+    pub fn unlocks(&self) -> Option<ShipKind> {
+        match self {
+            Tech::Scouting => Some(ShipKind::Scout),
+            Tech::Bombers => Some(ShipKind::Bomber),
+            Tech::Invasion => Some(ShipKind::Troops),
+            Tech::Fortification => Some(ShipKind::Platform),
+            Tech::Propulsion | Tech::Weapons | Tech::Adaptation | Tech::Logistics => None,
+        }
+    }
+
+    // wrapped for the research panel and the message panel
+    pub fn description(&self) -> &'static str {
+        match self {
+            Tech::Scouting => "Scouts can be built.",
+            Tech::Propulsion => "All ships travel\nfaster.",
+            Tech::Weapons => "Fighters hit harder.",
+            Tech::Bombers => "Bombers can be built.",
+            Tech::Invasion => "Troop transports can\nbe built.",
+            Tech::Fortification => "Defense platforms can\nbe built.",
+            Tech::Adaptation => "Colonies grow better\naround other stars.",
+            Tech::Logistics => "Ships are cheaper.",
+        }
+    }
+}
+
+impl fmt::Display for Tech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tech::Scouting => write!(f, "Scouting"),
+            Tech::Propulsion => write!(f, "Propulsion"),
+            Tech::Weapons => write!(f, "Weapons"),
+            Tech::Bombers => write!(f, "Bombers"),
+            Tech::Invasion => write!(f, "Invasion"),
+            Tech::Fortification => write!(f, "Fortification"),
+            Tech::Adaptation => write!(f, "Adaptation"),
+            Tech::Logistics => write!(f, "Logistics"),
+        }
+    }
+}
+
+// progress of a player through the techs
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Research {
+    pub known: Vec<Tech>,
+    pub current: Option<Tech>,
+    // points already put in the current tech
+    pub progress: f32,
+    // credits invested each turn, on top of what the population brings
+    pub funding: f32,
+}
+
+impl Universe {
+    pub fn research(&self, player: usize) -> &Research {
+        &self.players[player].research
+    }
+
+    pub fn knows(&self, player: usize, tech: Tech) -> bool {
+        self.players[player].research.known.contains(&tech)
+    }
+
+    pub fn can_research(&self, player: usize, tech: Tech) -> bool {
+        !self.knows(player, tech)
+            && tech
+                .requires()
+                .is_none_or(|required| self.knows(player, required))
+    }
+
+    pub fn can_build(&self, player: usize, kind: ShipKind) -> bool {
+        Tech::ALL
+            .iter()
+            .find(|tech| tech.unlocks() == Some(kind))
+            .is_none_or(|tech| self.knows(player, *tech))
+    }
+
+    // switching to another tech loses the points put in the current one
+    pub fn choose_research(&mut self, player: usize, tech: Tech) -> Result<(), OrderError> {
+        if self.knows(player, tech) {
+            return Err(OrderError::AlreadyResearched(tech));
+        }
+        if !self.can_research(player, tech) {
+            return Err(OrderError::MissingTech(tech.requires().unwrap_or(tech)));
+        }
+        let research = &mut self.players[player].research;
+        if research.current != Some(tech) {
+            research.current = Some(tech);
+            research.progress = 0.0;
+        }
+        Ok(())
+    }
+
+    pub fn fund_research(&mut self, player: usize, funding: f32) -> Result<(), OrderError> {
+        if !funding.is_finite() || funding < 0.0 {
+            return Err(OrderError::InvalidFunding);
+        }
+        self.players[player].research.funding = funding;
+        Ok(())
+    }
+
+    // research points a player will get next turn
+    pub fn research_rate(&self, player: usize) -> f32 {
+        self.players[player]
+            .research
+            .funding
+            .min(self.players[player].savings.max(0.0))
+            + self.player_population(player) * POPULATION_RESEARCH
+    }
+
+    // spends the funding and returns the tech completed this turn
+    pub fn advance_research(&mut self, player: usize) -> Option<Tech> {
+        let tech = self.players[player].research.current?;
+        let rate = self.research_rate(player);
+        let player = &mut self.players[player];
+        player.savings -= player.research.funding.min(player.savings.max(0.0));
+        player.research.progress += rate;
+        if player.research.progress < tech.cost() {
+            return None;
+        }
+        player.research.known.push(tech);
+        player.research.current = None;
+        player.research.progress = 0.0;
+        Some(tech)
+    }
+
+    pub fn speed_modifier(&self, player: usize) -> f32 {
+        if self.knows(player, Tech::Propulsion) {
+            PROPULSION_SPEED
+        } else {
+            1.0
+        }
+    }
+
+    pub fn attack_modifier(&self, player: usize, kind: ShipKind) -> f32 {
+        if kind == ShipKind::Fighter && self.knows(player, Tech::Weapons) {
+            WEAPONS_ATTACK
+        } else {
+            1.0
+        }
+    }
+
+    pub fn research_price_modifier(&self, player: usize) -> f32 {
+        if self.knows(player, Tech::Logistics) {
+            LOGISTICS_PRICE
+        } else {
+            1.0
+        }
+    }
+
+    // growth divisor on stars of another size, population cap multiplier on stars of another color
+    pub fn adaptation(&self, player: usize) -> (f32, f32) {
+        if self.knows(player, Tech::Adaptation) {
+            (1.0, ADAPTATION_POPULATION)
+        } else {
+            (self.balance.other_size_growth_divisor, 1.0)
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+
+use super::{
+    bots::BotTurnStatus,
+    fleet::{Fleet, FleetSize, Order, Owner, Ship, ShipKind},
+    galaxy::{GalaxyCreator, GalaxyKind},
+    stats::StatsHistory,
+    turns::{CurrentPlayer, JournalEntry, Message, TurnState, Turns},
+    ui::SelectedStar,
+    Controller, FleetsToSpawn, Universe,
+};
+
+// saves with a different version are refused instead of being loaded half right
+const SAVE_VERSION: u32 = 4;
+const SAVE_DIRECTORY: &str = "saves";
+
+#[derive(Clone, Copy, Debug)]
+pub enum SaveSlot {
+    Auto,
+    Manual,
+}
+
+impl SaveSlot {
+    fn path(&self) -> PathBuf {
+        PathBuf::from(SAVE_DIRECTORY).join(match self {
+            SaveSlot::Auto => "autosave.ron",
+            SaveSlot::Manual => "save.ron",
+        })
+    }
+}
+
+pub struct SaveEvent(pub SaveSlot);
+
+// the current turn of a loaded game has already been resolved
+#[derive(Resource)]
+pub struct LoadedGame;
+
+#[derive(Serialize, Deserialize)]
+pub struct Settings {
+    nb_players: u32,
+    size: f32,
+    density: f32,
+    kind: GalaxyKind,
+    difficulty: f32,
+}
+
+impl Settings {
+    pub fn new(creator: &GalaxyCreator) -> Self {
+        Settings {
+            nb_players: creator.nb_players,
+            size: creator.size,
+            density: creator.density,
+            kind: creator.kind,
+            difficulty: creator.difficulty,
+        }
+    }
+
+    pub fn creator(&self, universe: &Universe) -> GalaxyCreator {
+        GalaxyCreator {
+            nb_players: self.nb_players,
+            size: self.size,
+            density: self.density,
+            kind: self.kind,
+            generated: universe.galaxy.clone(),
+            names: vec![],
+            difficulty: self.difficulty,
+            seed: universe.seed,
+            rng: StdRng::seed_from_u64(universe.seed),
+            controllers: universe
+                .players
+                .iter()
+                .map(|player| player.controller)
+                .collect(),
+            personalities: universe
+                .players
+                .iter()
+                .map(|player| player.personality)
+                .collect(),
+            victory: universe.victory,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedFleet {
+    order: Order,
+    kind: ShipKind,
+    size: u32,
+    owner: usize,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    settings: Settings,
+    universe: Universe,
+    turn: u32,
+    // the messages of the current player, shown again once loaded
+    messages: Vec<Message>,
+    pending: Vec<Vec<Message>>,
+    journal: Vec<Vec<JournalEntry>>,
+    history: StatsHistory,
+    current: CurrentPlayer,
+    bots: BotTurnStatus,
+    fleets: Vec<SavedFleet>,
+}
+
+impl SaveGame {
+    fn capture<'a>(
+        creator: &GalaxyCreator,
+        universe: &Universe,
+        turns: &Turns,
+        history: &StatsHistory,
+        current: &CurrentPlayer,
+        bots: &BotTurnStatus,
+        fleets: impl Iterator<Item = (&'a Order, &'a Ship, &'a FleetSize, &'a Owner)>,
+        fleets_to_spawn: &'a FleetsToSpawn,
+    ) -> Self {
+        SaveGame {
+            version: SAVE_VERSION,
+            settings: Settings::new(creator),
+            universe: universe.clone(),
+            turn: turns.count,
+            messages: turns.messages.clone(),
+            pending: turns.pending.clone(),
+            journal: turns.journal.clone(),
+            history: history.clone(),
+            current: current.clone(),
+            bots: bots.clone(),
+            fleets: fleets
+                .chain(
+                    fleets_to_spawn
+                        .0
+                        .iter()
+                        .map(|fleet| (&fleet.order, &fleet.ship, &fleet.size, &fleet.owner)),
+                )
+                .map(|(order, ship, size, owner)| SavedFleet {
+                    order: order.clone(),
+                    kind: ship.kind,
+                    size: size.0,
+                    owner: owner.0,
+                })
+                .collect(),
+        }
+    }
+
+    fn write(&self, slot: SaveSlot) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIRECTORY)?;
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(slot.path(), content)?;
+        Ok(())
+    }
+
+    pub fn load(slot: SaveSlot) -> anyhow::Result<Self> {
+        Self::parse(&fs::read_to_string(slot.path())?)
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let header: Header = ron::from_str(content)?;
+        if header.version != SAVE_VERSION {
+            anyhow::bail!("unsupported save version {}", header.version);
+        }
+        Ok(ron::from_str(content)?)
+    }
+
+    pub fn restore(self, commands: &mut Commands) {
+        // players on the network or in other programs can't follow a loaded game
+        let mut universe = self.universe;
+        for player in universe.players.iter_mut() {
+            if matches!(player.controller, Controller::Remote | Controller::External) {
+                player.controller = Controller::Bot;
+            }
+        }
+        commands.insert_resource(self.settings.creator(&universe));
+        commands.insert_resource(FleetsToSpawn(
+            self.fleets
+                .into_iter()
+                .map(|fleet| Fleet {
+                    order: fleet.order,
+                    ship: Ship { kind: fleet.kind },
+                    size: FleetSize(fleet.size),
+                    owner: Owner(fleet.owner),
+                })
+                .collect(),
+        ));
+        let mut journal = self.journal;
+        journal.resize(self.pending.len(), vec![]);
+        commands.insert_resource(Turns {
+            count: self.turn,
+            messages: self.messages,
+            pending: self.pending,
+            journal,
+        });
+        commands.insert_resource(self.history);
+        commands.insert_resource(self.current);
+        commands.insert_resource(self.bots);
+        commands.insert_resource(Universe {
+            star_entities: Vec::with_capacity(universe.galaxy.len()),
+            ..universe
+        });
+        commands.init_resource::<SelectedStar>();
+        commands.insert_resource(LoadedGame);
+    }
+}
+
+pub struct Plugin;
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveEvent>()
+            .add_system_set(SystemSet::on_update(TurnState::Player).with_system(autosave))
+            .add_system_set(SystemSet::on_update(GameState::Game).with_system(save_game));
+    }
+}
+
+fn autosave(
+    state: Res<State<GameState>>,
+    creator: Res<GalaxyCreator>,
+    universe: Res<Universe>,
+    turns: Res<Turns>,
+    history: Res<StatsHistory>,
+    current: Res<CurrentPlayer>,
+    bots: Res<BotTurnStatus>,
+    fleets: Query<(&Order, &Ship, &FleetSize, &Owner)>,
+    fleets_to_spawn: Res<FleetsToSpawn>,
+) {
+    // once a human starts their turn, before they give any order, so that loading doesn't
+    // resolve the turn again
+    if !current.is_changed() {
+        return;
+    }
+    if current.remote {
+        // the host keeps the game
+        return;
+    }
+    if turns.messages.iter().any(|message| {
+        matches!(
+            message,
+            Message::Win | Message::Victory { .. } | Message::RivalVictory { .. }
+        )
+    }) || current.game_over(&universe)
+    {
+        // nothing left to continue
+        let _ = fs::remove_file(SaveSlot::Auto.path());
+        return;
+    }
+    if *state.current() != GameState::Game {
+        return;
+    }
+
+    let save = SaveGame::capture(
+        &creator,
+        &universe,
+        &turns,
+        &history,
+        &current,
+        &bots,
+        fleets.iter(),
+        &fleets_to_spawn,
+    );
+    if let Err(error) = save.write(SaveSlot::Auto) {
+        warn!("error saving game: {}", error);
+    }
+}
+
+fn save_game(
+    mut events: EventReader<SaveEvent>,
+    creator: Res<GalaxyCreator>,
+    universe: Res<Universe>,
+    turns: Res<Turns>,
+    history: Res<StatsHistory>,
+    current: Res<CurrentPlayer>,
+    bots: Res<BotTurnStatus>,
+    fleets: Query<(&Order, &Ship, &FleetSize, &Owner)>,
+    fleets_to_spawn: Res<FleetsToSpawn>,
+) {
+    for SaveEvent(slot) in events.iter() {
+        if current.remote {
+            warn!("games hosted on another computer can't be saved");
+            continue;
+        }
+        let save = SaveGame::capture(
+            &creator,
+            &universe,
+            &turns,
+            &history,
+            &current,
+            &bots,
+            fleets.iter(),
+            &fleets_to_spawn,
+        );
+        match save.write(*slot) {
+            Ok(()) => info!("game saved to {:?}", slot.path()),
+            Err(error) => warn!("error saving game: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+    use crate::game::{simulation, victory::VictoryRules};
+
+    fn game() -> (GalaxyCreator, Universe, Vec<Fleet>) {
+        let mut creator = GalaxyCreator {
+            nb_players: 3,
+            size: 2.0,
+            density: 2.5,
+            kind: GalaxyKind::Spiral,
+            generated: vec![],
+            names: (1..=100).map(|i| format!("Star {}", i)).collect(),
+            difficulty: 1.0,
+            seed: 5,
+            rng: StdRng::seed_from_u64(5),
+            controllers: vec![Controller::Human, Controller::Bot, Controller::Bot],
+            personalities: vec![],
+            victory: VictoryRules::default(),
+        };
+        for _ in creator.by_ref() {}
+        let (universe, fleets) = simulation::new_game(&mut creator);
+        (creator, universe, fleets)
+    }
+
+    #[test]
+    fn save_then_load() {
+        let (creator, universe, fleets) = game();
+        let turns = Turns {
+            count: 4,
+            messages: vec![Message::Turn(4), Message::Win],
+            pending: vec![vec![], vec![Message::Turn(4)], vec![]],
+            journal: vec![vec![]; 3],
+        };
+        let save = SaveGame::capture(
+            &creator,
+            &universe,
+            &turns,
+            &StatsHistory::default(),
+            &CurrentPlayer::default(),
+            &BotTurnStatus::default(),
+            std::iter::empty(),
+            &FleetsToSpawn(fleets.clone()),
+        );
+        let content = ron::to_string(&save).unwrap();
+        let loaded = SaveGame::parse(&content).unwrap();
+        assert_eq!(ron::to_string(&loaded).unwrap(), content);
+
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        loaded.restore(&mut Commands::new(&mut queue, &world));
+        queue.apply(&mut world);
+        assert_eq!(
+            ron::to_string(&world.resource::<Turns>().messages).unwrap(),
+            ron::to_string(&turns.messages).unwrap()
+        );
+        assert_eq!(world.resource::<FleetsToSpawn>().0.len(), fleets.len());
+        assert_eq!(
+            ron::to_string(world.resource::<Universe>()).unwrap(),
+            ron::to_string(&universe).unwrap()
+        );
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let (creator, universe, _) = game();
+        let mut save = SaveGame::capture(
+            &creator,
+            &universe,
+            &Turns {
+                count: 1,
+                messages: vec![],
+                pending: vec![vec![]; 3],
+                journal: vec![vec![]; 3],
+            },
+            &StatsHistory::default(),
+            &CurrentPlayer::default(),
+            &BotTurnStatus::default(),
+            std::iter::empty(),
+            &FleetsToSpawn(vec![]),
+        );
+        save.version = SAVE_VERSION - 1;
+        assert!(SaveGame::parse(&ron::to_string(&save).unwrap()).is_err());
+    }
+}
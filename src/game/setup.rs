@@ -1,6 +1,5 @@
-use std::f32::consts::PI;
-
 use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
     prelude::*,
     render::{
         camera::RenderTarget,
@@ -11,19 +10,26 @@ use bevy::{
     },
     sprite::MaterialMesh2dBundle,
 };
-use rand::{seq::SliceRandom, Rng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::game::{external, network};
 use crate::{
-    assets::{names::Names, GalaxyAssets, UiAssets},
+    assets::{balance::Balance, names::Names, GalaxyAssets, UiAssets},
     game::{
-        bots::BotTurnStatus,
-        fleet::{Fleet, FleetSize, Order, Owner, Ship, ShipKind},
-        galaxy::{GalaxyKind, StarSize},
-        turns::Turns,
+        bots::{BotTurnStatus, Personality},
+        galaxy::GalaxyKind,
+        simulation,
+        stats::StatsHistory,
+        turns::{CurrentPlayer, Turns},
         ui::SelectedStar,
-        FleetsToSpawn, Player, StarDetails, StarState, Universe,
+        victory::{VictoryCondition, VictoryRules},
+        Controller, FleetsToSpawn,
+    },
+    ui_helper::{
+        button::{ButtonId, ButtonText},
+        ColorScheme,
     },
-    ui_helper::{button::ButtonId, ColorScheme},
     GameState,
 };
 
@@ -46,15 +52,25 @@ impl bevy::app::Plugin for Plugin {
                     .with_system(display_galaxy)
                     .with_system(ui_button_system)
                     .with_system(setting_button)
-                    .with_system(action_button),
+                    .with_system(limit_players)
+                    .with_system(slot_button)
+                    .with_system(personality_button)
+                    .with_system(victory_button)
+                    .with_system(action_button)
+                    .with_system(edit_seed)
+                    .with_system(display_seed),
             )
-            .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down));
+            .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down))
+            .add_system_set(SystemSet::on_exit(GameState::Lost).with_system(rematch));
     }
 }
 
 #[derive(Component)]
 struct GalaxyPreview;
 
+#[derive(Component)]
+struct SeedText;
+
 fn setup(
     mut commands: Commands,
     ui_handles: Res<UiAssets>,
@@ -107,14 +123,28 @@ fn setup(
         },
         ScreenTag,
     ));
+    let seed = rand::thread_rng().gen_range(0..100_000_000);
     let galaxy = GalaxyCreator {
         generated: Vec::new(),
         nb_players: 2,
         size: SizeControl::default().into(),
         density: DensityControl::default().into(),
-        _kind: GalaxyKind::default(),
+        kind: GalaxyKind::default(),
         difficulty: DifficultyControl::default().into(),
         names: names.get(&galaxy_handles.star_names).unwrap().names.clone(),
+        seed,
+        rng: StdRng::seed_from_u64(seed),
+        controllers: (0..8)
+            .map(|slot| {
+                if slot == 0 {
+                    Controller::Human
+                } else {
+                    Controller::Bot
+                }
+            })
+            .collect(),
+        personalities: vec![Personality::default(); 8],
+        victory: VictoryRules::default(),
     };
 
     let category_style = Style {
@@ -229,18 +259,30 @@ fn setup(
                 ..Default::default()
             })
             .id();
-        let spiral = button.add(
-            &mut commands,
-            Val::Px(height / 6.0),
-            Val::Px(height / 20.0),
-            UiRect::all(Val::Auto),
-            ui_handles.font_main.clone_weak(),
-            GalaxyControl::Kind(GalaxyKind::Spiral),
-            height / 40.0,
-            crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
-        );
-        commands.entity(spiral).insert(Selected);
-        commands.entity(row).push_children(&[text, spiral]);
+        let mut children = vec![text];
+        for kind in [
+            GalaxyKind::Spiral,
+            GalaxyKind::Elliptical,
+            GalaxyKind::Ring,
+            GalaxyKind::Clusters,
+            GalaxyKind::Irregular,
+        ] {
+            let button_entity = button.add(
+                &mut commands,
+                Val::Px(height / 8.0),
+                Val::Px(height / 20.0),
+                UiRect::all(Val::Auto),
+                ui_handles.font_main.clone_weak(),
+                GalaxyControl::Kind(kind),
+                height / 40.0,
+                crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
+            );
+            if kind == galaxy.kind {
+                commands.entity(button_entity).insert(Selected);
+            }
+            children.push(button_entity);
+        }
+        commands.entity(row).push_children(&children);
         row
     };
 
@@ -267,7 +309,7 @@ fn setup(
             })
             .id();
         let mut children = vec![text];
-        for size_control in [SizeControl::Small, SizeControl::Medium, SizeControl::Large] {
+        for size_control in SizeControl::ALL {
             let button_entity = button.add(
                 &mut commands,
                 Val::Px(height / 6.0),
@@ -310,11 +352,7 @@ fn setup(
             })
             .id();
         let mut children = vec![text];
-        for density_control in [
-            DensityControl::Sparse,
-            DensityControl::Normal,
-            DensityControl::Dense,
-        ] {
+        for density_control in DensityControl::ALL {
             let button_entity = button.add(
                 &mut commands,
                 Val::Px(height / 6.0),
@@ -357,10 +395,10 @@ fn setup(
             })
             .id();
         let mut children = vec![text];
-        for nb in [2, 3, 4, 5] {
+        for nb in 2..=8 {
             let button_entity = button.add(
                 &mut commands,
-                Val::Px(height / 8.0),
+                Val::Px(height / 16.0),
                 Val::Px(height / 20.0),
                 UiRect::all(Val::Auto),
                 ui_handles.font_main.clone_weak(),
@@ -377,16 +415,99 @@ fn setup(
         row
     };
 
+    let row_humans = {
+        let row = commands
+            .spawn(NodeBundle {
+                style: row_style.clone(),
+                ..Default::default()
+            })
+            .id();
+        let text = commands
+            .spawn(TextBundle {
+                style: category_style.clone(),
+                text: Text::from_section(
+                    "humans".to_string(),
+                    TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        color: ColorScheme::TEXT,
+                        font_size: height / 30.0,
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            })
+            .id();
+        let mut children = vec![text];
+        for slot in 0..8 {
+            let button_entity = button.add(
+                &mut commands,
+                Val::Px(height / 16.0),
+                Val::Px(height / 20.0),
+                UiRect::all(Val::Auto),
+                ui_handles.font_main.clone_weak(),
+                SlotControl(slot),
+                height / 40.0,
+                crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
+            );
+            if galaxy.controllers[slot] != Controller::Bot {
+                commands.entity(button_entity).insert(Selected);
+            }
+            children.push(button_entity);
+        }
+        commands.entity(row).push_children(&children);
+        row
+    };
+
+    let row_bots = {
+        let row = commands
+            .spawn(NodeBundle {
+                style: row_style.clone(),
+                ..Default::default()
+            })
+            .id();
+        let text = commands
+            .spawn(TextBundle {
+                style: category_style.clone(),
+                text: Text::from_section(
+                    "bots".to_string(),
+                    TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        color: ColorScheme::TEXT,
+                        font_size: height / 30.0,
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            })
+            .id();
+        let mut children = vec![text];
+        for slot in 0..8 {
+            let button_entity = button.add(
+                &mut commands,
+                Val::Px(height / 16.0),
+                Val::Px(height / 20.0),
+                UiRect::all(Val::Auto),
+                ui_handles.font_main.clone_weak(),
+                PersonalityControl(slot),
+                height / 40.0,
+                crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
+            );
+            children.push(button_entity);
+        }
+        commands.entity(row).push_children(&children);
+        row
+    };
+
     let row_difficulty = {
         let row = commands
             .spawn(NodeBundle {
-                style: row_style,
+                style: row_style.clone(),
                 ..Default::default()
             })
             .id();
         let text = commands
             .spawn(TextBundle {
-                style: category_style,
+                style: category_style.clone(),
                 text: Text::from_section(
                     "difficulty".to_string(),
                     TextStyle {
@@ -424,6 +545,108 @@ fn setup(
         row
     };
 
+    let row_seed = {
+        let row = commands
+            .spawn(NodeBundle {
+                style: row_style.clone(),
+                ..Default::default()
+            })
+            .id();
+        let text = commands
+            .spawn(TextBundle {
+                style: category_style.clone(),
+                text: Text::from_section(
+                    "seed".to_string(),
+                    TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        color: ColorScheme::TEXT,
+                        font_size: height / 30.0,
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            })
+            .id();
+        let seed_text = commands
+            .spawn((
+                TextBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Auto),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        galaxy.seed.to_string(),
+                        TextStyle {
+                            font: ui_handles.font_sub.clone_weak(),
+                            color: ColorScheme::TEXT,
+                            font_size: height / 30.0,
+                            ..Default::default()
+                        },
+                    ),
+                    ..Default::default()
+                },
+                SeedText,
+            ))
+            .id();
+        let random = button.add(
+            &mut commands,
+            Val::Px(height / 6.0),
+            Val::Px(height / 20.0),
+            UiRect::all(Val::Auto),
+            ui_handles.font_main.clone_weak(),
+            Action::RandomSeed,
+            height / 40.0,
+            crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
+        );
+        commands
+            .entity(row)
+            .push_children(&[text, seed_text, random]);
+        row
+    };
+
+    let row_victory = {
+        let row = commands
+            .spawn(NodeBundle {
+                style: row_style,
+                ..Default::default()
+            })
+            .id();
+        let text = commands
+            .spawn(TextBundle {
+                style: category_style,
+                text: Text::from_section(
+                    "victory".to_string(),
+                    TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        color: ColorScheme::TEXT,
+                        font_size: height / 30.0,
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            })
+            .id();
+        let mut children = vec![text];
+        for condition in VictoryCondition::OPTIONAL {
+            let button_entity = button.add(
+                &mut commands,
+                Val::Px(height / 6.0),
+                Val::Px(height / 20.0),
+                UiRect::all(Val::Auto),
+                ui_handles.font_main.clone_weak(),
+                VictoryControl(condition),
+                height / 40.0,
+                crate::ui_helper::ColorScheme::TEXT_HIGHLIGHT,
+            );
+            if galaxy.victory.is_enabled(condition) {
+                commands.entity(button_entity).insert(Selected);
+            }
+            children.push(button_entity);
+        }
+        commands.entity(row).push_children(&children);
+        row
+    };
+
     let action_buttons = {
         let row = commands
             .spawn(NodeBundle {
@@ -472,7 +695,11 @@ fn setup(
         row_size,
         row_density,
         row_players,
+        row_humans,
+        row_bots,
         row_difficulty,
+        row_victory,
+        row_seed,
         action_buttons,
     ]);
 
@@ -541,7 +768,222 @@ fn setting_button(
                 GalaxyControl::Density(density) => creator.density = density.into(),
                 GalaxyControl::Players(nb) => creator.nb_players = nb,
                 GalaxyControl::Difficulty(difficulty) => creator.difficulty = difficulty.into(),
-                GalaxyControl::Kind(_) => (),
+                GalaxyControl::Kind(kind) => creator.kind = kind,
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn limit_players(
+    mut commands: Commands,
+    mut creator: ResMut<GalaxyCreator>,
+    mut buttons: Query<(
+        Entity,
+        &ButtonId<GalaxyControl>,
+        &mut Style,
+        &mut BackgroundColor,
+        Option<&Selected>,
+    )>,
+    mut slots: Query<
+        (
+            Entity,
+            &ButtonId<SlotControl>,
+            &mut Style,
+            &mut BackgroundColor,
+            Option<&Selected>,
+        ),
+        Without<ButtonId<GalaxyControl>>,
+    >,
+    mut texts: Query<(&mut Text, &ButtonText<SlotControl>)>,
+    mut personalities: Query<
+        (&mut Style, &ButtonId<PersonalityControl>),
+        (
+            Without<ButtonId<GalaxyControl>>,
+            Without<ButtonId<SlotControl>>,
+        ),
+    >,
+    mut personality_texts: Query<
+        (&mut Text, &ButtonText<PersonalityControl>),
+        Without<ButtonText<SlotControl>>,
+    >,
+) {
+    if !creator.is_changed() {
+        return;
+    }
+    let max_players = creator.max_players();
+    if creator.nb_players > max_players {
+        creator.nb_players = max_players;
+    }
+    for (entity, control, mut style, mut color, selected) in &mut buttons {
+        let GalaxyControl::Players(nb) = control.0 else {
+            continue;
+        };
+        style.display = if nb <= max_players {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if nb == creator.nb_players && selected.is_none() {
+            commands.entity(entity).insert(Selected);
+            *color = SELECTED_BUTTON.into();
+        } else if nb != creator.nb_players && selected.is_some() {
+            commands.entity(entity).remove::<Selected>();
+            *color = Color::NONE.into();
+        }
+    }
+    let nb_players = creator.nb_players as usize;
+    if !creator.controllers[..nb_players].contains(&Controller::Human) {
+        creator.controllers[0] = Controller::Human;
+    }
+    for (entity, slot, mut style, mut color, selected) in &mut slots {
+        let slot = slot.0 .0;
+        style.display = if slot < nb_players {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if creator.controllers[slot] != Controller::Bot && selected.is_none() {
+            commands.entity(entity).insert(Selected);
+            *color = SELECTED_BUTTON.into();
+        } else if creator.controllers[slot] == Controller::Bot && selected.is_some() {
+            commands.entity(entity).remove::<Selected>();
+            *color = Color::NONE.into();
+        }
+    }
+    for (mut text, slot) in &mut texts {
+        text.sections[0].value = match creator.controllers[slot.0 .0] {
+            Controller::Remote => "LAN".to_string(),
+            Controller::External => "EXT".to_string(),
+            Controller::Human | Controller::Bot => slot.0.into(),
+        };
+    }
+    for (mut style, slot) in &mut personalities {
+        style.display = if slot.0 .0 < nb_players {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    for (mut text, slot) in &mut personality_texts {
+        let slot = slot.0 .0;
+        text.sections[0].value = if creator.controllers[slot] == Controller::Bot {
+            creator.personalities[slot].to_string()[..1].to_string()
+        } else {
+            "-".to_string()
+        };
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SlotControl(usize);
+
+impl From<SlotControl> for String {
+    fn from(slot: SlotControl) -> Self {
+        (slot.0 + 1).to_string()
+    }
+}
+
+// each slot is played by a bot, a human on this computer, one joining over the network or
+// an external program, selected slots are not bots
+#[allow(clippy::type_complexity)]
+fn slot_button(
+    interaction_query: Query<
+        (&Interaction, &ButtonId<SlotControl>),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut creator: ResMut<GalaxyCreator>,
+) {
+    for (interaction, slot) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            let slot = slot.0 .0;
+            let humans = creator.controllers[..creator.nb_players as usize]
+                .iter()
+                .filter(|controller| **controller == Controller::Human)
+                .count();
+            let next = match creator.controllers[slot] {
+                Controller::Bot => Controller::Human,
+                // keep at least one human on this computer to play the game
+                Controller::Human if humans == 1 => Controller::Human,
+                #[cfg(not(target_arch = "wasm32"))]
+                Controller::Human => Controller::Remote,
+                #[cfg(target_arch = "wasm32")]
+                Controller::Human => Controller::Bot,
+                // only offered when there is a program to play it
+                #[cfg(not(target_arch = "wasm32"))]
+                Controller::Remote if external::bot_command().is_some() => Controller::External,
+                Controller::Remote | Controller::External => Controller::Bot,
+            };
+            creator.controllers[slot] = next;
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PersonalityControl(usize);
+
+impl From<PersonalityControl> for String {
+    fn from(_: PersonalityControl) -> Self {
+        "-".to_string()
+    }
+}
+
+// how each bot plays, shown with the initial of its personality
+#[allow(clippy::type_complexity)]
+fn personality_button(
+    interaction_query: Query<
+        (&Interaction, &ButtonId<PersonalityControl>),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut creator: ResMut<GalaxyCreator>,
+) {
+    for (interaction, slot) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            let slot = slot.0 .0;
+            if creator.controllers[slot] == Controller::Bot {
+                creator.personalities[slot] = creator.personalities[slot].next();
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct VictoryControl(VictoryCondition);
+
+impl From<VictoryControl> for String {
+    fn from(control: VictoryControl) -> Self {
+        match control.0 {
+            VictoryCondition::TurnLimit => format!("{} turns", VictoryRules::TURN_LIMIT),
+            VictoryCondition::Domination => "domination".to_string(),
+            VictoryCondition::Economic => "economy".to_string(),
+            VictoryCondition::SpecialStars => "special".to_string(),
+            VictoryCondition::Elimination => "elimination".to_string(),
+        }
+    }
+}
+
+// elimination always wins, any other rule can be added on top of it
+#[allow(clippy::type_complexity)]
+fn victory_button(
+    mut commands: Commands,
+    interaction_query: Query<
+        (
+            &Interaction,
+            &ButtonId<VictoryControl>,
+            Entity,
+            Option<&Selected>,
+        ),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut creator: ResMut<GalaxyCreator>,
+) {
+    for (interaction, control, entity, selected) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            creator.victory.toggle(control.0 .0);
+            if selected.is_some() {
+                commands.entity(entity).remove::<Selected>();
+            } else {
+                commands.entity(entity).insert(Selected);
             }
         }
     }
@@ -551,6 +993,7 @@ fn setting_button(
 enum Action {
     Start,
     Cancel,
+    RandomSeed,
 }
 
 impl From<Action> for String {
@@ -558,6 +1001,7 @@ impl From<Action> for String {
         match action {
             Action::Start => String::from("Start"),
             Action::Cancel => String::from("Cancel"),
+            Action::RandomSeed => String::from("random"),
         }
     }
 }
@@ -569,25 +1013,76 @@ fn action_button(
         (Changed<Interaction>, With<Button>),
     >,
     mut state: ResMut<State<GameState>>,
+    mut creator: ResMut<GalaxyCreator>,
 ) {
     for (interaction, control) in &interaction_query {
         if *interaction == Interaction::Clicked {
             match control.0 {
                 Action::Cancel => state.set(GameState::Menu).unwrap(),
                 Action::Start => state.set(GameState::Game).unwrap(),
+                Action::RandomSeed => creator.seed = rand::thread_rng().gen_range(0..100_000_000),
+            }
+        }
+    }
+}
+
+fn edit_seed(mut keyboard_events: EventReader<KeyboardInput>, mut creator: ResMut<GalaxyCreator>) {
+    for event in keyboard_events.iter() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let digit = match event.key_code {
+            Some(KeyCode::Key0 | KeyCode::Numpad0) => Some(0),
+            Some(KeyCode::Key1 | KeyCode::Numpad1) => Some(1),
+            Some(KeyCode::Key2 | KeyCode::Numpad2) => Some(2),
+            Some(KeyCode::Key3 | KeyCode::Numpad3) => Some(3),
+            Some(KeyCode::Key4 | KeyCode::Numpad4) => Some(4),
+            Some(KeyCode::Key5 | KeyCode::Numpad5) => Some(5),
+            Some(KeyCode::Key6 | KeyCode::Numpad6) => Some(6),
+            Some(KeyCode::Key7 | KeyCode::Numpad7) => Some(7),
+            Some(KeyCode::Key8 | KeyCode::Numpad8) => Some(8),
+            Some(KeyCode::Key9 | KeyCode::Numpad9) => Some(9),
+            Some(KeyCode::Back) => {
+                creator.seed /= 10;
+                None
             }
+            _ => None,
+        };
+        if let Some(seed) = digit.and_then(|digit| {
+            creator
+                .seed
+                .checked_mul(10)
+                .and_then(|seed| seed.checked_add(digit))
+        }) {
+            creator.seed = seed;
+        }
+    }
+}
+
+fn display_seed(creator: Res<GalaxyCreator>, mut text: Query<&mut Text, With<SeedText>>) {
+    if creator.is_changed() {
+        if let Ok(mut text) = text.get_single_mut() {
+            text.sections[0].value = creator.seed.to_string();
         }
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-enum DensityControl {
+pub enum DensityControl {
     #[default]
     Sparse,
     Normal,
     Dense,
 }
 
+impl DensityControl {
+    pub const ALL: [DensityControl; 3] = [
+        DensityControl::Sparse,
+        DensityControl::Normal,
+        DensityControl::Dense,
+    ];
+}
+
 impl From<DensityControl> for f32 {
     fn from(density: DensityControl) -> Self {
         match density {
@@ -599,13 +1094,17 @@ impl From<DensityControl> for f32 {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-enum SizeControl {
+pub enum SizeControl {
     Small,
     #[default]
     Medium,
     Large,
 }
 
+impl SizeControl {
+    pub const ALL: [SizeControl; 3] = [SizeControl::Small, SizeControl::Medium, SizeControl::Large];
+}
+
 impl From<SizeControl> for f32 {
     fn from(density: SizeControl) -> Self {
         match density {
@@ -654,6 +1153,10 @@ impl Into<String> for GalaxyControl {
             GalaxyControl::Density(DensityControl::Dense) => "dense".to_string(),
             GalaxyControl::Players(n) => format!("{}", n),
             GalaxyControl::Kind(GalaxyKind::Spiral) => "spiral".to_string(),
+            GalaxyControl::Kind(GalaxyKind::Elliptical) => "elliptical".to_string(),
+            GalaxyControl::Kind(GalaxyKind::Ring) => "ring".to_string(),
+            GalaxyControl::Kind(GalaxyKind::Clusters) => "clusters".to_string(),
+            GalaxyControl::Kind(GalaxyKind::Irregular) => "irregular".to_string(),
             GalaxyControl::Difficulty(DifficultyControl::Easy) => "easy".to_string(),
             GalaxyControl::Difficulty(DifficultyControl::Normal) => "normal".to_string(),
             GalaxyControl::Difficulty(DifficultyControl::Hard) => "hard".to_string(),
@@ -675,6 +1178,7 @@ fn display_galaxy(
     if creator.is_changed() {
         creator.generated = Vec::new();
         creator.names = names.get(&galaxy_handles.star_names).unwrap().names.clone();
+        creator.rng = StdRng::seed_from_u64(creator.seed);
 
         let entity = preview.single();
         commands.entity(entity).despawn_descendants();
@@ -702,8 +1206,10 @@ fn display_galaxy(
 fn tear_down(
     mut commands: Commands,
     query: Query<Entity, With<ScreenTag>>,
-    creator: Res<GalaxyCreator>,
+    mut creator: ResMut<GalaxyCreator>,
     mut turn_state: ResMut<State<TurnState>>,
+    galaxy_assets: Res<GalaxyAssets>,
+    balances: Res<Assets<Balance>>,
 ) {
     info!("tear down");
 
@@ -711,84 +1217,109 @@ fn tear_down(
         commands.entity(entity).despawn_recursive();
     }
 
-    let mut rand = rand::thread_rng();
-    let galaxy = creator.generated.clone();
-
-    let mut star_details = (0..galaxy.len())
-        .map(|_| StarDetails {
-            population: 0.0,
-            resources: rand.gen_range(50.0..100.0),
-            owner: usize::MAX,
-            owned_since: u32::MAX,
-        })
-        .collect::<Vec<StarDetails>>();
-
-    let mut fleets = vec![];
-
-    let mut player_names = ["Turandot", "Violetta", "Papageno", "Figaro", "Gilda"];
-    player_names.shuffle(&mut rand);
-
-    let seed = rand.gen_range(0..creator.nb_players) as usize;
-    let players = (0..(creator.nb_players as usize))
-        .into_iter()
-        .map(|player| {
-            let mut angle = PI * 2.0 / creator.nb_players as f32 * (player + seed) as f32;
-            if creator.nb_players % 2 == 0 {
-                angle += PI / (creator.nb_players as f32 * 1.5);
-            }
-            let position = Vec2::new(angle.cos(), angle.sin()) * creator.size * 100.0;
-            let mut closest_i = usize::MAX;
-            let mut closest_distance = f32::MAX;
-            for (i, star) in galaxy.iter().enumerate() {
-                if star.size != StarSize::Giant
-                    && star.position.distance_squared(position) < closest_distance
-                {
-                    closest_i = i;
-                    closest_distance = star.position.distance_squared(position);
+    start_game(
+        &mut commands,
+        &mut creator,
+        &mut turn_state,
+        balances
+            .get(&galaxy_assets.balance)
+            .cloned()
+            .unwrap_or_default(),
+    );
+}
+
+// asked from the end of game screen, to play the same galaxy again with the same settings
+#[derive(Resource)]
+pub struct Rematch;
+
+fn rematch(
+    mut commands: Commands,
+    rematch: Option<Res<Rematch>>,
+    mut creator: ResMut<GalaxyCreator>,
+    mut turn_state: ResMut<State<TurnState>>,
+    galaxy_assets: Res<GalaxyAssets>,
+    balances: Res<Assets<Balance>>,
+    names: Res<Assets<Names>>,
+) {
+    if rematch.is_none() {
+        return;
+    }
+    commands.remove_resource::<Rematch>();
+
+    creator.generated = Vec::new();
+    creator.names = names.get(&galaxy_assets.star_names).unwrap().names.clone();
+    creator.rng = StdRng::seed_from_u64(creator.seed);
+    for _ in creator.by_ref() {}
+
+    start_game(
+        &mut commands,
+        &mut creator,
+        &mut turn_state,
+        balances
+            .get(&galaxy_assets.balance)
+            .cloned()
+            .unwrap_or_default(),
+    );
+}
+
+fn start_game(
+    commands: &mut Commands,
+    creator: &mut GalaxyCreator,
+    turn_state: &mut State<TurnState>,
+    balance: Balance,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let remotes = creator.controllers[..creator.nb_players as usize]
+            .iter()
+            .enumerate()
+            .filter(|(_, controller)| **controller == Controller::Remote)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let externals = creator.controllers[..creator.nb_players as usize]
+            .iter()
+            .enumerate()
+            .filter(|(_, controller)| **controller == Controller::External)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if !externals.is_empty() {
+            let failed = match external::bot_command() {
+                Some(command) => {
+                    let (bots, failed) = external::ExternalBots::start(&command, &externals);
+                    commands.insert_resource(bots);
+                    failed
                 }
+                None => externals,
+            };
+            for player in failed {
+                creator.controllers[player] = Controller::Bot;
             }
-            // populate the starting star, and increase its resources
-            star_details[closest_i].population = rand.gen_range(70.0..80.0);
-            star_details[closest_i].resources = rand.gen_range(100.0..150.0);
-            star_details[closest_i].owner = player;
-            star_details[closest_i].owned_since = 0;
-
-            let mut vision = vec![StarState::Unknown; galaxy.len()];
-            vision[closest_i] = StarState::Owned(player);
-            fleets.push(Fleet {
-                owner: Owner(player),
-                order: Order::Orbit(closest_i),
-                ship: Ship {
-                    kind: ShipKind::Colony,
-                },
-                size: FleetSize(1),
-            });
-            // fleets.push(Fleet {
-            //     owner: Owner(player),
-            //     order: Order::Orbit(closest_i),
-            //     ship: Ship {
-            //         kind: ShipKind::Fighter,
-            //     },
-            //     size: FleetSize(2),
-            // });
-            Player {
-                start: closest_i,
-                vision,
-                savings: 10.0,
-                resources: 10.0,
-                first_colony_done: false,
-                name: player_names[player].to_string(),
+        }
+        if !remotes.is_empty() {
+            match network::Host::start(remotes) {
+                Ok(host) => commands.insert_resource(host),
+                Err(error) => {
+                    warn!("error hosting the game, bots will play instead: {}", error);
+                    for controller in creator.controllers.iter_mut() {
+                        if *controller == Controller::Remote {
+                            *controller = Controller::Bot;
+                        }
+                    }
+                }
             }
-        })
-        .collect();
-
-    commands.insert_resource(Universe {
-        star_entities: Vec::with_capacity(galaxy.len()),
-        galaxy,
-        players,
-        star_details,
-        difficulty: creator.difficulty,
+        }
+    }
+
+    let (mut universe, fleets) = simulation::new_game(creator);
+    universe.balance = balance;
+    let nb_players = universe.players.len();
+    commands.insert_resource(CurrentPlayer {
+        index: universe.humans().next().unwrap_or_default(),
+        waiting: vec![],
+        defeated: vec![],
+        remote: false,
     });
+    commands.insert_resource(universe);
 
     commands.insert_resource(FleetsToSpawn(fleets));
 
@@ -796,10 +1327,13 @@ fn tear_down(
     commands.insert_resource(Turns {
         count: 0,
         messages: vec![],
+        pending: vec![vec![]; nb_players],
+        journal: vec![vec![]; nb_players],
     });
+    commands.insert_resource(StatsHistory::default());
     commands.init_resource::<SelectedStar>();
     commands.insert_resource(BotTurnStatus {
         current: 0,
-        last_colony_ship_spawned: vec![0; 5],
+        last_colony_ship_spawned: vec![0; nb_players],
     })
 }
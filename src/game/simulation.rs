@@ -0,0 +1,1130 @@
+use std::{collections::BTreeMap, fmt};
+
+use rand::{
+    rngs::StdRng,
+    seq::{IteratorRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::assets::balance::Balance;
+
+use super::{
+    bots::Personality,
+    combat,
+    fleet::{Fleet, FleetSize, Order, Owner, Ship, ShipKind},
+    galaxy::{GalaxyCreator, StarSize},
+    pirates,
+    production::QueueChange,
+    research::{Research, Tech},
+    turns::{LoseCondition, Message},
+    victory, Controller, Player, StarDetails, StarState, Universe,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FleetState {
+    pub id: u64,
+    pub order: Order,
+    pub kind: ShipKind,
+    pub size: u32,
+    pub owner: usize,
+}
+
+// ships leaving a fleet orbiting a star, with the stops they go to
+#[derive(Serialize, Deserialize)]
+pub struct Split {
+    pub fleet: u64,
+    pub size: u32,
+    pub route: Vec<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Build {
+    pub star: usize,
+    pub kind: ShipKind,
+    pub size: u32,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Orders {
+    // fleet id and the star it should move to
+    pub moves: Vec<(u64, usize)>,
+    // fleet id and the stops to continue to once arrived
+    pub waypoints: Vec<(u64, Vec<usize>)>,
+    // fleet id and the star it should turn to, from where it is in flight
+    pub redirects: Vec<(u64, usize)>,
+    // ships moved from a fleet to another orbiting the same star: from, into, count
+    pub transfers: Vec<(u64, u64, u32)>,
+    pub splits: Vec<Split>,
+    // added at the end of the queue of their star, after the other changes
+    pub builds: Vec<Build>,
+    pub queue_changes: Vec<QueueChange>,
+    // tech to put research points in, kept from one turn to the next
+    pub research: Option<Tech>,
+    // credits to invest in research each turn
+    pub funding: Option<f32>,
+}
+
+pub enum Event {
+    FleetSpawned(Fleet),
+    FleetDestroyed(u64),
+    StarVisited {
+        player: usize,
+        star: usize,
+    },
+    VisionChanged {
+        player: usize,
+        star: usize,
+        state: StarState,
+    },
+    Message {
+        player: usize,
+        message: Message,
+    },
+}
+
+fn set_vision(
+    universe: &mut Universe,
+    events: &mut Vec<Event>,
+    player: usize,
+    star: usize,
+    state: StarState,
+) {
+    universe.players[player].vision[star] = state;
+    events.push(Event::VisionChanged {
+        player,
+        star,
+        state,
+    });
+}
+
+pub fn new_game(creator: &mut GalaxyCreator) -> (Universe, Vec<Fleet>) {
+    // keep drawing from the generator so that the whole game follows from the seed
+    let rand = &mut creator.rng;
+    let galaxy = creator.generated.clone();
+
+    let mut star_details = (0..galaxy.len())
+        .map(|_| StarDetails {
+            population: 0.0,
+            resources: rand.gen_range(50.0..100.0),
+            owner: usize::MAX,
+            owned_since: u32::MAX,
+        })
+        .collect::<Vec<StarDetails>>();
+
+    let mut fleets = vec![];
+
+    let mut player_names = vec![
+        "Turandot",
+        "Violetta",
+        "Papageno",
+        "Figaro",
+        "Gilda",
+        "Carmen",
+        "Tosca",
+        "Rigoletto",
+        "Norma",
+        "Aida",
+        "Tamino",
+        "Mimi",
+    ];
+    player_names.shuffle(rand);
+
+    let offset = rand.gen_range(0..creator.nb_players) as usize;
+    let start_positions = (0..(creator.nb_players as usize))
+        .map(|player| creator.start_position(player + offset))
+        .collect::<Vec<_>>();
+
+    let rand = &mut creator.rng;
+    let mut players: Vec<Player> = (0..(creator.nb_players as usize))
+        .map(|player| {
+            let position = start_positions[player];
+            let mut closest_i = usize::MAX;
+            let mut closest_distance = f32::MAX;
+            for (i, star) in galaxy.iter().enumerate() {
+                // two players can't share a start, even when the shape brings them close
+                if star.size != StarSize::Giant
+                    && star_details[i].owner == usize::MAX
+                    && star.position.distance_squared(position) < closest_distance
+                {
+                    closest_i = i;
+                    closest_distance = star.position.distance_squared(position);
+                }
+            }
+            // populate the starting star, and increase its resources
+            star_details[closest_i].population = rand.gen_range(70.0..80.0);
+            star_details[closest_i].resources = rand.gen_range(100.0..150.0);
+            star_details[closest_i].owner = player;
+            star_details[closest_i].owned_since = 0;
+
+            let mut vision = vec![StarState::Unknown; galaxy.len()];
+            vision[closest_i] = StarState::Owned(player);
+            fleets.push(Fleet {
+                owner: Owner(player),
+                order: Order::Orbit(closest_i),
+                ship: Ship {
+                    kind: ShipKind::Colony,
+                },
+                size: FleetSize(1),
+            });
+            Player {
+                start: closest_i,
+                vision,
+                savings: 10.0,
+                resources: 10.0,
+                first_colony_done: false,
+                name: player_names
+                    .get(player)
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("Player {}", player + 1)),
+                controller: creator.controllers.get(player).copied().unwrap_or_default(),
+                personality: creator
+                    .personalities
+                    .get(player)
+                    .copied()
+                    .unwrap_or_default(),
+                research: Research::default(),
+            }
+        })
+        .collect();
+
+    // pirates are a neutral faction, played after all the players
+    let pirates = players.len();
+    players.push(Player {
+        start: (0..galaxy.len())
+            .filter(|i| star_details[*i].owner == usize::MAX)
+            .choose(rand)
+            .unwrap_or_default(),
+        vision: vec![StarState::Unknown; galaxy.len()],
+        savings: 0.0,
+        resources: 0.0,
+        first_colony_done: false,
+        name: "Pirates".to_string(),
+        controller: Controller::Bot,
+        personality: Personality::default(),
+        research: Research::default(),
+    });
+
+    let mut universe = Universe {
+        star_entities: Vec::with_capacity(galaxy.len()),
+        galaxy,
+        players,
+        star_details,
+        difficulty: creator.difficulty,
+        pirates,
+        seed: creator.seed,
+        balance: Balance::default(),
+        victory: creator.victory,
+        special_stars: vec![],
+        queues: vec![],
+    };
+    universe.special_stars = victory::choose_special_stars(&universe, rand);
+
+    (universe, fleets)
+}
+
+#[derive(Debug)]
+pub enum OrderError {
+    UnknownFleet(u64),
+    NotYourFleet(u64),
+    AlreadyMoving(u64),
+    NotMoving(u64),
+    Stationary(u64),
+    NotEnoughShips(u64),
+    CantMerge(u64, u64),
+    UnknownStar(usize),
+    NotYourColony(usize),
+    NotInQueue(usize, usize),
+    EmptyBuild,
+    Locked(ShipKind),
+    InsufficientSavings,
+    InsufficientResources,
+    AlreadyResearched(Tech),
+    MissingTech(Tech),
+    InvalidFunding,
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::UnknownFleet(id) => write!(f, "fleet {} doesn't exist", id),
+            OrderError::NotYourFleet(id) => write!(f, "fleet {} belongs to someone else", id),
+            OrderError::AlreadyMoving(id) => write!(f, "fleet {} is already moving", id),
+            OrderError::NotMoving(id) => write!(f, "fleet {} is not moving", id),
+            OrderError::Stationary(id) => write!(f, "fleet {} can't leave its star", id),
+            OrderError::NotEnoughShips(id) => write!(f, "fleet {} doesn't have enough ships", id),
+            OrderError::CantMerge(from, into) => {
+                write!(f, "fleet {} can't join fleet {}", from, into)
+            }
+            OrderError::UnknownStar(star) => write!(f, "star {} doesn't exist", star),
+            OrderError::NotYourColony(star) => write!(f, "star {} is not your colony", star),
+            OrderError::NotInQueue(star, position) => {
+                write!(
+                    f,
+                    "nothing at position {} in the queue of star {}",
+                    position, star
+                )
+            }
+            OrderError::EmptyBuild => write!(f, "can't build zero ships"),
+            OrderError::Locked(kind) => write!(f, "{} ships are not researched yet", kind),
+            OrderError::InsufficientSavings => write!(f, "not enough credits"),
+            OrderError::InsufficientResources => write!(f, "not enough resources"),
+            OrderError::AlreadyResearched(tech) => write!(f, "{} is already known", tech),
+            OrderError::MissingTech(tech) => write!(f, "{} must be researched first", tech),
+            OrderError::InvalidFunding => write!(f, "research funding must be positive"),
+        }
+    }
+}
+
+// the same rules as for a human using the interface, whoever gives the orders
+pub fn apply_orders(
+    universe: &mut Universe,
+    fleets: &mut Vec<FleetState>,
+    player: usize,
+    orders: &Orders,
+) -> (Vec<Fleet>, Vec<OrderError>) {
+    let mut rejected = vec![];
+    let mut spawned = vec![];
+
+    // regrouping happens before the fleets leave their star
+    for (from, into, count) in &orders.transfers {
+        let Some(source) = fleets.iter().position(|fleet| fleet.id == *from) else {
+            rejected.push(OrderError::UnknownFleet(*from));
+            continue;
+        };
+        let Some(target) = fleets.iter().position(|fleet| fleet.id == *into) else {
+            rejected.push(OrderError::UnknownFleet(*into));
+            continue;
+        };
+        if fleets[source].owner != player || fleets[target].owner != player {
+            rejected.push(OrderError::NotYourFleet(*from));
+            continue;
+        }
+        if source == target
+            || fleets[source].kind != fleets[target].kind
+            || !matches!(fleets[source].order, Order::Orbit(_))
+            || fleets[source].order != fleets[target].order
+        {
+            rejected.push(OrderError::CantMerge(*from, *into));
+            continue;
+        }
+        if fleets[source].size < *count {
+            rejected.push(OrderError::NotEnoughShips(*from));
+            continue;
+        }
+        fleets[source].size -= count;
+        fleets[target].size += count;
+    }
+    for split in &orders.splits {
+        let Some(fleet) = fleets.iter_mut().find(|fleet| fleet.id == split.fleet) else {
+            rejected.push(OrderError::UnknownFleet(split.fleet));
+            continue;
+        };
+        if fleet.owner != player {
+            rejected.push(OrderError::NotYourFleet(split.fleet));
+            continue;
+        }
+        let Order::Orbit(star) = fleet.order else {
+            rejected.push(OrderError::AlreadyMoving(split.fleet));
+            continue;
+        };
+        if fleet.kind.is_stationary() && !split.route.is_empty() {
+            rejected.push(OrderError::Stationary(split.fleet));
+            continue;
+        }
+        if split.size == 0 || fleet.size < split.size {
+            rejected.push(OrderError::NotEnoughShips(split.fleet));
+            continue;
+        }
+        if let Some(star) = split
+            .route
+            .iter()
+            .find(|star| **star >= universe.galaxy.len())
+        {
+            rejected.push(OrderError::UnknownStar(*star));
+            continue;
+        }
+        fleet.size -= split.size;
+        spawned.push(Fleet {
+            order: split
+                .route
+                .iter()
+                .fold(Order::Orbit(star), |order, stop| order.then(*stop)),
+            ship: Ship { kind: fleet.kind },
+            size: FleetSize(split.size),
+            owner: Owner(player),
+        });
+    }
+    // fleets whose ships all went to others
+    fleets.retain(|fleet| fleet.size > 0);
+
+    for (id, to) in &orders.moves {
+        let Some(fleet) = fleets.iter_mut().find(|fleet| fleet.id == *id) else {
+            rejected.push(OrderError::UnknownFleet(*id));
+            continue;
+        };
+        if fleet.owner != player {
+            rejected.push(OrderError::NotYourFleet(*id));
+            continue;
+        }
+        if *to >= universe.galaxy.len() {
+            rejected.push(OrderError::UnknownStar(*to));
+            continue;
+        }
+        if fleet.kind.is_stationary() {
+            rejected.push(OrderError::Stationary(*id));
+            continue;
+        }
+        match fleet.order {
+            Order::Orbit(from) if from != *to => {
+                fleet.order = Order::Move {
+                    from,
+                    to: *to,
+                    step: 0,
+                    waypoints: vec![],
+                    departure: None,
+                };
+            }
+            Order::Orbit(_) => (),
+            Order::Move { .. } => rejected.push(OrderError::AlreadyMoving(*id)),
+        }
+    }
+    for (id, to) in &orders.redirects {
+        let Some(fleet) = fleets.iter_mut().find(|fleet| fleet.id == *id) else {
+            rejected.push(OrderError::UnknownFleet(*id));
+            continue;
+        };
+        if fleet.owner != player {
+            rejected.push(OrderError::NotYourFleet(*id));
+            continue;
+        }
+        if *to >= universe.galaxy.len() {
+            rejected.push(OrderError::UnknownStar(*to));
+            continue;
+        }
+        if !fleet.order.in_flight() {
+            rejected.push(OrderError::NotMoving(*id));
+            continue;
+        }
+        fleet.order =
+            fleet
+                .order
+                .redirect(*to, &universe.galaxy, universe.speed(player, fleet.kind));
+    }
+    for (id, stops) in &orders.waypoints {
+        let Some(fleet) = fleets.iter_mut().find(|fleet| fleet.id == *id) else {
+            rejected.push(OrderError::UnknownFleet(*id));
+            continue;
+        };
+        if fleet.owner != player {
+            rejected.push(OrderError::NotYourFleet(*id));
+            continue;
+        }
+        if let Some(star) = stops.iter().find(|star| **star >= universe.galaxy.len()) {
+            rejected.push(OrderError::UnknownStar(*star));
+            continue;
+        }
+        if fleet.kind.is_stationary() && !stops.is_empty() {
+            rejected.push(OrderError::Stationary(*id));
+            continue;
+        }
+        for star in stops {
+            fleet.order = fleet.order.clone().then(*star);
+        }
+    }
+
+    // ships are paid now and built over the next turns
+    for change in orders
+        .queue_changes
+        .iter()
+        .cloned()
+        .chain(orders.builds.iter().cloned().map(QueueChange::Build))
+    {
+        if let Err(error) = universe.apply_queue_change(player, &change) {
+            rejected.push(error);
+        }
+    }
+
+    if let Some(funding) = orders.funding {
+        if let Err(error) = universe.fund_research(player, funding) {
+            rejected.push(error);
+        }
+    }
+    if let Some(tech) = orders.research {
+        if let Err(error) = universe.choose_research(player, tech) {
+            rejected.push(error);
+        }
+    }
+    (spawned, rejected)
+}
+
+fn grow_economy(universe: &mut Universe, turn: u32) {
+    let balance = universe.balance.clone();
+    for i in 0..universe.players.len() {
+        let good_conditions = &universe.galaxy[universe.players[i].start].clone();
+        universe.players[i].savings += universe.player_revenue(i);
+        let (size_divisor, adapted_population) = universe.adaptation(i);
+
+        let mut harvested = 0.0;
+        universe
+            .galaxy
+            .clone()
+            .iter()
+            .zip(universe.star_details.iter_mut())
+            .filter(|(_, details)| details.owner == i)
+            .for_each(|(star, details)| {
+                let good = star.color == good_conditions.color;
+                // grow population
+                {
+                    let max_population = balance
+                        .population(good)
+                        .max_population(turn as f32 - details.owned_since as f32)
+                        * if good { 1.0 } else { adapted_population };
+                    let lerp = (details.population / max_population).min(1.2);
+                    let growth_factor = if lerp < 0.5 {
+                        (10.0 * lerp).powf(3.0)
+                    } else if lerp < 1.0 {
+                        1.0 - (-2.0 * lerp + 2.0).powf(3.0) / 2.0
+                    } else {
+                        1.0 - (-2.0 * lerp + 4.0).powf(3.0) / 2.0
+                    };
+                    details.population = if star.size == good_conditions.size {
+                        details.population + growth_factor
+                    } else {
+                        details.population + growth_factor / size_divisor
+                    };
+                }
+
+                // harvest resources, what can be collected always follows the poor curve
+                {
+                    let current_resources = balance.poor_resources.available(details.resources);
+                    let collect = balance.harvest(good).min(current_resources);
+                    harvested += collect;
+                    let curve = balance.resources(good);
+                    details.resources = curve
+                        .remaining(curve.available(details.resources) - collect)
+                        .max(0.0);
+                }
+            });
+        universe.players[i].resources += harvested;
+    }
+}
+
+pub fn resolve_turn(
+    universe: &mut Universe,
+    fleets: &mut Vec<FleetState>,
+    turn: u32,
+) -> Vec<Event> {
+    let mut events = vec![];
+
+    if turn != 0 {
+        grow_economy(universe, turn);
+        for player in 0..universe.pirates {
+            if let Some(tech) = universe.advance_research(player) {
+                events.push(Event::Message {
+                    player,
+                    message: Message::TechResearched { tech },
+                });
+            }
+        }
+    }
+
+    // every fleet moves first, then battles are fought among everyone present at a star
+    let mut arrived = vec![];
+    for fleet in fleets.iter_mut() {
+        let Order::Move { to, step, .. } = &mut fleet.order else {
+            continue;
+        };
+        *step += 1;
+        let (to, step) = (*to, *step);
+        // a leg can get shorter while under way once faster engines are researched
+        if step
+            < fleet
+                .order
+                .leg_turns(&universe.galaxy, universe.speed(fleet.owner, fleet.kind))
+        {
+            continue;
+        }
+        let owner = fleet.owner;
+        arrived.push(fleet.id);
+
+        // exploration
+        if universe.star_details[to].owner != owner {
+            // star exploration and visibility in universe
+            if universe.players[owner].vision[to] == StarState::Unknown {
+                let start_conditions = &universe.galaxy[universe.players[owner].start];
+                let new_star = &universe.galaxy[to];
+                events.push(Event::Message {
+                    player: owner,
+                    message: Message::StarExplored {
+                        star_name: new_star.name.clone(),
+                        color_condition: start_conditions.color == new_star.color,
+                        size_condition: start_conditions.size == new_star.size,
+                        index: to,
+                    },
+                });
+            }
+            events.push(Event::StarVisited {
+                player: owner,
+                star: to,
+            });
+        }
+    }
+
+    let mut present: BTreeMap<usize, Vec<combat::Combatant>> = BTreeMap::new();
+    for fleet in fleets.iter() {
+        let star = match fleet.order {
+            Order::Orbit(around) => around,
+            Order::Move { to, .. } if arrived.contains(&fleet.id) => to,
+            Order::Move { .. } => continue,
+        };
+        present.entry(star).or_default().push(combat::Combatant {
+            fleet: fleet.id,
+            owner: fleet.owner,
+            kind: fleet.kind,
+            size: fleet.size,
+            attack_modifier: universe.attack_modifier(fleet.owner, fleet.kind),
+        });
+    }
+
+    let mut destroyed = vec![];
+    for (star, combatants) in &present {
+        let star = *star;
+        let defender = universe.star_details[star].owner;
+        // the same battle is fought again when replaying the turn from a save or on a client
+        let mut rand = StdRng::seed_from_u64(universe.seed ^ ((turn as u64) << 32) ^ star as u64);
+        let Some(battle) = combat::resolve(
+            defender,
+            universe.star_details[star].population,
+            combatants,
+            &mut rand,
+        ) else {
+            continue;
+        };
+
+        for fleet in fleets.iter_mut() {
+            if let Some((_, size)) = battle.survivors.iter().find(|(id, _)| *id == fleet.id) {
+                fleet.size = *size;
+                if *size == 0 {
+                    destroyed.push(fleet.id);
+                }
+            }
+        }
+        universe.star_details[star].population = battle.population;
+
+        let armed = |player: usize| {
+            combatants
+                .iter()
+                .zip(&battle.survivors)
+                .filter(|(fleet, _)| fleet.owner == player && fleet.kind.attack() > 0.0)
+                .map(|(_, (_, size))| *size)
+                .sum::<u32>()
+        };
+        let strongest = battle
+            .sides
+            .iter()
+            .map(|side| (side.player, armed(side.player)))
+            .filter(|(_, armed)| *armed > 0)
+            .max_by_key(|(_, armed)| *armed)
+            .map(|(player, _)| player);
+
+        // troops land once no armed ship defends the colony, and take it over if there are
+        // enough of them to hold its population
+        let landing = battle
+            .sides
+            .iter()
+            .filter(|side| side.player != defender)
+            .map(|side| {
+                let troops = combatants
+                    .iter()
+                    .zip(&battle.survivors)
+                    .filter(|(fleet, _)| fleet.owner == side.player)
+                    .map(|(fleet, (_, size))| fleet.kind.troops() * *size as f32)
+                    .sum::<f32>();
+                (side.player, troops)
+            })
+            .filter(|(_, troops)| *troops > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        let captured = match landing {
+            Some((invader, troops))
+                if defender != usize::MAX
+                    && armed(defender) == 0
+                    && troops >= battle.population =>
+            {
+                Some(invader)
+            }
+            _ => None,
+        };
+        if let Some(invader) = captured {
+            for fleet in fleets.iter() {
+                if fleet.owner == invader
+                    && fleet.kind.troops() > 0.0
+                    && combatants
+                        .iter()
+                        .any(|combatant| combatant.fleet == fleet.id)
+                    && !destroyed.contains(&fleet.id)
+                {
+                    destroyed.push(fleet.id);
+                }
+            }
+            universe.star_details[star].owner = invader;
+            universe.star_details[star].owned_since = turn;
+            for (player, other, attacker) in [(defender, invader, false), (invader, defender, true)]
+            {
+                events.push(Event::Message {
+                    player,
+                    message: Message::ColonyCaptured {
+                        star_name: universe.galaxy[star].name.clone(),
+                        index: star,
+                        player_name: universe.players[other].name.clone(),
+                        attacker,
+                    },
+                });
+            }
+        } else if defender != usize::MAX && battle.population < 10.0 {
+            if let Some(winner) = strongest.filter(|winner| *winner != defender) {
+                universe.star_details[star].population = 0.0;
+                universe.star_details[star].owner = usize::MAX;
+                events.push(Event::Message {
+                    player: defender,
+                    message: Message::ColonyDestroyed {
+                        star_name: universe.galaxy[star].name.clone(),
+                        player_name: universe.players[winner].name.clone(),
+                        index: star,
+                    },
+                });
+            }
+        }
+
+        let owner = universe.star_details[star].owner;
+        for side in &battle.sides {
+            let wiped = side.ships == side.ships_lost && side.player != owner;
+            let state = match strongest {
+                // the last thing they saw is who beat them
+                Some(winner) if wiped => StarState::Owned(winner),
+                _ if owner == usize::MAX => StarState::Uninhabited,
+                _ => StarState::Owned(owner),
+            };
+            set_vision(universe, &mut events, side.player, star, state);
+        }
+
+        for side in &battle.sides {
+            let mut sides = battle.sides.clone();
+            // the receiver of the report comes first
+            sides.sort_by_key(|other| other.player != side.player);
+            events.push(Event::Message {
+                player: side.player,
+                message: Message::Battle {
+                    index: star,
+                    star_name: universe.galaxy[star].name.clone(),
+                    rounds: battle.rounds,
+                    sides: sides
+                        .into_iter()
+                        .map(|side| (universe.players[side.player].name.clone(), side))
+                        .collect(),
+                },
+            });
+        }
+    }
+
+    // armed ships of other players still around the star once the battles are over
+    let guarded = |star: usize, player: usize| {
+        present.get(&star).is_some_and(|combatants| {
+            combatants.iter().any(|fleet| {
+                fleet.owner != player
+                    && fleet.kind.attack() > 0.0
+                    && !destroyed.contains(&fleet.fleet)
+            })
+        })
+    };
+    let mut consumed = vec![];
+    for fleet in fleets.iter_mut() {
+        if destroyed.contains(&fleet.id) || !arrived.contains(&fleet.id) {
+            continue;
+        }
+        let owner = fleet.owner;
+        let Order::Move { to, waypoints, .. } = &mut fleet.order else {
+            continue;
+        };
+        let to = *to;
+        let waypoints = std::mem::take(waypoints);
+
+        // colonize the star, unless someone there can still shoot the colony ship down
+        if fleet.kind == ShipKind::Colony
+            && universe.star_details[to].owner == usize::MAX
+            && !guarded(to, owner)
+        {
+            events.push(Event::Message {
+                player: owner,
+                message: Message::ColonyFounded {
+                    star_name: universe.galaxy[to].name.clone(),
+                    index: to,
+                },
+            });
+            if !universe.players[owner].first_colony_done {
+                universe.players[owner].first_colony_done = true;
+                events.push(Event::Message {
+                    player: owner,
+                    message: Message::Story {
+                        title: "First colony!".to_string(),
+                        details: r#"You just founded your first colony!
+If the color is the same as your
+starting system, your population
+will grow faster, but you'll
+get less resources."#
+                            .to_string(),
+                        order: 0,
+                        index: None,
+                    },
+                });
+                events.push(Event::Message {
+                    player: owner,
+                    message: Message::Story {
+                        title: "revenue".to_string(),
+                        details: r#"New colonies cost credits.
+Once population has grown, colonies
+will start earning credits."#
+                            .to_string(),
+                        order: 1,
+                        index: None,
+                    },
+                });
+            }
+
+            // Colony ship is consumed
+            consumed.push(fleet.id);
+
+            set_vision(universe, &mut events, owner, to, StarState::Owned(owner));
+            universe.star_details[to].owner = owner;
+            universe.star_details[to].owned_since = turn;
+            universe.star_details[to].population = 10.0;
+        }
+
+        fleet.order = match waypoints.split_first() {
+            // the fleet keeps going without waiting for a new order
+            Some((next, rest)) => Order::Move {
+                from: to,
+                to: *next,
+                step: 0,
+                waypoints: rest.to_vec(),
+                departure: None,
+            },
+            None => Order::Orbit(to),
+        };
+    }
+
+    destroyed.extend(consumed);
+    fleets.retain(|fleet| !destroyed.contains(&fleet.id));
+    events.extend(destroyed.into_iter().map(Event::FleetDestroyed));
+
+    // shipyards deliver once the battles are over, the new ships fight from the next turn
+    for star in 0..universe.galaxy.len() {
+        for built in universe.advance_production(star) {
+            events.push(Event::FleetSpawned(Fleet {
+                order: Order::Orbit(star),
+                ship: Ship { kind: built.kind },
+                size: FleetSize(built.size),
+                owner: Owner(built.owner),
+            }));
+            events.push(Event::Message {
+                player: built.owner,
+                message: Message::ShipsBuilt {
+                    star_name: universe.galaxy[star].name.clone(),
+                    index: star,
+                    kind: built.kind,
+                    size: built.size,
+                },
+            });
+        }
+    }
+
+    events
+}
+
+// the Enemy phase then the resolution, as in a game with a screen, for tournaments
+pub fn end_turn(universe: &mut Universe, fleets: &mut Vec<FleetState>, turn: u32) -> Vec<Event> {
+    let mut events = vec![];
+    if turn != 0 {
+        events = pirates::play(universe, fleets, turn);
+    }
+    events.extend(resolve_turn(universe, fleets, turn));
+    events
+}
+
+pub fn outcome(universe: &Universe, player: usize, turn: u32) -> Option<Message> {
+    if !universe
+        .star_details
+        .iter()
+        .any(|details| details.owner == player)
+    {
+        Some(Message::Lose {
+            condition: LoseCondition::NoMoreColonies,
+        })
+    } else if universe.players[player].savings < universe.balance.deficit_limit {
+        Some(Message::Lose {
+            condition: LoseCondition::Deficit,
+        })
+    } else if !universe
+        .star_details
+        .iter()
+        .any(|details| details.owner != player && details.owner != usize::MAX)
+    {
+        Some(Message::Win)
+    } else {
+        universe.victory(turn).map(|(winner, condition)| {
+            if winner == player {
+                Message::Victory { condition }
+            } else {
+                Message::RivalVictory {
+                    condition,
+                    player_name: universe.players[winner].name.clone(),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Vec2;
+
+    use super::*;
+    use crate::game::galaxy::{GalaxyKind, Star, StarColor};
+
+    // two players at both ends of a line of stars, a few turns of travel apart
+    fn universe() -> Universe {
+        let galaxy = (0..4)
+            .map(|i| Star {
+                position: Vec2::new(i as f32 * 100.0, 0.0),
+                size: StarSize::Dwarf,
+                color: StarColor::Yellow,
+                start: None,
+                name: format!("Star {}", i),
+            })
+            .collect::<Vec<_>>();
+        let mut star_details = vec![
+            StarDetails {
+                population: 0.0,
+                resources: 80.0,
+                owner: usize::MAX,
+                owned_since: u32::MAX,
+            };
+            galaxy.len()
+        ];
+        let players = [0, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(player, start)| {
+                star_details[start] = StarDetails {
+                    population: 50.0,
+                    resources: 120.0,
+                    owner: player,
+                    owned_since: 0,
+                };
+                let mut vision = vec![StarState::Unknown; galaxy.len()];
+                vision[start] = StarState::Owned(player);
+                Player {
+                    start,
+                    vision,
+                    savings: 10.0,
+                    resources: 10.0,
+                    first_colony_done: false,
+                    name: format!("Player {}", player + 1),
+                    controller: Controller::Bot,
+                    personality: Personality::default(),
+                    research: Research::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        Universe {
+            star_entities: vec![],
+            pirates: players.len(),
+            players,
+            star_details,
+            difficulty: 1.0,
+            seed: 0,
+            balance: Balance::default(),
+            victory: victory::VictoryRules::default(),
+            special_stars: vec![],
+            queues: vec![],
+            galaxy,
+        }
+    }
+
+    fn fleet(id: u64, owner: usize, kind: ShipKind, around: usize) -> FleetState {
+        FleetState {
+            id,
+            order: Order::Orbit(around),
+            kind,
+            size: 1,
+            owner,
+        }
+    }
+
+    fn move_to(fleet: u64, to: usize) -> Orders {
+        Orders {
+            moves: vec![(fleet, to)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn population_grows() {
+        let mut universe = universe();
+        let mut fleets = vec![];
+
+        resolve_turn(&mut universe, &mut fleets, 1);
+
+        assert!(universe.star_details[0].population > 50.0);
+        assert!(universe.star_details[3].population > 50.0);
+        assert_eq!(universe.star_details[1].population, 0.0);
+    }
+
+    #[test]
+    fn harvest_is_capped_by_the_poor_curve() {
+        let mut universe = universe();
+        universe.star_details[0].resources = 0.1;
+        let mut fleets = vec![];
+
+        resolve_turn(&mut universe, &mut fleets, 1);
+
+        // on a good star, 0.1 ^ 0.8 could give the full 0.2 but (0.1 * 1.2) ^ 1.5 can't
+        let collected = (0.1_f32 * 1.2).powf(1.5);
+        assert!((universe.players[0].resources - 10.0 - collected).abs() < 1e-4);
+        let remaining = (0.1_f32.powf(0.8) - collected).powf(1.0 / 0.8);
+        assert!((universe.star_details[0].resources - remaining).abs() < 1e-4);
+    }
+
+    #[test]
+    fn captured_colony_refunds_its_queue() {
+        let mut universe = universe();
+        let orders = Orders {
+            builds: vec![Build {
+                star: 0,
+                kind: ShipKind::Fighter,
+                size: 1,
+            }],
+            ..Default::default()
+        };
+        let (_, rejected) = apply_orders(&mut universe, &mut vec![], 0, &orders);
+        assert!(rejected.is_empty());
+        assert!(universe.players[0].savings < 10.0);
+        assert_eq!(universe.queue(0).len(), 1);
+
+        universe.star_details[0].owner = 1;
+        assert!(universe.advance_production(0).is_empty());
+
+        assert!(universe.queue(0).is_empty());
+        assert!((universe.players[0].savings - 10.0).abs() < 1e-4);
+        assert!((universe.players[0].resources - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn move_arrives_after_several_turns() {
+        let mut universe = universe();
+        let mut fleets = vec![fleet(0, 0, ShipKind::Fighter, 0)];
+
+        let (_, rejected) = apply_orders(&mut universe, &mut fleets, 0, &move_to(0, 1));
+        assert!(rejected.is_empty());
+        let turns = fleets[0]
+            .order
+            .leg_turns(&universe.galaxy, universe.speed(0, ShipKind::Fighter));
+        assert!(turns > 1);
+
+        for turn in 1..turns {
+            resolve_turn(&mut universe, &mut fleets, turn);
+            assert!(matches!(fleets[0].order, Order::Move { step, .. } if step == turn));
+        }
+        resolve_turn(&mut universe, &mut fleets, turns);
+        assert_eq!(fleets[0].order, Order::Orbit(1));
+    }
+
+    #[test]
+    fn colony_ship_founds_a_colony() {
+        let mut universe = universe();
+        let mut fleets = vec![fleet(0, 0, ShipKind::Colony, 0)];
+
+        apply_orders(&mut universe, &mut fleets, 0, &move_to(0, 1));
+        let mut events = vec![];
+        for turn in 1..100 {
+            events = resolve_turn(&mut universe, &mut fleets, turn);
+            if fleets.is_empty() {
+                break;
+            }
+        }
+
+        assert_eq!(universe.star_details[1].owner, 0);
+        assert_eq!(universe.players[0].vision[1], StarState::Owned(0));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::FleetDestroyed(0))));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            Event::Message {
+                player: 0,
+                message: Message::ColonyFounded { index: 1, .. }
+            }
+        )));
+    }
+
+    #[test]
+    fn invalid_orders_are_rejected() {
+        let mut universe = universe();
+        let mut fleets = vec![
+            fleet(0, 0, ShipKind::Fighter, 0),
+            fleet(1, 0, ShipKind::Platform, 0),
+        ];
+
+        let (_, rejected) = apply_orders(&mut universe, &mut fleets, 1, &move_to(0, 1));
+        assert!(matches!(rejected[..], [OrderError::NotYourFleet(0)]));
+
+        let (_, rejected) = apply_orders(&mut universe, &mut fleets, 0, &move_to(1, 1));
+        assert!(matches!(rejected[..], [OrderError::Stationary(1)]));
+
+        let orders = Orders {
+            builds: vec![Build {
+                star: 0,
+                kind: ShipKind::Fighter,
+                size: 100,
+            }],
+            ..Default::default()
+        };
+        let (_, rejected) = apply_orders(&mut universe, &mut fleets, 0, &orders);
+        assert!(matches!(rejected[..], [OrderError::InsufficientSavings]));
+        assert_eq!(universe.players[0].savings, 10.0);
+        assert!(universe.queue(0).is_empty());
+
+        // nothing moved
+        assert!(fleets.iter().all(|fleet| fleet.order == Order::Orbit(0)));
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let game = |seed| {
+            let mut creator = GalaxyCreator {
+                nb_players: 4,
+                size: 3.0,
+                density: 2.5,
+                kind: GalaxyKind::Spiral,
+                generated: vec![],
+                names: (1..=200).map(|i| format!("Star {}", i)).collect(),
+                difficulty: 1.0,
+                seed,
+                rng: StdRng::seed_from_u64(seed),
+                controllers: vec![Controller::Bot; 4],
+                personalities: vec![],
+                victory: victory::VictoryRules::default(),
+            };
+            for _ in creator.by_ref() {}
+            let (universe, fleets) = new_game(&mut creator);
+            let fleets = fleets
+                .iter()
+                .map(|fleet| (fleet.owner.0, format!("{:?}", fleet.order)))
+                .collect::<Vec<_>>();
+            (ron::to_string(&universe).unwrap(), fleets)
+        };
+        assert_eq!(game(3), game(3));
+        assert_ne!(game(3).0, game(4).0);
+    }
+}
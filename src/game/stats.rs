@@ -0,0 +1,130 @@
+use bevy::prelude::{Color, Resource};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    simulation::{self, FleetState},
+    turns::Message,
+    Universe,
+};
+
+// the state of an empire at the start of a turn
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub population: f32,
+    pub revenue: f32,
+    pub savings: f32,
+    pub resources: f32,
+    pub colonies: u32,
+    pub fleet_strength: u32,
+}
+
+// one snapshot per player for a turn
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub turn: u32,
+    pub snapshots: Vec<Snapshot>,
+}
+
+// every turn played, from the first one seen by a player joining over the network
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct StatsHistory {
+    pub samples: Vec<Sample>,
+}
+
+impl StatsHistory {
+    // only the first call for a turn is kept, so every human of a hotseat game can call it
+    pub fn record(&mut self, turn: u32, universe: &Universe, fleets: &[FleetState]) {
+        if self
+            .samples
+            .last()
+            .is_some_and(|sample| sample.turn >= turn)
+        {
+            return;
+        }
+        self.samples.push(Sample {
+            turn,
+            snapshots: snapshot(universe, fleets),
+        });
+    }
+}
+
+pub fn snapshot(universe: &Universe, fleets: &[FleetState]) -> Vec<Snapshot> {
+    (0..universe.pirates)
+        .map(|player| Snapshot {
+            population: universe.player_population(player),
+            revenue: universe.player_revenue(player),
+            savings: universe.players[player].savings,
+            resources: universe.players[player].resources,
+            colonies: universe
+                .star_details
+                .iter()
+                .filter(|details| details.owner == player)
+                .count() as u32,
+            fleet_strength: fleets
+                .iter()
+                .filter(|fleet| fleet.owner == player && fleet.kind.attack() > 0.0)
+                .map(|fleet| fleet.size)
+                .sum(),
+        })
+        .collect()
+}
+
+// a player's place at the end of a game
+pub struct Standing {
+    pub name: String,
+    pub color: Color,
+    pub eliminated: bool,
+    pub last: Snapshot,
+    pub peak: Snapshot,
+}
+
+// players still in the game first, then the others by how long they lasted
+pub fn standings(universe: &Universe, history: &StatsHistory, turn: u32) -> Vec<Standing> {
+    let mut standings = (0..universe.pirates)
+        .map(|player| {
+            let eliminated = matches!(
+                simulation::outcome(universe, player, turn),
+                Some(Message::Lose { .. })
+            );
+            let lasted = history
+                .samples
+                .iter()
+                .rev()
+                .find(|sample| sample.snapshots[player].colonies > 0)
+                .map(|sample| sample.turn)
+                .unwrap_or_default();
+            let last = history
+                .samples
+                .last()
+                .map(|sample| sample.snapshots[player])
+                .unwrap_or_default();
+            let peak = history
+                .samples
+                .iter()
+                .map(|sample| sample.snapshots[player])
+                .fold(Snapshot::default(), |peak, snapshot| Snapshot {
+                    population: peak.population.max(snapshot.population),
+                    revenue: peak.revenue.max(snapshot.revenue),
+                    savings: peak.savings.max(snapshot.savings),
+                    resources: peak.resources.max(snapshot.resources),
+                    colonies: peak.colonies.max(snapshot.colonies),
+                    fleet_strength: peak.fleet_strength.max(snapshot.fleet_strength),
+                });
+            (
+                (!eliminated, lasted, last.colonies, last.population),
+                Standing {
+                    name: universe.players[player].name.clone(),
+                    color: universe.owner_color(player),
+                    eliminated,
+                    last,
+                    peak,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    standings.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    standings
+        .into_iter()
+        .map(|(_, standing)| standing)
+        .collect()
+}
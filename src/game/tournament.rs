@@ -0,0 +1,356 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+
+use crate::assets::balance::Balance;
+
+use super::{
+    bots::{self, Personality},
+    fleet::Fleet,
+    galaxy::{GalaxyCreator, GalaxyKind},
+    setup::{DensityControl, SizeControl},
+    simulation::{self, Event, FleetState},
+    turns::{LoseCondition, Message},
+    victory::VictoryRules,
+    Controller, Universe,
+};
+
+const STAR_NAMES: &str = "assets/star.names";
+const BALANCE: &str = "assets/economy.balance";
+
+// read from a RON file, anything missing keeps the default of the setup screen
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    games: u32,
+    max_turns: u32,
+    // game n is played on seed + n so that any of them can be replayed
+    seed: u64,
+    nb_players: u32,
+    size: f32,
+    density: f32,
+    kind: GalaxyKind,
+    difficulty: f32,
+    // cycled over the player slots
+    personalities: Vec<Personality>,
+    victory: VictoryRules,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            games: 10,
+            max_turns: 300,
+            seed: 0,
+            nb_players: 4,
+            size: 3.0,
+            density: 1.0,
+            kind: GalaxyKind::default(),
+            difficulty: 1.0,
+            personalities: Personality::ALL.to_vec(),
+            victory: VictoryRules::default(),
+        }
+    }
+}
+
+impl Settings {
+    // galaxies are the ones of the setup screen, anything else may never finish generating
+    fn validate(&self) -> anyhow::Result<()> {
+        let sizes = SizeControl::ALL.map(f32::from);
+        if !sizes.contains(&self.size) {
+            anyhow::bail!("size {} is not one of {:?}", self.size, sizes);
+        }
+        let densities = DensityControl::ALL.map(f32::from);
+        if !densities.contains(&self.density) {
+            anyhow::bail!("density {} is not one of {:?}", self.density, densities);
+        }
+        Ok(())
+    }
+
+    fn creator(&self, seed: u64, names: Vec<String>) -> GalaxyCreator {
+        let mut creator = GalaxyCreator {
+            nb_players: self.nb_players,
+            size: self.size,
+            density: self.density,
+            kind: self.kind,
+            generated: Vec::new(),
+            names,
+            difficulty: self.difficulty,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            controllers: vec![],
+            personalities: vec![],
+            victory: self.victory,
+        };
+        // the slots are filled once the number of players fits the galaxy
+        creator.nb_players = creator.nb_players.clamp(2, creator.max_players());
+        let nb_players = creator.nb_players as usize;
+        creator.controllers = vec![Controller::Bot; nb_players];
+        creator.personalities = (0..nb_players)
+            .map(|slot| {
+                self.personalities
+                    .get(slot % self.personalities.len().max(1))
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect();
+        // stars are kept in `generated` as they are created
+        for _ in creator.by_ref() {}
+        creator
+    }
+}
+
+// usage: tournament [settings.ron] [results.csv]
+#[derive(Resource)]
+pub struct Tournament {
+    settings: Settings,
+    names: Vec<String>,
+    balance: Balance,
+    output: BufWriter<File>,
+    played: u32,
+    game: Option<Game>,
+}
+
+impl Tournament {
+    pub fn from_args() -> anyhow::Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let settings = match args.next() {
+            Some(path) => ron::from_str(&fs::read_to_string(path)?)?,
+            None => Settings::default(),
+        };
+        settings.validate()?;
+        let output = PathBuf::from(args.next().unwrap_or_else(|| "tournament.csv".to_string()));
+
+        // stars past the last name are numbered
+        let names = fs::read_to_string(STAR_NAMES)
+            .map(|names| names.lines().map(|name| name.to_string()).collect())
+            .unwrap_or_else(|error| {
+                warn!("error reading {}, using numbers: {}", STAR_NAMES, error);
+                (1..=1000).map(|i| format!("Star {}", i)).collect()
+            });
+
+        let balance = fs::read_to_string(BALANCE)
+            .map_err(anyhow::Error::from)
+            .and_then(|balance| Ok(ron::from_str(&balance)?))
+            .unwrap_or_else(|error| {
+                warn!("error reading {}, using the defaults: {}", BALANCE, error);
+                Balance::default()
+            });
+
+        let mut output = BufWriter::new(File::create(output)?);
+        writeln!(
+            output,
+            "game,seed,turns,winner,player,name,personality,result,defeated_turn,colonies,population,revenue,savings,resources,ships,ships_built"
+        )?;
+
+        Ok(Tournament {
+            settings,
+            names,
+            balance,
+            output,
+            played: 0,
+            game: None,
+        })
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct PlayerStats {
+    defeated: Option<(u32, LoseCondition)>,
+    ships_built: u32,
+    last_colony_ship_spawned: u32,
+}
+
+fn spawned_fleets(events: &[Event]) -> Vec<Fleet> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::FleetSpawned(fleet) => Some(fleet.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+struct Game {
+    seed: u64,
+    universe: Universe,
+    fleets: Vec<FleetState>,
+    next_id: u64,
+    turn: u32,
+    rng: StdRng,
+    stats: Vec<PlayerStats>,
+    winner: Option<usize>,
+}
+
+impl Game {
+    fn new(settings: &Settings, seed: u64, names: Vec<String>, balance: Balance) -> Self {
+        let mut creator = settings.creator(seed, names);
+        let (mut universe, fleets) = simulation::new_game(&mut creator);
+        universe.balance = balance;
+        let mut game = Game {
+            seed,
+            stats: vec![PlayerStats::default(); universe.pirates],
+            universe,
+            fleets: vec![],
+            next_id: 0,
+            turn: 0,
+            // bots draw from the same seed as the galaxy, a game can be played again exactly
+            rng: creator.rng,
+            winner: None,
+        };
+        game.spawn(fleets);
+        game
+    }
+
+    fn spawn(&mut self, fleets: Vec<Fleet>) {
+        for fleet in fleets {
+            self.fleets.push(FleetState {
+                id: self.next_id,
+                order: fleet.order,
+                kind: fleet.ship.kind,
+                size: fleet.size.0,
+                owner: fleet.owner.0,
+            });
+            self.next_id += 1;
+        }
+    }
+
+    // same order as in a game with a screen: the end of the previous turn, then the players
+    fn play_turn(&mut self) {
+        let events = simulation::end_turn(&mut self.universe, &mut self.fleets, self.turn);
+        self.spawn(spawned_fleets(&events));
+        // only what shipyards delivered, not the starting ships nor the split fleets
+        for event in &events {
+            if let Event::Message {
+                player,
+                message: Message::ShipsBuilt { size, .. },
+            } = event
+            {
+                self.stats[*player].ships_built += size;
+            }
+        }
+        self.turn += 1;
+
+        for player in 0..self.universe.pirates {
+            match simulation::outcome(&self.universe, player, self.turn) {
+                Some(Message::Lose { condition }) if self.stats[player].defeated.is_none() => {
+                    self.stats[player].defeated = Some((self.turn, condition));
+                }
+                Some(Message::Win) | Some(Message::Victory { .. }) => self.winner = Some(player),
+                _ => (),
+            }
+        }
+        if self.is_over() {
+            return;
+        }
+
+        for player in 0..self.universe.pirates {
+            let orders = bots::bot_orders(
+                &self.universe,
+                &self.fleets,
+                player,
+                self.turn,
+                &mut self.stats[player].last_colony_ship_spawned,
+                &mut self.rng,
+            );
+            let (spawned, _) =
+                simulation::apply_orders(&mut self.universe, &mut self.fleets, player, &orders);
+            self.spawn(spawned);
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.winner.is_some() || self.stats.iter().all(|stats| stats.defeated.is_some())
+    }
+
+    fn write(&self, number: u32, output: &mut impl Write) -> std::io::Result<()> {
+        let universe = &self.universe;
+        let winner = self
+            .winner
+            .map(|winner| universe.players[winner].name.clone())
+            .unwrap_or_else(|| "draw".to_string());
+        for (player, stats) in self.stats.iter().enumerate() {
+            let result = match (self.winner, stats.defeated) {
+                (Some(winner), _) if winner == player => "won",
+                (_, Some((_, LoseCondition::Deficit))) => "deficit",
+                (_, Some((_, LoseCondition::NoMoreColonies))) => "no more colonies",
+                (Some(_), None) => "lost",
+                (None, None) => "draw",
+            };
+            let fleets = self.fleets.iter().filter(|fleet| fleet.owner == player);
+            writeln!(
+                output,
+                "{},{},{},{},{},{},{},{},{},{},{:.0},{:.2},{:.2},{:.2},{},{}",
+                number,
+                self.seed,
+                self.turn,
+                winner,
+                player,
+                universe.players[player].name,
+                universe.players[player].personality,
+                result,
+                stats
+                    .defeated
+                    .map(|(turn, _)| turn.to_string())
+                    .unwrap_or_default(),
+                universe
+                    .star_details
+                    .iter()
+                    .filter(|details| details.owner == player)
+                    .count(),
+                universe.player_population(player),
+                universe.player_revenue(player),
+                universe.players[player].savings,
+                universe.players[player].resources,
+                fleets.map(|fleet| fleet.size).sum::<u32>(),
+                stats.ships_built,
+            )?;
+        }
+        output.flush()
+    }
+}
+
+pub struct Plugin;
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(play);
+    }
+}
+
+// one turn per frame, the games are played one after the other
+fn play(mut tournament: ResMut<Tournament>, mut app_exit_events: EventWriter<AppExit>) {
+    let tournament = &mut *tournament;
+    if tournament.played == tournament.settings.games {
+        app_exit_events.send(AppExit);
+        return;
+    }
+
+    let game = tournament.game.get_or_insert_with(|| {
+        let seed = tournament.settings.seed + tournament.played as u64;
+        Game::new(
+            &tournament.settings,
+            seed,
+            tournament.names.clone(),
+            tournament.balance.clone(),
+        )
+    });
+    game.play_turn();
+
+    if game.is_over() || game.turn >= tournament.settings.max_turns {
+        if let Err(error) = game.write(tournament.played, &mut tournament.output) {
+            error!("error writing results: {}", error);
+        }
+        info!(
+            "game {} on seed {} done in {} turns",
+            tournament.played, game.seed, game.turn
+        );
+        tournament.played += 1;
+        tournament.game = None;
+    }
+}
@@ -1,18 +1,34 @@
-use bevy::{prelude::*, utils::HashMap};
+use std::fmt;
 
-use crate::assets::{GalaxyAssets, UiAssets};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::{balance::Balance, GalaxyAssets, UiAssets},
+    GameState,
+};
 
 use super::{
-    bots::{self, BotTurnStatus},
-    fleet::{turns_between, FleetSize, Order, Owner, Ship, ShipKind},
+    bots::{self, BotTurnStatus, ReceivedOrders},
+    combat,
+    fleet::{fleet_states, update_fleets, FleetQuery, ShipKind},
     galaxy::StarColor,
+    pirates,
+    research::Tech,
+    save::LoadedGame,
+    simulation::{self, Event},
+    stats::StatsHistory,
+    victory::VictoryCondition,
     world::{StarHat, StarMask},
-    StarState, Universe,
+    Controller, FleetsToSpawn, StarState, Universe,
 };
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 pub enum TurnState {
     Player,
+    Handover,
+    // the turn is resolved by the host of a network game
+    Waiting,
     Bots,
     Enemy,
     Out,
@@ -22,13 +38,68 @@ pub enum TurnState {
 pub struct Turns {
     pub count: u32,
     pub messages: Vec<Message>,
+    // messages sent to each player between two of their turns, displayed with their next turn
+    pub pending: Vec<Vec<Message>>,
+    // every message already displayed to each player
+    pub journal: Vec<Vec<JournalEntry>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub turn: u32,
+    pub message: Message,
+}
+
+impl Turns {
+    pub fn send(&mut self, universe: &Universe, player: usize, message: Message) {
+        // nobody reads the messages of bots
+        if universe
+            .players
+            .get(player)
+            .map(|player| matches!(player.controller, Controller::Human | Controller::Remote))
+            .unwrap_or(false)
+        {
+            self.pending[player].push(message);
+        }
+    }
+}
+
+// the human in front of the screen, and the ones still to play this turn
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct CurrentPlayer {
+    pub index: usize,
+    pub waiting: Vec<usize>,
+    pub defeated: Vec<usize>,
+    // playing in a game hosted on another computer
+    #[serde(skip)]
+    pub remote: bool,
 }
 
+impl CurrentPlayer {
+    pub fn end_turn(&self, turn_state: &mut State<TurnState>) {
+        let _ = turn_state.set(if self.remote {
+            TurnState::Waiting
+        } else if self.waiting.is_empty() {
+            TurnState::Bots
+        } else {
+            TurnState::Handover
+        });
+    }
+
+    pub fn game_over(&self, universe: &Universe) -> bool {
+        universe
+            .humans()
+            .all(|human| self.defeated.contains(&human))
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum LoseCondition {
     Deficit,
     NoMoreColonies,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Message {
     Turn(u32),
     ColonyFounded {
@@ -40,6 +111,12 @@ pub enum Message {
         index: usize,
         player_name: String,
     },
+    ColonyCaptured {
+        star_name: String,
+        index: usize,
+        player_name: String,
+        attacker: bool,
+    },
     StarExplored {
         star_name: String,
         color_condition: bool,
@@ -52,30 +129,133 @@ pub enum Message {
         order: u32,
         index: Option<usize>,
     },
-    Fight {
+    Battle {
         index: usize,
         star_name: String,
-        attacker: bool,
-        ship_lost: u32,
-        ship_destroyed: u32,
-        population_killed: f32,
-        player_name: String,
+        rounds: u32,
+        // named sides of the battle, the one of the player receiving the message first
+        sides: Vec<(String, combat::Side)>,
+    },
+    PiratesSighted {
+        star_name: String,
+        index: usize,
+        fleet_size: u32,
+    },
+    PirateRaid {
+        star_name: String,
+        index: usize,
+        fleet_size: u32,
+    },
+    TechResearched {
+        tech: Tech,
+    },
+    ShipsBuilt {
+        star_name: String,
+        index: usize,
+        kind: ShipKind,
+        size: u32,
     },
     Win,
     Lose {
         condition: LoseCondition,
     },
+    Victory {
+        condition: VictoryCondition,
+    },
+    RivalVictory {
+        condition: VictoryCondition,
+        player_name: String,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageKind {
+    Fights,
+    Colonies,
+    Exploration,
+    Story,
+}
+
+impl MessageKind {
+    pub const ALL: [MessageKind; 4] = [
+        MessageKind::Fights,
+        MessageKind::Colonies,
+        MessageKind::Exploration,
+        MessageKind::Story,
+    ];
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MessageKind::Fights => "Fights",
+                MessageKind::Colonies => "Colonies",
+                MessageKind::Exploration => "Exploration",
+                MessageKind::Story => "Story",
+            }
+        )
+    }
 }
 
 impl Message {
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            Message::Battle { .. }
+            | Message::PiratesSighted { .. }
+            | Message::PirateRaid { .. } => MessageKind::Fights,
+            Message::ColonyFounded { .. }
+            | Message::ColonyDestroyed { .. }
+            | Message::ColonyCaptured { .. }
+            | Message::ShipsBuilt { .. } => MessageKind::Colonies,
+            Message::StarExplored { .. } => MessageKind::Exploration,
+            Message::Turn(_)
+            | Message::Story { .. }
+            | Message::TechResearched { .. }
+            | Message::Win
+            | Message::Lose { .. }
+            | Message::Victory { .. }
+            | Message::RivalVictory { .. } => MessageKind::Story,
+        }
+    }
+
+    // the star the message is about
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Message::ColonyFounded { index, .. }
+            | Message::ColonyDestroyed { index, .. }
+            | Message::ColonyCaptured { index, .. }
+            | Message::ShipsBuilt { index, .. }
+            | Message::StarExplored { index, .. }
+            | Message::Battle { index, .. }
+            | Message::PiratesSighted { index, .. }
+            | Message::PirateRaid { index, .. } => Some(*index),
+            Message::Story { index, .. } => *index,
+            Message::Turn(_)
+            | Message::TechResearched { .. }
+            | Message::Win
+            | Message::Lose { .. }
+            | Message::Victory { .. }
+            | Message::RivalVictory { .. } => None,
+        }
+    }
+
     fn order(&self) -> u32 {
         match self {
             Message::Turn(_) => 0,
             Message::StarExplored { .. } => 1,
-            Message::Fight { .. } => 2,
-            Message::ColonyFounded { .. } => 3,
-            Message::ColonyDestroyed { .. } => 4,
-            Message::Win | Message::Lose { .. } => 5,
+            Message::Battle { .. } => 2,
+            Message::PiratesSighted { .. } | Message::PirateRaid { .. } => 2,
+            Message::ColonyFounded { .. }
+            | Message::TechResearched { .. }
+            | Message::ShipsBuilt { .. } => 3,
+            Message::ColonyDestroyed { .. } | Message::ColonyCaptured { .. } => 4,
+            Message::Win
+            | Message::Lose { .. }
+            | Message::Victory { .. }
+            | Message::RivalVictory { .. } => 5,
             Message::Story { order, .. } => 6 + order,
         }
     }
@@ -120,6 +300,33 @@ impl Message {
                     },
                 },
             ],
+            Message::ColonyCaptured {
+                star_name,
+                player_name,
+                attacker,
+                ..
+            } => vec![
+                TextSection {
+                    value: "Colony captured\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: if *attacker {
+                        format!("Your troops took {}\nfrom {}", star_name, player_name)
+                    } else {
+                        format!("{} captured your colony\non {}", player_name, star_name)
+                    },
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
             Message::StarExplored {
                 star_name,
                 color_condition,
@@ -177,17 +384,14 @@ impl Message {
                     },
                 },
             ],
-            Message::Fight {
+            Message::Battle {
                 star_name,
-                attacker,
-                ship_lost,
-                ship_destroyed,
-                population_killed,
-                player_name,
+                rounds,
+                sides,
                 ..
             } => vec![
                 TextSection {
-                    value: format!("Fight on {}\n", star_name),
+                    value: format!("Battle on {}\n", star_name),
                     style: TextStyle {
                         font: ui_handles.font_main.clone_weak(),
                         font_size: 20.0,
@@ -195,11 +399,121 @@ impl Message {
                     },
                 },
                 TextSection {
-                    value: if *attacker {
-                        format!("You attacked {}\nand lost {} ships.\nYou destroyed {} ships and\nkilled {:.1} population.", player_name, ship_lost, ship_destroyed, population_killed)
-                    } else {
-                        format!("You defended against {},\nlost {} ships and {:.1} population.\nYou destroyed {} ships.", player_name, ship_lost, population_killed, ship_destroyed)
+                    value: format!(
+                        "{} round(s) of fighting.\n{}",
+                        rounds,
+                        sides
+                            .iter()
+                            .enumerate()
+                            .map(|(i, (name, side))| {
+                                let mut losses = format!(
+                                    "{} lost {}/{} ships",
+                                    if i == 0 { "You" } else { name },
+                                    side.ships_lost,
+                                    side.ships
+                                );
+                                if side.population_lost > 0.0 {
+                                    losses.push_str(&format!(
+                                        "\nand {:.1} population",
+                                        side.population_lost
+                                    ));
+                                }
+                                losses
+                            })
+                            .collect::<Vec<_>>()
+                            .join(",\n")
+                    ),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
+            Message::PiratesSighted {
+                star_name,
+                fleet_size,
+                ..
+            } => vec![
+                TextSection {
+                    value: "Pirates sighted\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: format!(
+                        "{} pirate ships are\ngathering around {}.",
+                        fleet_size, star_name
+                    ),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
+            Message::PirateRaid {
+                star_name,
+                fleet_size,
+                ..
+            } => vec![
+                TextSection {
+                    value: "Pirate raid\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: format!(
+                        "{} pirate ships are\nheading to your colony\non {}.",
+                        fleet_size, star_name
+                    ),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
+            Message::ShipsBuilt {
+                star_name,
+                kind,
+                size,
+                ..
+            } => vec![
+                TextSection {
+                    value: "Ships built\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: format!("{} {} ready\naround {}", size, kind, star_name),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
+            Message::TechResearched { tech } => vec![
+                TextSection {
+                    value: format!("{} researched\n", tech),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
                     },
+                },
+                TextSection {
+                    value: tech.description().to_string(),
                     style: TextStyle {
                         font: ui_handles.font_sub.clone_weak(),
                         font_size: 20.0,
@@ -248,673 +562,251 @@ impl Message {
                     },
                 },
             ],
+            Message::Victory { condition } => vec![
+                TextSection {
+                    value: "You won\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: condition.achievement().to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
+            Message::RivalVictory {
+                condition,
+                player_name,
+            } => vec![
+                TextSection {
+                    value: "You lost\n".to_string(),
+                    style: TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+                TextSection {
+                    value: format!("{} won by\n{}.", player_name, condition),
+                    style: TextStyle {
+                        font: ui_handles.font_sub.clone_weak(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                },
+            ],
         }
     }
 }
 
-#[derive(Debug)]
-struct FightReport {
-    against: usize,
-    attacker: bool,
-    ship_lost: u32,
-    ship_destroyed: u32,
-    population_killed: f32,
-}
-
 pub struct Plugin;
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_state(TurnState::Out)
-            .insert_resource(BotTurnStatus {
-                current: 0,
-                last_colony_ship_spawned: vec![0; 5],
-            })
+            .init_resource::<BotTurnStatus>()
+            .init_resource::<CurrentPlayer>()
+            .init_resource::<ReceivedOrders>()
+            .init_resource::<StatsHistory>()
             .add_system_set(SystemSet::on_enter(TurnState::Player).with_system(start_player_turn))
             .add_system_set(SystemSet::on_enter(TurnState::Bots).with_system(bots::start_bots))
             .add_system_set(SystemSet::on_update(TurnState::Bots).with_system(bots::run_bots_turn))
-            .add_system_set(SystemSet::on_update(TurnState::Enemy).with_system(run_enemy_turn));
+            .add_system_set(
+                SystemSet::on_update(TurnState::Enemy).with_system(pirates::run_pirates_turn),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game).with_system(reload_balance));
     }
 }
 
-#[allow(clippy::type_complexity)]
-fn update_mask_for_star(
-    star: usize,
-    owner: usize,
-    decorations: &mut ParamSet<(
-        Query<(&mut Visibility, &StarHat)>,
-        Query<(&mut Visibility, &mut Sprite, &StarMask)>,
-    )>,
+// with the `hot` feature, changes to the balance file apply to the game being played
+fn reload_balance(
+    mut events: EventReader<AssetEvent<Balance>>,
+    balances: Res<Assets<Balance>>,
+    galaxy_assets: Res<GalaxyAssets>,
+    current: Res<CurrentPlayer>,
+    mut universe: ResMut<Universe>,
 ) {
-    let mut p1 = decorations.p1();
-    let mut p11 = p1.iter_mut();
-    let (mut visibility, mut sprite, _) = p11.find(|(_, _, mask)| mask.0 == star).unwrap();
-    if owner != 0 {
-        visibility.is_visible = true;
-        sprite.color = match owner {
-            1 => Color::RED,
-            2 => Color::BLUE,
-            3 => Color::PURPLE,
-            4 => Color::BLACK,
-            _ => unreachable!(),
-        };
-    } else {
-        visibility.is_visible = false;
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            // the host of a network game decides of the balance
+            if *handle != galaxy_assets.balance || current.remote {
+                continue;
+            }
+            if let Some(balance) = balances.get(handle) {
+                info!("balance reloaded");
+                universe.balance = balance.clone();
+            }
+        }
     }
 }
 
 #[allow(clippy::type_complexity)]
 fn start_player_turn(
     mut commands: Commands,
+    loaded: Option<Res<LoadedGame>>,
     mut universe: ResMut<Universe>,
     mut turns: ResMut<Turns>,
+    mut current: ResMut<CurrentPlayer>,
+    mut turn_state: ResMut<State<TurnState>>,
     galaxy_assets: Res<GalaxyAssets>,
-    mut fleets: Query<(Entity, &mut Order, &Ship, &Owner, &mut FleetSize)>,
+    mut fleets: FleetQuery,
     mut materials: Query<&mut Handle<ColorMaterial>>,
-    mut decorations: ParamSet<(
-        Query<(&mut Visibility, &StarHat)>,
-        Query<(&mut Visibility, &mut Sprite, &StarMask)>,
-    )>,
+    mut hats: Query<(&mut Visibility, &StarHat)>,
+    mut masks: Query<(&mut Visibility, &mut Sprite, &StarMask), Without<StarHat>>,
+    mut history: ResMut<StatsHistory>,
+    mut fleets_to_spawn: ResMut<FleetsToSpawn>,
 ) {
-    turns.messages = vec![];
-
-    if turns.count != 0 {
-        for i in 0..universe.players.len() {
-            let good_conditions = &universe.galaxy[universe.players[i].start].clone();
-            universe.players[i].savings += universe.player_revenue(i);
-
-            let mut harvested = 0.0;
-            universe
-                .galaxy
-                .clone()
-                .iter()
-                .zip(universe.star_details.iter_mut())
-                .filter(|(_, details)| details.owner == i)
-                .for_each(|(star, details)| {
-                    // grow population
-                    {
-                        let max_population = if star.color == good_conditions.color {
-                            120.0 + (turns.count as f32 - details.owned_since as f32) / 5.0
-                        } else {
-                            10.0 + (turns.count as f32 - details.owned_since as f32) / 10.0
-                        };
-                        let lerp = (details.population / max_population).min(1.2);
-                        let growth_factor = if lerp < 0.5 {
-                            (10.0 * lerp).powf(3.0)
-                        } else if lerp < 1.0 {
-                            1.0 - (-2.0 * lerp + 2.0).powf(3.0) / 2.0
-                        } else {
-                            1.0 - (-2.0 * lerp + 4.0).powf(3.0) / 2.0
-                        };
-                        details.population = if star.size == good_conditions.size {
-                            details.population + growth_factor
-                        } else {
-                            details.population + growth_factor / 2.0
-                        };
-                    }
-
-                    // harvest resources
-                    {
-                        let to_get: f32 = if star.color == good_conditions.color {
-                            0.2
-                        } else {
-                            1.5
-                        };
-                        let current_resources = (details.resources * 1.2).powf(1.5);
-                        let collect = to_get.min(current_resources);
-                        harvested += collect;
-                        details.resources = if star.color != good_conditions.color {
-                            ((details.resources * 1.2).powf(1.5) - collect).powf(1.0 / 1.5) / 1.2
-                        } else {
-                            ((details.resources).powf(0.8) - collect).powf(1.0 / 0.8)
-                        }
-                        .max(0.0);
-                    }
-                });
-            universe.players[i].resources += harvested;
-        }
+    if loaded.is_some() {
+        // this turn was already resolved before the game was saved
+        commands.remove_resource::<LoadedGame>();
+        return;
     }
 
-    let mut fleets_per_star: HashMap<usize, Vec<_>> = fleets.iter().fold(
-        HashMap::new(),
-        |mut acc, (_, order, ship, owner, fleet_size)| {
-            match order {
-                Order::Orbit(around) => {
-                    acc.entry(*around)
-                        .or_default()
-                        .push((*owner, *ship, fleet_size.0 as i32))
+    let mut events = vec![];
+    if current.waiting.is_empty() {
+        // every human played, time to resolve the turn
+        let mut states = fleet_states(&fleets);
+        let count = turns.count;
+        events = simulation::resolve_turn(&mut universe, &mut states, count);
+        update_fleets(&mut fleets, &states);
+
+        for event in &events {
+            match event {
+                Event::FleetDestroyed(id) => {
+                    commands.entity(Entity::from_bits(*id)).despawn_recursive();
                 }
-                Order::Move { from, to, step, .. } => {
-                    if *step + 1
-                        == turns_between(
-                            universe.galaxy[*from].position,
-                            universe.galaxy[*to].position,
-                        )
-                    {
-                        acc.entry(*to)
-                            .or_default()
-                            .push((*owner, *ship, fleet_size.0 as i32))
-                    }
-                }
-            };
-            acc
-        },
-    );
-
-    let mut fight_reports_per_star = HashMap::new();
-
-    'next_ship: for (entity, mut order, ship, owner, mut fleet_size) in &mut fleets {
-        match order.bypass_change_detection() {
-            Order::Orbit(around) => match ship.kind {
-                ShipKind::Colony => {
-                    if let Some(other_owner) = fleets_per_star.get(around).and_then(|entry| {
-                        entry
-                            .iter()
-                            .map(|(other_owner, _, _)| other_owner)
-                            .find(|other_owner| other_owner.0 != owner.0)
-                    }) {
-                        // There is an ennemy ship, colony ships always lose
-                        commands.entity(entity).despawn_recursive();
-                        if owner.0 == 0 {
-                            fight_reports_per_star
-                                .entry(*around)
-                                .or_insert(FightReport {
-                                    against: other_owner.0,
-                                    attacker: false,
-                                    ship_lost: 0,
-                                    ship_destroyed: 0,
-                                    population_killed: 0.0,
-                                })
-                                .ship_lost += fleet_size.0;
-                        } else if other_owner.0 == 0 {
-                            fight_reports_per_star
-                                .entry(*around)
-                                .or_insert(FightReport {
-                                    against: owner.0,
-                                    attacker: true,
-                                    ship_lost: 0,
-                                    ship_destroyed: 0,
-                                    population_killed: 0.0,
-                                })
-                                .ship_destroyed += fleet_size.0;
-                        }
-                    }
-                }
-                ShipKind::Fighter => {
-                    let enemy_fighters = fleets_per_star
-                        .get(around)
-                        .map(|entry| {
-                            entry
-                                .iter()
-                                .filter(|(other_owner, ship, _)| {
-                                    other_owner.0 != owner.0 && ship.kind == ShipKind::Fighter
-                                })
-                                .fold(
-                                    vec![0, 0, 0, 0, 0],
-                                    |mut acc, (other_owner, _, fleet_size)| {
-                                        acc[other_owner.0] += fleet_size;
-                                        acc
-                                    },
-                                )
-                        })
-                        .unwrap_or_else(|| vec![0; 5]);
-
-                    for (u, n) in enemy_fighters.iter().enumerate() {
-                        if fleet_size.0 > 0 && *n > 0 {
-                            let current_lost = fleet_size.0.min(*n as u32);
-                            fleet_size.0 -= current_lost;
-                            fleets_per_star.get_mut(around).unwrap().push((
-                                Owner(u),
-                                Ship {
-                                    kind: ShipKind::Fighter,
-                                },
-                                -(current_lost as i32),
-                            ));
-                            if current_lost > 0 {
-                                if owner.0 == 0 {
-                                    fight_reports_per_star
-                                        .entry(*around)
-                                        .or_insert(FightReport {
-                                            against: u,
-                                            attacker: false,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_lost += current_lost;
-                                } else if u == 0 {
-                                    fight_reports_per_star
-                                        .entry(*around)
-                                        .or_insert(FightReport {
-                                            against: owner.0,
-                                            attacker: true,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_destroyed += current_lost;
-                                }
-                            }
-                            if fleet_size.0 == 0 {
-                                commands.entity(entity).despawn_recursive();
-                                continue 'next_ship;
-                            }
-                        }
-                    }
+                Event::FleetSpawned(fleet) => fleets_to_spawn.0.push(fleet.clone()),
+                Event::Message { player, message } => {
+                    turns.send(&universe, *player, message.clone());
                 }
-            },
-            Order::Move { from, to, step, .. } => {
-                *step += 1;
-                if *step
-                    == turns_between(
-                        universe.galaxy[*from].position,
-                        universe.galaxy[*to].position,
-                    )
-                {
-                    // exploration
-                    if universe.star_details[*to].owner != owner.0 {
-                        // star exploration and visibility in universe
-                        if owner.0 == 0 {
-                            if universe.players[owner.0].vision[*to] == StarState::Unknown {
-                                let start_conditions = &universe.galaxy[universe.players[0].start];
-                                let new_star = &universe.galaxy[*to];
-                                turns.messages.push(Message::StarExplored {
-                                    star_name: universe.galaxy[*to].name.clone(),
-                                    color_condition: start_conditions.color == new_star.color,
-                                    size_condition: start_conditions.size == new_star.size,
-                                    index: *to,
-                                });
-                            }
-
-                            *materials.get_mut(universe.star_entities[*to]).unwrap() =
-                                match universe.galaxy[*to].color {
-                                    StarColor::Blue => galaxy_assets.blue_star.clone_weak(),
-                                    StarColor::Orange => galaxy_assets.orange_star.clone_weak(),
-                                    StarColor::Yellow => galaxy_assets.yellow_star.clone_weak(),
-                                };
-                        }
-                    }
-                    match ship.kind {
-                        ShipKind::Colony => {
-                            // fight against fleets
-                            if let Some(other_owner) = fleets_per_star.get(to).and_then(|entry| {
-                                entry
-                                    .iter()
-                                    .map(|(other_owner, _, _)| other_owner)
-                                    .find(|other_owner| other_owner.0 != owner.0)
-                            }) {
-                                // There is an ennemy ship, colony ships always lose
-                                commands.entity(entity).despawn_recursive();
-
-                                universe.players[owner.0].vision[*to] =
-                                    StarState::Owned(other_owner.0);
-
-                                if owner.0 == 0 {
-                                    update_mask_for_star(*to, other_owner.0, &mut decorations);
-                                    fight_reports_per_star
-                                        .entry(*to)
-                                        .or_insert(FightReport {
-                                            against: other_owner.0,
-                                            attacker: true,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_lost += fleet_size.0;
-                                } else if other_owner.0 == 0 {
-                                    fight_reports_per_star
-                                        .entry(*to)
-                                        .or_insert(FightReport {
-                                            against: owner.0,
-                                            attacker: false,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_destroyed += fleet_size.0;
-                                }
-                                // ship destroyed, continue with next ship
-                                continue 'next_ship;
-                            }
-
-                            // fight against population
-                            if universe.star_details[*to].owner != owner.0
-                                && universe.star_details[*to].owner != usize::MAX
-                            {
-                                // fight against population, colony ship always lose
-                                commands.entity(entity).despawn_recursive();
-                                universe.players[owner.0].vision[*to] =
-                                    StarState::Owned(universe.star_details[*to].owner);
-
-                                if owner.0 == 0 {
-                                    update_mask_for_star(
-                                        *to,
-                                        universe.star_details[*to].owner,
-                                        &mut decorations,
-                                    );
-                                    fight_reports_per_star
-                                        .entry(*to)
-                                        .or_insert(FightReport {
-                                            against: universe.star_details[*to].owner,
-                                            attacker: true,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_lost += fleet_size.0;
-                                } else if universe.star_details[*to].owner == 0 {
-                                    fight_reports_per_star
-                                        .entry(*to)
-                                        .or_insert(FightReport {
-                                            against: owner.0,
-                                            attacker: false,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        })
-                                        .ship_destroyed += fleet_size.0;
-                                }
-
-                                // ship destroyed, continue with next ship
-                                continue 'next_ship;
-                            }
-
-                            // colonize the star!
-                            if universe.star_details[*to].owner != owner.0 {
-                                if owner.0 == 0 {
-                                    turns.messages.push(Message::ColonyFounded {
-                                        star_name: universe.galaxy[*to].name.clone(),
-                                        index: *to,
-                                    });
-                                    if !universe.players[0].first_colony_done {
-                                        universe.players[0].first_colony_done = true;
-                                        turns.messages.push(Message::Story {
-                                            title: "First colony!".to_string(),
-                                            details: r#"You just founded your first colony!
-If the color is the same as your
-starting system, your population
-will grow faster, but you'll
-get less resources."#
-                                                .to_string(),
-                                            order: 0,
-                                            index: None,
-                                        });
-                                        turns.messages.push(Message::Story {
-                                            title: "revenue".to_string(),
-                                            details: r#"New colonies cost credits.
-Once population has grown, colonies
-will start earning credits."#
-                                                .to_string(),
-                                            order: 1,
-                                            index: None,
-                                        });
-                                    }
-
-                                    decorations
-                                        .p0()
-                                        .iter_mut()
-                                        .find(|(_, hat)| hat.0 == *to)
-                                        .unwrap()
-                                        .0
-                                        .is_visible = true;
-                                    update_mask_for_star(*to, 0, &mut decorations);
-                                }
-
-                                // Colony ship is consumed
-                                commands.entity(entity).despawn_recursive();
-
-                                universe.players[owner.0].vision[*to] = StarState::Owned(owner.0);
-                                universe.star_details[*to].owner = owner.0;
-                                universe.star_details[*to].owned_since = turns.count;
-                                universe.star_details[*to].population = 10.0;
-                            }
-                        }
-                        ShipKind::Fighter => {
-                            let enemy_fighters = fleets_per_star
-                                .get(to)
-                                .map(|entry| {
-                                    entry
-                                        .iter()
-                                        .filter(|(other_owner, ship, _)| {
-                                            other_owner.0 != owner.0
-                                                && ship.kind == ShipKind::Fighter
-                                        })
-                                        .fold(
-                                            vec![0, 0, 0, 0, 0],
-                                            |mut acc, (other_owner, _, fleet_size)| {
-                                                acc[other_owner.0] += fleet_size;
-                                                acc
-                                            },
-                                        )
-                                })
-                                .unwrap_or_else(|| vec![0; 5]);
-
-                            for (u, n) in enemy_fighters.iter().enumerate() {
-                                if fleet_size.0 > 0 && *n > 0 {
-                                    let current_lost = fleet_size.0.min(*n as u32);
-                                    fleet_size.0 -= current_lost;
-                                    fleets_per_star.get_mut(to).unwrap().push((
-                                        Owner(u),
-                                        Ship {
-                                            kind: ShipKind::Fighter,
-                                        },
-                                        -(current_lost as i32),
-                                    ));
-                                    if *n > 0 {
-                                        if owner.0 == 0 {
-                                            fight_reports_per_star
-                                                .entry(*to)
-                                                .or_insert(FightReport {
-                                                    against: u,
-                                                    attacker: true,
-                                                    ship_lost: 0,
-                                                    ship_destroyed: 0,
-                                                    population_killed: 0.0,
-                                                })
-                                                .ship_lost += current_lost;
-                                        } else if u == 0 {
-                                            fight_reports_per_star
-                                                .entry(*to)
-                                                .or_insert(FightReport {
-                                                    against: owner.0,
-                                                    attacker: false,
-                                                    ship_lost: 0,
-                                                    ship_destroyed: 0,
-                                                    population_killed: 0.0,
-                                                })
-                                                .ship_destroyed += current_lost;
-                                        }
-                                        if fleet_size.0 == 0 {
-                                            commands.entity(entity).despawn_recursive();
-                                            universe.players[owner.0].vision[*to] =
-                                                StarState::Owned(u);
-                                            if owner.0 == 0 {
-                                                update_mask_for_star(*to, u, &mut decorations);
-                                            }
-                                            // ship destroyed, continue with next ship
-                                            continue 'next_ship;
-                                        }
-                                    }
-                                }
-                            }
-                            if universe.star_details[*to].population == 0.0 {
-                                for (n, u) in enemy_fighters.iter().enumerate() {
-                                    if *u > 0 {
-                                        // player n had ship on this star, and an enemy ship survived
-                                        universe.players[n].vision[*to] = StarState::Owned(owner.0);
-                                        if n == 0 {
-                                            update_mask_for_star(*to, owner.0, &mut decorations);
-                                        }
-                                    }
-                                }
-                                universe.players[owner.0].vision[*to] = StarState::Uninhabited;
-                                if owner.0 == 0 {
-                                    update_mask_for_star(*to, 0, &mut decorations);
-                                }
-                            }
-
-                            let attacked = universe.star_details[*to].owner;
-                            if attacked != owner.0 && attacked != usize::MAX {
-                                // fight against population, each fighter kills 10 population
-                                let mut population = universe.star_details[*to].population;
-                                let mut killed = 0.0;
-                                let mut lost = 0;
-                                while population >= 10.0 && fleet_size.0 > 0 {
-                                    population -= 10.0;
-                                    killed += 10.0;
-                                    fleet_size.0 -= 1;
-                                    lost += 1;
-                                }
-
-                                if fleet_size.0 > 0 && population < 10.0 {
-                                    // fleet is victorious
-                                    killed = universe.star_details[*to].population;
-                                    universe.star_details[*to].population = 0.0;
-                                    universe.star_details[*to].owner = usize::MAX;
-                                    universe.players[owner.0].vision[*to] = StarState::Uninhabited;
-                                    universe.players[attacked].vision[*to] =
-                                        StarState::Owned(owner.0);
-                                    if owner.0 == 0 {
-                                        update_mask_for_star(*to, 0, &mut decorations);
-                                    } else if attacked == 0 {
-                                        update_mask_for_star(*to, owner.0, &mut decorations);
-                                        decorations
-                                            .p0()
-                                            .iter_mut()
-                                            .find(|(_, hat)| hat.0 == *to)
-                                            .unwrap()
-                                            .0
-                                            .is_visible = false;
-
-                                        turns.messages.push(Message::ColonyDestroyed {
-                                            star_name: universe.galaxy[*to].name.clone(),
-                                            player_name: universe.players[owner.0].name.clone(),
-                                            index: *to,
-                                        });
-                                    }
-                                } else {
-                                    // fleet is destroyed
-                                    commands.entity(entity).despawn_recursive();
-                                    universe.star_details[*to].population = population;
+                _ => (),
+            }
+        }
 
-                                    universe.players[owner.0].vision[*to] =
-                                        StarState::Owned(attacked);
-                                    if owner.0 == 0 {
-                                        update_mask_for_star(*to, attacked, &mut decorations);
-                                    }
-                                }
+        turns.count += 1;
+        let count = turns.count;
+        let humans = universe.humans().collect::<Vec<_>>();
+        for human in humans {
+            if current.defeated.contains(&human) {
+                continue;
+            }
+            current.waiting.push(human);
+
+            if universe.player_revenue(human) < 0.0 {
+                turns.pending[human].push(Message::Story {
+                    title: "Revenue Alert!".to_string(),
+                    details: "You have negative revenue.\nToo much debt and you'll lose\nthe game."
+                        .to_string(),
+                    order: 0,
+                    index: None,
+                });
+            }
 
-                                if owner.0 == 0 {
-                                    let mut report =
-                                        fight_reports_per_star.entry(*to).or_insert(FightReport {
-                                            against: attacked,
-                                            attacker: true,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        });
-                                    report.ship_lost += lost;
-                                    report.population_killed += killed;
-                                } else if attacked == 0 {
-                                    let mut report =
-                                        fight_reports_per_star.entry(*to).or_insert(FightReport {
-                                            against: owner.0,
-                                            attacker: false,
-                                            ship_lost: 0,
-                                            ship_destroyed: 0,
-                                            population_killed: 0.0,
-                                        });
-                                    report.ship_destroyed += lost;
-                                    report.population_killed += killed;
-                                }
-                            }
-                        }
-                    }
+            turns.pending[human].push(Message::Turn(count));
+            if count == 1 {
+                turns.pending[human].push(Message::Story {
+                    title: "You can see your starting\nstar system".to_string(),
+                    details: "Click on it for more details.".to_string(),
+                    order: 0,
+                    index: None,
+                });
+                turns.pending[human].push(Message::Story {
+                    title: "Fleet panel".to_string(),
+                    details: "Drag and drop your colony ship\nto another star to launch it."
+                        .to_string(),
+                    order: 1,
+                    index: Some(universe.players[human].start),
+                });
+                turns.pending[human].push(Message::Story {
+                    title: "Ending turn".to_string(),
+                    details: r#"You can end your turn with the
+button in the bottom right corner."#
+                        .to_string(),
+                    order: 1,
+                    index: None,
+                });
+                turns.pending[human].push(Message::Story {
+                    title: "Let's explore!".to_string(),
+                    details: "".to_string(),
+                    order: 4,
+                    index: None,
+                });
+            }
 
-                    *order = Order::Orbit(*to);
+            if let Some(outcome) = simulation::outcome(&universe, human, count) {
+                if matches!(outcome, Message::Lose { .. } | Message::RivalVictory { .. }) {
+                    current.defeated.push(human);
                 }
-                order.set_changed();
+                turns.pending[human].push(outcome);
             }
         }
-    }
 
-    for (index, fight_report) in fight_reports_per_star {
-        turns.messages.push(Message::Fight {
-            index,
-            star_name: universe.galaxy[index].name.clone(),
-            attacker: fight_report.attacker,
-            ship_lost: fight_report.ship_lost,
-            ship_destroyed: fight_report.ship_destroyed,
-            population_killed: fight_report.population_killed,
-            player_name: universe.players[fight_report.against].name.clone(),
-        });
+        if current.waiting.len() > 1 {
+            // the device must change hands before the first human plays
+            let _ = turn_state.set(TurnState::Handover);
+            return;
+        }
     }
 
-    let revenue = universe.player_revenue(0);
-    if revenue < 0.0 {
-        turns.messages.push(Message::Story {
-            title: "Revenue Alert!".to_string(),
-            details: "You have negative revenue.\nToo much debt and you'll lose\nthe game."
-                .to_string(),
-            order: 0,
-            index: None,
-        });
+    if current.waiting.is_empty() {
+        return;
     }
-
-    turns.count += 1;
-    let count = turns.count;
-    turns.messages.push(Message::Turn(count));
-    if turns.count == 1 {
-        turns.messages.push(Message::Story {
-            title: "You can see your starting\nstar system".to_string(),
-            details: "Click on it for more details.".to_string(),
-            order: 0,
-            index: None,
-        });
-        turns.messages.push(Message::Story {
-            title: "Fleet panel".to_string(),
-            details: "Drag and drop your colony ship\nto another star to launch it.".to_string(),
-            order: 1,
-            index: Some(universe.players[0].start),
-        });
-        turns.messages.push(Message::Story {
-            title: "Ending turn".to_string(),
-            details: r#"You can end your turn with the
-button in the bottom right corner."#
-                .to_string(),
-            order: 1,
-            index: None,
-        });
-        turns.messages.push(Message::Story {
-            title: "Let's explore!".to_string(),
-            details: "".to_string(),
-            order: 4,
-            index: None,
-        });
+    let human = current.waiting.remove(0);
+    current.index = human;
+    // clients record the view they receive, their fleets aren't spawned yet
+    if !current.remote {
+        history.record(turns.count, &universe, &fleet_states(&fleets));
     }
-
-    if !universe
-        .star_details
-        .iter()
-        .any(|details| details.owner == 0)
-    {
-        turns.messages.push(Message::Lose {
-            condition: LoseCondition::NoMoreColonies,
-        });
-    } else if universe.players[0].savings < -100.0 {
-        turns.messages.push(Message::Lose {
-            condition: LoseCondition::Deficit,
-        });
-    } else if !universe
-        .star_details
+    turns.messages = std::mem::take(&mut turns.pending[human]);
+    turns.messages.sort_by_key(|m| m.order());
+    let count = turns.count;
+    let entries = turns
+        .messages
         .iter()
-        .any(|details| details.owner != 0 && details.owner != usize::MAX)
-    {
-        turns.messages.push(Message::Win);
+        .filter(|message| !matches!(message, Message::Turn(_)))
+        .map(|message| JournalEntry {
+            turn: count,
+            message: message.clone(),
+        })
+        .collect::<Vec<_>>();
+    turns.journal[human].extend(entries);
+
+    for event in events {
+        match event {
+            Event::StarVisited { player, star } if player == human => {
+                *materials.get_mut(universe.star_entities[star]).unwrap() =
+                    match universe.galaxy[star].color {
+                        StarColor::Blue => galaxy_assets.blue_star.clone_weak(),
+                        StarColor::Orange => galaxy_assets.orange_star.clone_weak(),
+                        StarColor::Yellow => galaxy_assets.yellow_star.clone_weak(),
+                    };
+            }
+            Event::VisionChanged {
+                player,
+                star,
+                state,
+            } if player == human => {
+                if let Some((mut visibility, _)) = hats.iter_mut().find(|(_, hat)| hat.0 == star) {
+                    visibility.is_visible = state == StarState::Owned(human);
+                }
+                if let Some((mut visibility, mut sprite, _)) =
+                    masks.iter_mut().find(|(_, _, mask)| mask.0 == star)
+                {
+                    match state {
+                        StarState::Owned(owner) if owner != human => {
+                            visibility.is_visible = true;
+                            sprite.color = universe.owner_color(owner);
+                        }
+                        _ => visibility.is_visible = false,
+                    }
+                }
+            }
+            _ => (),
+        }
     }
-
-    turns.messages.sort_by_key(|m| m.order());
-}
-
-fn run_enemy_turn(mut state: ResMut<State<TurnState>>) {
-    let _ = state.set(TurnState::Player);
 }
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::UiAssets,
+    game::{
+        turns::{CurrentPlayer, TurnState},
+        ui::ScreenTag,
+        Universe,
+    },
+};
+
+#[derive(Component)]
+pub struct HandoverTag;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ready;
+
+impl From<Ready> for String {
+    fn from(_: Ready) -> Self {
+        "Ready".to_string()
+    }
+}
+
+// hides the galaxy while the device is passed to the next human
+pub fn setup(
+    mut commands: Commands,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
+) {
+    let Some(next) = current.waiting.first() else {
+        return;
+    };
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+    let ready = button.add(
+        &mut commands,
+        Val::Px(150.),
+        Val::Px(40.),
+        UiRect::all(Val::Px(20.0)),
+        ui_handles.font_sub.clone_weak(),
+        Ready,
+        25.,
+        crate::ui_helper::ColorScheme::TEXT,
+    );
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                    },
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            HandoverTag,
+            ScreenTag,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    format!("Pass the device to {}", universe.players[*next].name),
+                    TextStyle {
+                        font: ui_handles.font_main.clone_weak(),
+                        font_size: 40.0,
+                        color: universe.owner_color(*next),
+                    },
+                ),
+                ..default()
+            });
+        })
+        .push_children(&[ready]);
+}
+
+pub fn button_system(
+    interaction_query: Query<
+        (&Interaction, &crate::ui_helper::button::ButtonId<Ready>),
+        Changed<Interaction>,
+    >,
+    mut turn_state: ResMut<State<TurnState>>,
+) {
+    for (interaction, _) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            let _ = turn_state.set(TurnState::Player);
+        }
+    }
+}
+
+pub fn tear_down(mut commands: Commands, query: Query<Entity, With<HandoverTag>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
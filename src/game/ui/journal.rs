@@ -0,0 +1,321 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    assets::UiAssets,
+    game::{
+        turns::{CurrentPlayer, MessageKind, Turns},
+        world::CameraControllerTarget,
+        Universe,
+    },
+    ui_helper::button::ButtonId,
+};
+
+use super::{ScreenTag, SelectedStar, DAMPENER};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JournalButtons {
+    Filter(MessageKind),
+    Close,
+}
+
+impl From<JournalButtons> for String {
+    fn from(button: JournalButtons) -> Self {
+        match button {
+            JournalButtons::Filter(kind) => kind.to_string(),
+            JournalButtons::Close => {
+                material_icons::icon_to_char(material_icons::Icon::Close).to_string()
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct JournalState {
+    pub open: bool,
+    // kinds of messages filtered out
+    hidden: Vec<MessageKind>,
+}
+
+#[derive(Component)]
+pub struct JournalPanel;
+
+#[derive(Component, Default)]
+pub struct JournalList {
+    position: f32,
+}
+
+#[derive(Component)]
+pub struct JournalEntryStar(usize);
+
+pub fn display_journal(
+    mut commands: Commands,
+    state: Res<JournalState>,
+    turns: Res<Turns>,
+    current: Res<CurrentPlayer>,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    panel: Query<Entity, With<JournalPanel>>,
+) {
+    if !(state.is_changed() || turns.is_changed() || current.is_changed()) {
+        return;
+    }
+    if let Ok(entity) = panel.get_single() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+
+    let mut filters = MessageKind::ALL
+        .iter()
+        .map(|kind| {
+            button.add(
+                &mut commands,
+                Val::Px(110.),
+                Val::Px(30.),
+                UiRect::all(Val::Px(2.0)),
+                ui_handles.font_sub.clone_weak(),
+                JournalButtons::Filter(*kind),
+                20.,
+                if state.hidden.contains(kind) {
+                    crate::ui_helper::ColorScheme::TEXT_DARK
+                } else {
+                    crate::ui_helper::ColorScheme::TEXT
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    filters.push(button.add(
+        &mut commands,
+        Val::Px(30.),
+        Val::Px(30.),
+        UiRect::all(Val::Px(2.0)),
+        ui_handles.font_material.clone_weak(),
+        JournalButtons::Close,
+        20.,
+        crate::ui_helper::ColorScheme::TEXT,
+    ));
+
+    let base = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(10.0)),
+                size: Size {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                },
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_shrink: 0.,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&filters);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        flex_grow: 1.0,
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    max_size: Size::UNDEFINED,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            JournalList::default(),
+                        ))
+                        .with_children(|parent| {
+                            let journal = &turns.journal[current.index];
+                            let mut last_turn = None;
+                            // most recent first
+                            for entry in journal
+                                .iter()
+                                .rev()
+                                .filter(|entry| !state.hidden.contains(&entry.message.kind()))
+                            {
+                                if last_turn != Some(entry.turn) {
+                                    last_turn = Some(entry.turn);
+                                    parent.spawn(TextBundle {
+                                        text: Text::from_section(
+                                            format!("Turn {}", entry.turn),
+                                            TextStyle {
+                                                font: ui_handles.font_main.clone_weak(),
+                                                font_size: 25.0,
+                                                color: crate::ui_helper::ColorScheme::TEXT,
+                                            },
+                                        ),
+                                        style: Style {
+                                            margin: UiRect {
+                                                top: Val::Px(10.0),
+                                                ..default()
+                                            },
+                                            flex_shrink: 0.,
+                                            ..default()
+                                        },
+                                        ..default()
+                                    });
+                                }
+                                let mut entity = parent.spawn(ButtonBundle {
+                                    background_color: BackgroundColor(Color::NONE),
+                                    style: Style {
+                                        margin: UiRect {
+                                            left: Val::Px(10.0),
+                                            bottom: Val::Px(5.0),
+                                            ..default()
+                                        },
+                                        flex_shrink: 0.,
+                                        ..default()
+                                    },
+                                    ..default()
+                                });
+                                if let Some(index) = entry.message.index() {
+                                    entity.insert(JournalEntryStar(index));
+                                }
+                                entity.with_children(|parent| {
+                                    parent.spawn(TextBundle {
+                                        text: Text::from_sections(
+                                            entry.message.as_sections(&ui_handles),
+                                        ),
+                                        ..default()
+                                    });
+                                });
+                            }
+                        });
+                });
+        })
+        .id();
+
+    let journal_panel = commands
+        .spawn(bevy_ninepatch::NinePatchBundle {
+            style: Style {
+                size: Size::new(Val::Px(520.0), Val::Percent(80.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            nine_patch_data: bevy_ninepatch::NinePatchData::with_single_content(
+                ui_handles.panel_handle.1.clone_weak(),
+                ui_handles.panel_handle.0.clone_weak(),
+                base,
+            ),
+            ..default()
+        })
+        .id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(DAMPENER),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            JournalPanel,
+            ScreenTag,
+        ))
+        .push_children(&[journal_panel]);
+}
+
+pub fn button_system(
+    interaction_query: Query<(&Interaction, &ButtonId<JournalButtons>), Changed<Interaction>>,
+    mut state: ResMut<JournalState>,
+    mut selected_star: ResMut<SelectedStar>,
+) {
+    for (interaction, button_id) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            match button_id.0 {
+                JournalButtons::Filter(kind) => {
+                    if let Some(position) = state.hidden.iter().position(|k| *k == kind) {
+                        state.hidden.remove(position);
+                    } else {
+                        state.hidden.push(kind);
+                    }
+                }
+                JournalButtons::Close => state.open = false,
+            }
+            selected_star.bypass_change_detection().ignore_next_click = true;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn entry_click(
+    interaction_query: Query<
+        (&Interaction, &JournalEntryStar),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut state: ResMut<JournalState>,
+    mut selected_star: ResMut<SelectedStar>,
+    universe: Res<Universe>,
+    mut controller_target: ResMut<CameraControllerTarget>,
+) {
+    for (interaction, entry) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            state.open = false;
+            selected_star.index = Some(entry.0);
+            selected_star.ignore_next_click = true;
+            controller_target.zoom_level = 8.0;
+            controller_target.position = universe.galaxy[entry.0].position;
+        }
+    }
+}
+
+pub fn scroll(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    state: Res<JournalState>,
+    mut query_list: Query<(&mut JournalList, &mut Style, &Children, &Parent)>,
+    query_item: Query<&Node>,
+) {
+    if !state.open {
+        return;
+    }
+    for mouse_wheel_event in mouse_wheel_events.iter() {
+        for (mut scrolling_list, mut style, children, parent) in &mut query_list {
+            let items_height: f32 = children
+                .iter()
+                .map(|entity| query_item.get(*entity).unwrap().size().y)
+                .sum();
+            let panel_height = query_item.get(parent.get()).unwrap().size().y;
+            let max_scroll = items_height - panel_height.max(0.);
+            if max_scroll > 0.0 {
+                let dy = match mouse_wheel_event.unit {
+                    MouseScrollUnit::Line => mouse_wheel_event.y * 20.,
+                    MouseScrollUnit::Pixel => mouse_wheel_event.y,
+                };
+                scrolling_list.position += dy;
+                scrolling_list.position = scrolling_list.position.clamp(-max_scroll, 0.);
+                style.position.top = Val::Px(scrolling_list.position);
+            }
+        }
+    }
+}
@@ -6,7 +6,12 @@ use bevy::{
 
 use crate::{
     assets::UiAssets,
-    game::{world::CameraControllerTarget, StarState, Universe},
+    game::{
+        turns::{CurrentPlayer, Turns},
+        victory::VictoryCondition,
+        world::CameraControllerTarget,
+        StarState, Universe,
+    },
 };
 
 use super::{shipyard, ScreenTag, SelectedStar, DAMPENER, LEFT_PANEL_WIDTH};
@@ -14,12 +19,21 @@ use super::{shipyard, ScreenTag, SelectedStar, DAMPENER, LEFT_PANEL_WIDTH};
 #[derive(Component)]
 pub struct PlayerStatsMarker;
 
+#[derive(Component)]
+pub struct VictoryProgressMarker;
+
 #[derive(Component, Default)]
 pub struct StarList {
     position: f32,
 }
 
-pub fn setup(commands: &mut Commands, ui_handles: &UiAssets) {
+pub fn setup(commands: &mut Commands, ui_handles: &UiAssets, universe: &Universe) {
+    // one line for each victory rule of the game
+    let rules = VictoryCondition::OPTIONAL
+        .iter()
+        .filter(|condition| universe.victory.is_enabled(**condition))
+        .count();
+
     let left_panel_top = {
         let base = commands
             .spawn(NodeBundle {
@@ -115,10 +129,31 @@ pub fn setup(commands: &mut Commands, ui_handles: &UiAssets) {
                     },
                     PlayerStatsMarker,
                 ));
+                parent.spawn((
+                    TextBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: ui_handles.font_sub.clone_weak(),
+                                font_size: 20.0,
+                                color: Color::GOLD,
+                            },
+                        ),
+                        style: Style {
+                            size: Size {
+                                width: Val::Undefined,
+                                height: Val::Px(20.0 * rules as f32),
+                            },
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    VictoryProgressMarker,
+                ));
             })
             .id();
 
-        let panel_height = 120.0;
+        let panel_height = 120.0 + 20.0 * rules as f32;
         let panel_style = Style {
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
@@ -233,24 +268,65 @@ pub fn setup(commands: &mut Commands, ui_handles: &UiAssets) {
 pub fn update_player_stats(
     mut text: Query<&mut Text, With<PlayerStatsMarker>>,
     universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
 ) {
-    if universe.is_changed() {
+    if universe.is_changed() || current.is_changed() {
+        let player = current.index;
         let mut text = text.single_mut();
-        text.sections[1].value = format!("{:.1}\n", universe.player_population(0));
-        let revenue = universe.player_revenue(0);
-        text.sections[3].value = format!("{:.1}\n", universe.player_revenue(0));
+        text.sections[1].value = format!("{:.1}\n", universe.player_population(player));
+        let revenue = universe.player_revenue(player);
+        text.sections[3].value = format!("{:.1}\n", universe.player_revenue(player));
         if revenue < 0.0 {
             text.sections[3].style.color = Color::rgb(0.64, 0.17, 0.17)
         } else {
             text.sections[3].style.color = Color::GREEN
         }
-        text.sections[5].value = format!("{:.1}\n", universe.players[0].savings);
-        if universe.players[0].savings < 0.0 {
+        text.sections[5].value = format!("{:.1}\n", universe.players[player].savings);
+        if universe.players[player].savings < 0.0 {
             text.sections[5].style.color = Color::rgb(0.64, 0.17, 0.17)
         } else {
             text.sections[5].style.color = Color::GREEN
         }
-        text.sections[7].value = format!("{:.1}\n", universe.players[0].resources);
+        text.sections[7].value = format!("{:.1}\n", universe.players[player].resources);
+    }
+}
+
+pub fn update_victory_progress(
+    mut text: Query<&mut Text, With<VictoryProgressMarker>>,
+    universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
+    turns: Res<Turns>,
+) {
+    if universe.is_changed() || current.is_changed() || turns.is_changed() {
+        let player = current.index;
+        let rules = &universe.victory;
+        let mut progress = String::new();
+        if let Some(limit) = rules.turn_limit {
+            progress.push_str(&format!("Turn {} / {}\n", turns.count, limit));
+        }
+        if let Some(needed) = universe.domination_target() {
+            progress.push_str(&format!(
+                "Stars {} / {}\n",
+                universe.owned_stars(player),
+                needed
+            ));
+        }
+        if let Some(target) = rules.economic {
+            progress.push_str(&format!(
+                "Goal {:.0} / {:.0}\n",
+                universe.players[player].savings.max(0.0),
+                target
+            ));
+        }
+        // players on another computer only know the special stars they have seen
+        if let Some(count) = rules.special_stars {
+            progress.push_str(&format!(
+                "Special {} / {}\n",
+                universe.held_special_stars(player),
+                count
+            ));
+        }
+        text.single_mut().sections[0].value = progress;
     }
 }
 
@@ -315,19 +391,20 @@ pub fn display_star_list(
     universe: Res<Universe>,
     ui_container: Query<Entity, With<StarList>>,
     ui_assets: Res<UiAssets>,
+    current: Res<CurrentPlayer>,
 ) {
     let Ok(ui_container) = ui_container.get_single() else {
         return;
     };
 
-    if universe.is_changed() {
+    if universe.is_changed() || current.is_changed() {
         commands.entity(ui_container).despawn_descendants();
         commands.entity(ui_container).with_children(|parent| {
-            for (star, _) in universe.players[0]
+            for (star, _) in universe.players[current.index]
                 .vision
                 .iter()
                 .enumerate()
-                .filter(|(_, state)| **state == StarState::Owned(0))
+                .filter(|(_, state)| **state == StarState::Owned(current.index))
             {
                 parent
                     .spawn((
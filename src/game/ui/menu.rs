@@ -46,6 +46,47 @@ pub fn setup(
         25.,
         crate::ui_helper::ColorScheme::TEXT,
     );
+    #[cfg(not(target_arch = "wasm32"))]
+    let save_button = button.add(
+        commands,
+        Val::Px(100.),
+        Val::Px(40.),
+        UiRect::all(Val::Auto),
+        ui_handles.font_sub.clone_weak(),
+        UiButtons::SaveGame,
+        25.,
+        crate::ui_helper::ColorScheme::TEXT,
+    );
+    let journal_button = button.add(
+        commands,
+        Val::Px(100.),
+        Val::Px(40.),
+        UiRect::all(Val::Auto),
+        ui_handles.font_sub.clone_weak(),
+        UiButtons::Journal,
+        25.,
+        crate::ui_helper::ColorScheme::TEXT,
+    );
+    let stats_button = button.add(
+        commands,
+        Val::Px(100.),
+        Val::Px(40.),
+        UiRect::all(Val::Auto),
+        ui_handles.font_sub.clone_weak(),
+        UiButtons::Stats,
+        25.,
+        crate::ui_helper::ColorScheme::TEXT,
+    );
+    let research_button = button.add(
+        commands,
+        Val::Px(100.),
+        Val::Px(40.),
+        UiRect::all(Val::Auto),
+        ui_handles.font_sub.clone_weak(),
+        UiButtons::Research,
+        25.,
+        crate::ui_helper::ColorScheme::TEXT,
+    );
     let back_to_menu_button = button.add(
         commands,
         Val::Px(100.),
@@ -68,7 +109,7 @@ pub fn setup(
                     },
                     size: Size {
                         width: Val::Px(100.0),
-                        height: Val::Px(150.0),
+                        height: Val::Px(250.0),
                     },
                     flex_direction: FlexDirection::Column,
                     justify_content: JustifyContent::SpaceAround,
@@ -106,6 +147,13 @@ pub fn setup(
                     },
                     MenuContainer,
                 ))
-                .push_children(&[back_to_menu_button]);
+                .push_children(&[
+                    #[cfg(not(target_arch = "wasm32"))]
+                    save_button,
+                    journal_button,
+                    stats_button,
+                    research_button,
+                    back_to_menu_button,
+                ]);
         });
 }
@@ -1,6 +1,6 @@
 use std::{f32::consts::PI, time::Duration};
 
-use bevy::{input::mouse::MouseMotion, math::Vec3Swizzles, prelude::*};
+use bevy::{input::mouse::MouseMotion, math::Vec3Swizzles, prelude::*, utils::HashMap};
 use bevy_easings::{Ease, EaseFunction, EasingType};
 use bevy_prototype_lyon::{
     prelude::{DrawMode, GeometryBuilder, PathBuilder, StrokeMode},
@@ -13,19 +13,29 @@ use crate::{
     GameState,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::save::{SaveEvent, SaveSlot};
 use super::{
-    fleet::{turns_between, FleetSize, Order, Owner, Ship, ShipKind},
+    fleet::{FleetSize, Order, Owner, Regroup, Ship},
     galaxy::StarSize,
-    turns::{Message, TurnState, Turns},
+    production::PendingQueueChanges,
+    turns::{CurrentPlayer, Message, TurnState, Turns},
     world::{CameraController, CameraControllerTarget, RATIO_ZOOM_DISTANCE},
     z_levels, StarState, Universe,
 };
 
+mod handover;
+mod journal;
 mod left_panel;
 mod menu;
+mod research;
 mod shipyard;
+mod stats;
+mod transit;
 mod turn;
 
+pub use stats::StatsState;
+
 pub const LEFT_PANEL_WIDTH: f32 = 200.0;
 
 const DAMPENER: Color = Color::rgba(0.15, 0.15, 0.15, 0.75);
@@ -37,6 +47,11 @@ impl bevy::app::Plugin for Plugin {
         app.init_resource::<SelectedStar>()
             .init_resource::<turn::DisplayedMessage>()
             .init_resource::<shipyard::ShipyadForStar>()
+            .init_resource::<PendingQueueChanges>()
+            .init_resource::<journal::JournalState>()
+            .init_resource::<stats::StatsState>()
+            .init_resource::<research::ResearchState>()
+            .init_resource::<transit::TransitState>()
             .add_event::<shipyard::ShipyardEvent>()
             .add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup))
             .add_system_set(
@@ -51,12 +66,38 @@ impl bevy::app::Plugin for Plugin {
                     .with_system(rotate_mark)
                     .with_system(dragging_ship.after(display_star_selected))
                     .with_system(left_panel::update_player_stats)
+                    .with_system(left_panel::update_victory_progress)
                     .with_system(turn::display_messages)
                     .with_system(shipyard::display_shipyard)
                     .with_system(shipyard::button_system)
+                    .with_system(shipyard::display_queue)
+                    .with_system(journal::display_journal)
+                    .with_system(journal::button_system)
+                    .with_system(journal::entry_click)
+                    .with_system(journal::scroll)
+                    .with_system(stats::display_stats)
+                    .with_system(stats::button_system)
+                    .with_system(research::display_research)
+                    .with_system(research::button_system)
+                    .with_system(transit::display_transit)
+                    .with_system(transit::button_system)
                     .with_system(make_it_visible),
             )
-            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(tear_down));
+            .add_system_set(SystemSet::on_enter(TurnState::Handover).with_system(handover::setup))
+            .add_system_set(
+                SystemSet::on_update(TurnState::Handover).with_system(handover::button_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(TurnState::Handover).with_system(handover::tear_down),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(tear_down))
+            // the charts of every player can be looked at once the game is over
+            .add_system_set(
+                SystemSet::on_update(GameState::Lost)
+                    .with_system(stats::display_stats)
+                    .with_system(stats::button_system),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Lost).with_system(tear_down));
     }
 }
 
@@ -73,6 +114,11 @@ pub enum UiButtons {
     ZoomIn,
     ZoomOut,
     GameMenu,
+    #[cfg(not(target_arch = "wasm32"))]
+    SaveGame,
+    Journal,
+    Stats,
+    Research,
     BackToMenu,
     EndTurn,
     NextMessage,
@@ -94,6 +140,11 @@ impl From<UiButtons> for String {
             UiButtons::GameMenu => {
                 material_icons::icon_to_char(material_icons::Icon::Settings).to_string()
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            UiButtons::SaveGame => "Save".to_string(),
+            UiButtons::Journal => "Journal".to_string(),
+            UiButtons::Stats => "Stats".to_string(),
+            UiButtons::Research => "Research".to_string(),
             UiButtons::BackToMenu => "Menu".to_string(),
             UiButtons::EndTurn => {
                 material_icons::icon_to_char(material_icons::Icon::FastForward).to_string()
@@ -112,14 +163,20 @@ fn setup(
     mut commands: Commands,
     ui_handles: Res<UiAssets>,
     buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    universe: Res<Universe>,
 ) {
     info!("loading UI");
 
+    commands.insert_resource(journal::JournalState::default());
+    commands.insert_resource(stats::StatsState::default());
+    commands.insert_resource(research::ResearchState::default());
+    commands.insert_resource(transit::TransitState::default());
+
     menu::setup(&mut commands, &ui_handles, &buttons);
 
     turn::setup(&mut commands, &ui_handles, &buttons);
 
-    left_panel::setup(&mut commands, &ui_handles);
+    left_panel::setup(&mut commands, &ui_handles, &universe);
 
     // star panel
     {
@@ -297,6 +354,8 @@ pub struct SelectedStar {
     ignore_next_click: bool,
     index: Option<usize>,
     dragging_ship: (Option<Entity>, Option<Entity>),
+    // ships picked to be dragged or split away from a fleet, all of them when absent
+    detached: HashMap<Entity, u32>,
 }
 
 fn button_system(
@@ -310,6 +369,12 @@ fn button_system(
     mut selected_star: ResMut<SelectedStar>,
     mut shipyard: EventWriter<shipyard::ShipyardEvent>,
     turns: Res<Turns>,
+    current: Res<CurrentPlayer>,
+    universe: Res<Universe>,
+    mut journal: ResMut<journal::JournalState>,
+    mut stats: ResMut<stats::StatsState>,
+    mut research: ResMut<research::ResearchState>,
+    #[cfg(not(target_arch = "wasm32"))] mut save: EventWriter<SaveEvent>,
 ) {
     for (interaction, button_id, changed) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
@@ -325,18 +390,47 @@ fn button_system(
                 (UiButtons::GameMenu, true) => {
                     menu_container.single_mut().toggle();
                 }
+                #[cfg(not(target_arch = "wasm32"))]
+                (UiButtons::SaveGame, true) => {
+                    save.send(SaveEvent(SaveSlot::Manual));
+                    menu_container.single_mut().is_visible = false;
+                }
+                (UiButtons::Journal, true) => {
+                    journal.open = !journal.open;
+                    menu_container.single_mut().is_visible = false;
+                    selected_star.bypass_change_detection().ignore_next_click = true;
+                }
+                (UiButtons::Stats, true) => {
+                    stats.open = !stats.open;
+                    menu_container.single_mut().is_visible = false;
+                    selected_star.bypass_change_detection().ignore_next_click = true;
+                }
+                (UiButtons::Research, true) => {
+                    research.open = !research.open;
+                    menu_container.single_mut().is_visible = false;
+                    selected_star.bypass_change_detection().ignore_next_click = true;
+                }
                 (UiButtons::BackToMenu, true) => state.set(GameState::Menu).unwrap(),
-                (UiButtons::EndTurn, true) => {
-                    if displayed_message.0 >= turns.messages.len() - 1 {
-                        turn_state.set(TurnState::Bots).unwrap();
-                        shipyard.send(shipyard::ShipyardEvent::Close);
-                    }
+                (UiButtons::EndTurn, true)
+                    if displayed_message.0 >= turns.messages.len() - 1
+                        && *turn_state.current() == TurnState::Player =>
+                {
+                    current.end_turn(&mut turn_state);
+                    shipyard.send(shipyard::ShipyardEvent::Close);
                 }
                 (UiButtons::NextMessage, true) | (UiButtons::LastMessage, true) => {
-                    if matches!(turns.messages[displayed_message.0], Message::Win)
-                        || matches!(turns.messages[displayed_message.0], Message::Lose { .. })
-                    {
-                        let _ = state.set(GameState::Menu);
+                    match turns.messages[displayed_message.0] {
+                        Message::Win | Message::Victory { .. } | Message::RivalVictory { .. } => {
+                            let _ = state.set(GameState::Lost);
+                        }
+                        Message::Lose { .. } if current.game_over(&universe) => {
+                            let _ = state.set(GameState::Lost);
+                        }
+                        Message::Lose { .. } => {
+                            // the other humans keep playing without this one
+                            current.end_turn(&mut turn_state);
+                        }
+                        _ => (),
                     }
                     displayed_message.0 += 1;
                     selected_star.bypass_change_detection().ignore_next_click = true;
@@ -351,6 +445,7 @@ fn button_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn select_star(
     mouse_input: Res<Input<MouseButton>>,
     touches: Res<Touches>,
@@ -362,6 +457,12 @@ fn select_star(
     mut last_pressed: Local<f32>,
     mut pressed_at: Local<Option<Vec2>>,
     time: Res<Time>,
+    (fleets, sprites): (
+        Query<(Entity, &Order, &Owner, &Children)>,
+        Query<&GlobalTransform>,
+    ),
+    current: Res<CurrentPlayer>,
+    mut transit: ResMut<transit::TransitState>,
 ) {
     if mouse_input.just_pressed(MouseButton::Left) {
         *last_pressed = time.elapsed_seconds();
@@ -393,6 +494,22 @@ fn select_star(
                 .unwrap()
                 .origin
                 .xy();
+            // fleets in flight are picked before the stars they pass by
+            if let Some((entity, ..)) = fleets.iter().find(|(_, order, owner, children)| {
+                owner.0 == current.index
+                    && order.in_flight()
+                    && sprites.get(children[0]).is_ok_and(|sprite| {
+                        sprite.translation().xy().distance(clicked)
+                            < 5.0 * controller.zoom_level.powf(0.7)
+                    })
+            }) {
+                transit.fleet = Some(entity);
+                selected_star.index = None;
+                return;
+            }
+            if transit.fleet.is_some() {
+                transit.fleet = None;
+            }
             if let Some((index, _)) = universe.galaxy.iter().enumerate().find(|(_, star)| {
                 (star.position * controller.zoom_level / RATIO_ZOOM_DISTANCE).distance(clicked)
                     < <StarSize as Into<f32>>::into(star.size)
@@ -401,9 +518,11 @@ fn select_star(
             }) {
                 if selected_star.index != Some(index) {
                     selected_star.index = Some(index);
+                    selected_star.detached.clear();
                 }
             } else {
                 selected_star.index = None;
+                selected_star.detached.clear();
             }
         }
     }
@@ -417,12 +536,28 @@ struct MarkedStar;
 enum StarAction {
     Ship(Entity),
     Shipyard(usize),
+    Fewer(Entity),
+    More(Entity),
+    Split(Entity),
+    Merge(Entity),
 }
 
 impl From<StarAction> for String {
     fn from(action: StarAction) -> Self {
         match action {
             StarAction::Ship(_) => "".to_string(),
+            StarAction::Fewer(_) => {
+                material_icons::icon_to_char(material_icons::Icon::Remove).to_string()
+            }
+            StarAction::More(_) => {
+                material_icons::icon_to_char(material_icons::Icon::Add).to_string()
+            }
+            StarAction::Split(_) => {
+                material_icons::icon_to_char(material_icons::Icon::CallSplit).to_string()
+            }
+            StarAction::Merge(_) => {
+                material_icons::icon_to_char(material_icons::Icon::CallMerge).to_string()
+            }
             StarAction::Shipyard(_) => {
                 material_icons::icon_to_char(material_icons::Icon::RocketLaunch).to_string()
             }
@@ -435,21 +570,85 @@ fn star_button_system(
     mut target: ResMut<CameraControllerTarget>,
     mut selected_star: ResMut<SelectedStar>,
     mut shipyard: EventWriter<shipyard::ShipyardEvent>,
+    transit: Res<transit::TransitState>,
+    mut regroup: EventWriter<Regroup>,
+    fleets: Query<(Entity, &Ship, &Order, &FleetSize, &Owner)>,
 ) {
     for (interaction, button_id, changed) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
             match (&button_id.0, changed) {
                 (StarAction::Ship(entity), true) => {
+                    if fleets
+                        .get(*entity)
+                        .is_ok_and(|(_, ship, ..)| ship.kind.is_stationary())
+                    {
+                        continue;
+                    }
                     target.ignore_movement = true;
                     selected_star.dragging_ship.0 = Some(*entity);
                 }
                 (StarAction::Shipyard(index), true) => {
                     shipyard.send(shipyard::ShipyardEvent::OpenForStar(*index));
                 }
+                (StarAction::Fewer(entity), true) | (StarAction::More(entity), true) => {
+                    let Ok((_, _, _, size, _)) = fleets.get(*entity) else {
+                        continue;
+                    };
+                    let detached = selected_star.detached.get(entity).copied();
+                    let detached = match button_id.0 {
+                        StarAction::Fewer(_) => detached.unwrap_or(size.0).saturating_sub(1),
+                        _ => detached.unwrap_or(size.0) + 1,
+                    };
+                    if detached >= size.0 {
+                        selected_star.detached.remove(entity);
+                    } else {
+                        selected_star.detached.insert(*entity, detached.max(1));
+                    }
+                    selected_star.ignore_next_click = true;
+                }
+                (StarAction::Split(entity), true) => {
+                    let (Ok((_, _, _, size, _)), Some(star)) =
+                        (fleets.get(*entity), selected_star.index)
+                    else {
+                        continue;
+                    };
+                    // half of the fleet when no number of ships was picked
+                    let detached = selected_star.detached.remove(entity).unwrap_or(size.0 / 2);
+                    regroup.send(Regroup::Split {
+                        fleet: *entity,
+                        size: detached,
+                        order: Order::Orbit(star),
+                    });
+                    selected_star.ignore_next_click = true;
+                }
+                (StarAction::Merge(entity), true) => {
+                    let Ok((_, ship, order, _, owner)) = fleets.get(*entity) else {
+                        continue;
+                    };
+                    for (other, other_ship, other_order, _, other_owner) in &fleets {
+                        if other != *entity
+                            && order.waiting_at().is_some()
+                            && other_ship.kind == ship.kind
+                            && other_owner.0 == owner.0
+                            && other_order.waiting_at() == order.waiting_at()
+                        {
+                            selected_star.detached.remove(&other);
+                            regroup.send(Regroup::Merge {
+                                fleet: other,
+                                into: *entity,
+                            });
+                        }
+                    }
+                    selected_star.detached.remove(entity);
+                    selected_star.ignore_next_click = true;
+                }
                 _ => (),
             }
         }
-        if *interaction == Interaction::None && changed && selected_star.index.is_some() {
+        if *interaction == Interaction::None
+            && changed
+            && (selected_star.index.is_some() || transit.fleet.is_some())
+        {
             target.ignore_movement = false;
         }
     }
@@ -460,7 +659,7 @@ fn display_star_selected(
     mut commands: Commands,
     selected_star: Res<SelectedStar>,
     marked: Query<Entity, With<MarkedStar>>,
-    universe: Res<Universe>,
+    (universe, current): (Res<Universe>, Res<CurrentPlayer>),
     mut star_panel: Query<(&mut Style, &mut BackgroundColor), With<StarPanel>>,
     star_details: Query<Entity, With<StarDetails>>,
     mut fleets_panel: Query<
@@ -527,7 +726,7 @@ fn display_star_selected(
 
     if let Some(index) = selected_star.index {
         let star = &universe.galaxy[index];
-        let good = &universe.galaxy[universe.players[0].start];
+        let good = &universe.galaxy[universe.players[current.index].start];
         if selected_star.is_changed() {
             commands
                 .entity(universe.star_entities[index])
@@ -566,7 +765,12 @@ fn display_star_selected(
                             TextStyle {
                                 font: ui_assets.font_main.clone_weak(),
                                 font_size: 20.0,
-                                color: Color::WHITE,
+                                // special stars are a victory condition
+                                color: if universe.is_special_star(index) {
+                                    Color::GOLD
+                                } else {
+                                    Color::WHITE
+                                },
                             },
                         ),
                         style: Style {
@@ -579,8 +783,8 @@ fn display_star_selected(
                         },
                         ..default()
                     });
-                    match universe.players[0].vision[index] {
-                        StarState::Owned(0) => {
+                    match universe.players[current.index].vision[index] {
+                        StarState::Owned(owner) if owner == current.index => {
                             let star_revenue = universe.star_revenue(index);
                             parent.spawn(TextBundle {
                                 text: Text::from_sections([
@@ -711,14 +915,7 @@ fn display_star_selected(
                 let fleets = fleets
                     .iter()
                     .filter(|(_, _, order, _, owner)| {
-                        if owner.0 == 0 {
-                            match order {
-                                Order::Orbit(around) => *around == index,
-                                Order::Move { from, to: _, step } => *from == index && *step == 0,
-                            }
-                        } else {
-                            false
-                        }
+                        owner.0 == current.index && order.waiting_at() == Some(index)
                     })
                     .collect::<Vec<_>>();
                 if !fleets.is_empty() {
@@ -738,14 +935,7 @@ fn display_star_selected(
 
                                     parent.spawn((
                                         ImageBundle {
-                                            image: UiImage(match ship.kind {
-                                                ShipKind::Colony => {
-                                                    ship_assets.colony_ship.clone_weak()
-                                                }
-                                                ShipKind::Fighter => {
-                                                    ship_assets.fighter.clone_weak()
-                                                }
-                                            }),
+                                            image: UiImage(ship_assets.ship(ship.kind)),
                                             style: Style {
                                                 size: Size::new(Val::Px(15.0), Val::Px(15.0)),
                                                 ..default()
@@ -802,7 +992,16 @@ fn display_star_selected(
                                                     },
                                                 },
                                                 TextSection {
-                                                    value: format!(" {} {}\n", fleet_size, ship),
+                                                    value: match selected_star.detached.get(entity)
+                                                    {
+                                                        Some(detached) => format!(
+                                                            " {}/{} {}\n",
+                                                            detached, fleet_size, ship
+                                                        ),
+                                                        None => {
+                                                            format!(" {} {}\n", fleet_size, ship)
+                                                        }
+                                                    },
                                                     style: TextStyle {
                                                         font: ui_assets.font_sub.clone_weak(),
                                                         font_size: 20.0,
@@ -823,6 +1022,41 @@ fn display_star_selected(
                                         Interaction::None,
                                         ButtonId(StarAction::Ship(*entity)),
                                     ));
+
+                                    let mut actions = vec![];
+                                    if fleet_size.0 > 1 {
+                                        actions.extend([
+                                            StarAction::Fewer(*entity),
+                                            StarAction::More(*entity),
+                                            StarAction::Split(*entity),
+                                        ]);
+                                    }
+                                    if fleets.iter().any(|(other, other_ship, ..)| {
+                                        other != entity && other_ship.kind == ship.kind
+                                    }) {
+                                        actions.push(StarAction::Merge(*entity));
+                                    }
+                                    for action in actions {
+                                        parent.spawn((
+                                            TextBundle {
+                                                text: Text::from_section(
+                                                    action,
+                                                    TextStyle {
+                                                        font: ui_assets.font_material.clone_weak(),
+                                                        font_size: 15.0,
+                                                        color: Color::WHITE,
+                                                    },
+                                                ),
+                                                style: Style {
+                                                    margin: UiRect::left(Val::Px(5.0)),
+                                                    ..default()
+                                                },
+                                                ..default()
+                                            },
+                                            Interaction::None,
+                                            ButtonId(action),
+                                        ));
+                                    }
                                 });
                         }
                     });
@@ -856,26 +1090,19 @@ fn display_star_selected(
             }
             {
                 let has_fleets = fleets.iter().any(|(_, _, order, _, owner)| {
-                    if owner.0 == 0 {
-                        match order {
-                            Order::Orbit(around) => *around == index,
-                            Order::Move { from, to: _, step } => *from == index && *step == 0,
-                        }
-                    } else {
-                        false
-                    }
+                    owner.0 == current.index && order.waiting_at() == Some(index)
                 });
                 if has_fleets {
                     let (mut style, mut background_color) = fleets_panel.single_mut();
                     background_color.0 = DAMPENER;
                     style.display = Display::Flex;
-                    style.size = Size::new(Val::Px(200.0), Val::Px(120.0));
+                    style.size = Size::new(Val::Px(260.0), Val::Px(120.0));
                     style.position.left = Val::Px(
                         pos.x
                             - <StarSize as Into<f32>>::into(star.size)
                                 * 5.0
                                 * camera_controller.zoom_level.powf(0.7)
-                            - 200.0,
+                            - 260.0,
                     );
                     let Val::Px(height) = style.size.height else{
                         return;
@@ -883,7 +1110,7 @@ fn display_star_selected(
                     style.position.bottom = Val::Px(pos.y - height / 2.0);
                 }
             }
-            if universe.star_details[index].owner == 0 {
+            if universe.star_details[index].owner == current.index {
                 let mut style = shipyard_button.single_mut().0;
                 style.display = Display::Flex;
                 style.size = Size::new(Val::Px(110.0), Val::Px(40.0));
@@ -928,18 +1155,35 @@ fn dragging_ship(
     mut mouse_motion: EventReader<MouseMotion>,
     universe: Res<Universe>,
     controller: Res<CameraController>,
-    ship_assets: Res<ShipAssets>,
-    ui_assets: Res<UiAssets>,
+    (ship_assets, ui_assets): (Res<ShipAssets>, Res<UiAssets>),
     camera: Query<(&Camera, &GlobalTransform)>,
     windows: Res<Windows>,
     mut transform: Query<&mut Transform>,
     time: Res<Time>,
-    fleets: Query<&Ship>,
-    mut over_star: Local<Option<(usize, [Entity; 2], Entity, usize)>>,
+    fleets: Query<(&Ship, &Order, &Owner)>,
+    mut regroup: EventWriter<Regroup>,
+    keyboard_input: Res<Input<KeyCode>>,
+    // the order to extend, or to turn around when the fleet is in flight, and the fleet speed
+    mut route: Local<Option<(Order, bool, f32)>>,
+    mut over_star: Local<Option<(usize, [Entity; 2], Entity)>>,
 ) {
     if selected_star.is_changed() {
         if let (Some(fleet_entity), None) = selected_star.dragging_ship {
-            let fleet = fleets.get(fleet_entity).unwrap();
+            let (fleet, order, owner) = fleets.get(fleet_entity).unwrap();
+            let speed = universe.speed(owner.0, fleet.kind);
+            // with shift held, a leg is added to the route given this turn instead of replacing it
+            let extend = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+            *route = Some(if order.in_flight() {
+                (order.clone(), !extend, speed)
+            } else {
+                let star = selected_star.index.unwrap();
+                match order {
+                    Order::Move { from, .. } if *from == star && extend => {
+                        (order.clone(), false, speed)
+                    }
+                    _ => (Order::Orbit(star), false, speed),
+                }
+            });
             let position = windows
                 .primary()
                 .cursor_position()
@@ -953,10 +1197,7 @@ fn dragging_ship(
                 .extend(z_levels::SHIP_DRAGGING);
             let ship_entity = commands
                 .spawn(SpriteBundle {
-                    texture: match fleet.kind {
-                        ShipKind::Colony => ship_assets.colony_ship.clone_weak(),
-                        ShipKind::Fighter => ship_assets.fighter.clone_weak(),
-                    },
+                    texture: ship_assets.ship(fleet.kind),
                     transform: Transform::from_translation(position)
                         .with_scale(Vec3::splat(0.2))
                         .with_rotation(Quat::from_rotation_z(PI)),
@@ -968,7 +1209,12 @@ fn dragging_ship(
     }
     if selected_star.dragging_ship.0.is_some() {
         if mouse_input.just_released(MouseButton::Left) {
-            if over_star.is_none() {
+            if over_star.is_none()
+                && matches!(*route, Some((Order::Orbit(_), ..)))
+                && !selected_star
+                    .detached
+                    .contains_key(&selected_star.dragging_ship.0.unwrap())
+            {
                 // reset order to orbiting
                 commands
                     .entity(selected_star.dragging_ship.0.unwrap())
@@ -995,7 +1241,10 @@ fn dragging_ship(
                         .extend(z_levels::SHIP_DRAGGING);
                 }
                 let hover = transform.translation.xy();
-                let coming_from = selected_star.index.unwrap();
+                let Some(base) = route.as_ref() else {
+                    return;
+                };
+                let coming_from = base.0.last_stop();
                 if let Some((index, _)) = universe
                     .galaxy
                     .iter()
@@ -1011,9 +1260,14 @@ fn dragging_ship(
                 {
                     if over_star.is_none() {
                         let mut path_builder = PathBuilder::new();
-                        let from = universe.galaxy[coming_from].position;
+                        let from = if base.1 {
+                            base.0.position(&universe.galaxy, base.2)
+                        } else {
+                            universe.galaxy[coming_from].position
+                        };
                         let to = universe.galaxy[index].position;
-                        let turns = turns_between(from, to);
+                        let planned = planned_order(base, index, &universe);
+                        let turns = planned.eta(&universe.galaxy, base.2);
                         let length = commands
                             .spawn(Text2dBundle {
                                 text: Text::from_section(
@@ -1032,8 +1286,16 @@ fn dragging_ship(
                                 ..default()
                             })
                             .id();
-                        path_builder.move_to(from * controller.zoom_level / RATIO_ZOOM_DISTANCE);
-                        path_builder.line_to(to * controller.zoom_level / RATIO_ZOOM_DISTANCE);
+                        path_builder.move_to(
+                            planned.departure(&universe.galaxy) * controller.zoom_level
+                                / RATIO_ZOOM_DISTANCE,
+                        );
+                        for stop in planned.route().into_iter().skip(1) {
+                            path_builder.line_to(
+                                universe.galaxy[stop].position * controller.zoom_level
+                                    / RATIO_ZOOM_DISTANCE,
+                            );
+                        }
                         let line = path_builder.build();
                         let path = commands
                             .spawn(GeometryBuilder::build_as(
@@ -1051,26 +1313,46 @@ fn dragging_ship(
                             index,
                             [path, length],
                             selected_star.dragging_ship.0.unwrap(),
-                            coming_from,
                         ));
                     }
-                } else if let Some((_, entities, _, _)) = *over_star {
+                } else if let Some((_, entities, _)) = *over_star {
                     commands.entity(entities[0]).despawn_recursive();
                     commands.entity(entities[1]).despawn_recursive();
                     *over_star = None;
                 }
             }
         }
-    } else if let Some((index, entities, fleet_entity, from_star)) = *over_star {
+    } else if let Some((index, entities, fleet_entity)) = *over_star {
         commands.entity(entities[0]).despawn_recursive();
         commands.entity(entities[1]).despawn_recursive();
         *over_star = None;
-        commands.entity(fleet_entity).insert(Order::Move {
-            from: from_star,
-            to: index,
-            step: 0,
-        });
+        if let Some(route) = route.take() {
+            let order = planned_order(&route, index, &universe);
+            match selected_star.detached.remove(&fleet_entity) {
+                // only the picked ships leave, as a new fleet
+                Some(size) if !route.0.in_flight() => regroup.send(Regroup::Split {
+                    fleet: fleet_entity,
+                    size,
+                    order,
+                }),
+                _ => {
+                    commands.entity(fleet_entity).insert(order);
+                }
+            }
+        }
         selected_star.set_changed();
     }
     mouse_motion.clear();
 }
+
+fn planned_order(
+    (base, redirect, speed): &(Order, bool, f32),
+    star: usize,
+    universe: &Universe,
+) -> Order {
+    if *redirect {
+        base.redirect(star, &universe.galaxy, *speed)
+    } else {
+        base.clone().then(star)
+    }
+}
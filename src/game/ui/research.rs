@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::UiAssets,
+    game::{research::Tech, turns::CurrentPlayer, Universe},
+    ui_helper::{button::ButtonId, ColorScheme},
+};
+
+use super::{ScreenTag, SelectedStar, LEFT_PANEL_WIDTH};
+
+// credits per turn that can be put in research
+const FUNDING: [u32; 5] = [0, 2, 5, 10, 20];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ResearchButtons {
+    Tech(Tech),
+    Funding(u32),
+    Close,
+}
+
+impl From<ResearchButtons> for String {
+    fn from(button: ResearchButtons) -> Self {
+        match button {
+            ResearchButtons::Tech(tech) => tech.to_string(),
+            ResearchButtons::Funding(credits) => format!("{}", credits),
+            ResearchButtons::Close => {
+                material_icons::icon_to_char(material_icons::Icon::Close).to_string()
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ResearchState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct ResearchPanel;
+
+pub fn display_research(
+    mut commands: Commands,
+    state: Res<ResearchState>,
+    universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    displayed: Query<Entity, With<ResearchPanel>>,
+) {
+    if !(state.is_changed() || universe.is_changed() || current.is_changed()) {
+        return;
+    }
+    for entity in &displayed {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+    let player = current.index;
+    let research = universe.research(player);
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+
+    let mut funding_buttons = FUNDING
+        .iter()
+        .map(|credits| {
+            button.add(
+                &mut commands,
+                Val::Px(50.),
+                Val::Px(30.),
+                UiRect::all(Val::Px(2.0)),
+                ui_handles.font_sub.clone_weak(),
+                ResearchButtons::Funding(*credits),
+                20.,
+                if research.funding == *credits as f32 {
+                    ColorScheme::TEXT
+                } else {
+                    ColorScheme::TEXT_DARK
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    funding_buttons.push(button.add(
+        &mut commands,
+        Val::Px(30.),
+        Val::Px(30.),
+        UiRect::all(Val::Px(2.0)),
+        ui_handles.font_material.clone_weak(),
+        ResearchButtons::Close,
+        20.,
+        ColorScheme::TEXT,
+    ));
+    let tech_buttons = Tech::ALL
+        .iter()
+        .map(|tech| {
+            button.add(
+                &mut commands,
+                Val::Px(150.),
+                Val::Px(30.),
+                UiRect::all(Val::Px(2.0)),
+                ui_handles.font_sub.clone_weak(),
+                ResearchButtons::Tech(*tech),
+                20.,
+                if research.current == Some(*tech) {
+                    ColorScheme::TEXT_HIGHLIGHT
+                } else if universe.can_research(player, *tech) {
+                    ColorScheme::TEXT
+                } else {
+                    ColorScheme::TEXT_DARK
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let text_style = TextStyle {
+        font: ui_handles.font_sub.clone_weak(),
+        font_size: 20.0,
+        color: ColorScheme::TEXT,
+    };
+    let base = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "{:.1} research points per turn\nCredits invested each turn:",
+                    universe.research_rate(player)
+                ),
+                text_style.clone(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&funding_buttons);
+            for (tech, tech_button) in Tech::ALL.iter().zip(tech_buttons) {
+                let status = if universe.knows(player, *tech) {
+                    "researched".to_string()
+                } else if research.current == Some(*tech) {
+                    format!("{:.0}/{:.0} points", research.progress, tech.cost())
+                } else if let Some(required) = tech
+                    .requires()
+                    .filter(|required| !universe.knows(player, *required))
+                {
+                    format!("needs {}", required)
+                } else {
+                    format!("{:.0} points", tech.cost())
+                };
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::vertical(Val::Px(2.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .push_children(&[tech_button])
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!("{} ({})", tech.description().replace('\n', " "), status),
+                                TextStyle {
+                                    color: if universe.knows(player, *tech) {
+                                        ColorScheme::TEXT_DARK
+                                    } else {
+                                        ColorScheme::TEXT
+                                    },
+                                    ..text_style.clone()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::left(Val::Px(10.0)),
+                                ..default()
+                            }),
+                        );
+                    });
+            }
+        })
+        .id();
+
+    let panel = commands
+        .spawn(bevy_ninepatch::NinePatchBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            nine_patch_data: bevy_ninepatch::NinePatchData::with_single_content(
+                ui_handles.panel_handle.1.clone_weak(),
+                ui_handles.panel_handle.0.clone_weak(),
+                base,
+            ),
+            ..default()
+        })
+        .id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(LEFT_PANEL_WIDTH + 20.0),
+                        top: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            ResearchPanel,
+            ScreenTag,
+        ))
+        .push_children(&[panel]);
+}
+
+pub fn button_system(
+    interaction_query: Query<(&Interaction, &ButtonId<ResearchButtons>), Changed<Interaction>>,
+    mut state: ResMut<ResearchState>,
+    mut universe: ResMut<Universe>,
+    current: Res<CurrentPlayer>,
+    mut selected_star: ResMut<SelectedStar>,
+) {
+    for (interaction, button_id) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            // techs that can't be researched yet are shown but do nothing
+            let _ = match button_id.0 {
+                ResearchButtons::Tech(tech) => universe.choose_research(current.index, tech),
+                ResearchButtons::Funding(credits) => {
+                    universe.fund_research(current.index, credits as f32)
+                }
+                ResearchButtons::Close => {
+                    state.open = false;
+                    Ok(())
+                }
+            };
+            selected_star.bypass_change_detection().ignore_next_click = true;
+        }
+    }
+}
@@ -5,9 +5,12 @@ use bevy::{prelude::*, ui::FocusPolicy};
 use crate::{
     assets::{loader::ShipAssets, UiAssets},
     game::{
-        fleet::{Fleet, FleetSize, Order, Owner, Ship, ShipKind},
+        fleet::ShipKind,
+        production::{PendingQueueChanges, QueueChange},
+        simulation::{Build, OrderError},
+        turns::CurrentPlayer,
         world::CameraControllerTarget,
-        FleetsToSpawn, Universe,
+        Universe,
     },
     ui_helper::button::{ButtonId, ButtonText},
 };
@@ -16,31 +19,36 @@ use super::{OneFrameDelay, ScreenTag, SelectedStar, DAMPENER};
 
 #[derive(Clone, Copy)]
 pub enum ShipyardButtons {
-    BuildColonyShip,
-    BuildFighter,
-    AddFighter,
-    RemoveFighter,
+    Build(ShipKind),
+    Add(ShipKind),
+    Remove(ShipKind),
+    // position in the queue of the star
+    MoveUp(usize),
+    Cancel(usize),
     Exit,
 }
 
 impl From<ShipyardButtons> for String {
     fn from(button: ShipyardButtons) -> Self {
         match button {
-            ShipyardButtons::BuildColonyShip => {
-                material_icons::icon_to_char(material_icons::Icon::Construction).to_string()
-            }
-            ShipyardButtons::BuildFighter => {
+            ShipyardButtons::Build(_) => {
                 material_icons::icon_to_char(material_icons::Icon::Construction).to_string()
             }
             ShipyardButtons::Exit => {
                 material_icons::icon_to_char(material_icons::Icon::Logout).to_string()
             }
-            ShipyardButtons::AddFighter => {
+            ShipyardButtons::Add(_) => {
                 material_icons::icon_to_char(material_icons::Icon::Add).to_string()
             }
-            ShipyardButtons::RemoveFighter => {
+            ShipyardButtons::Remove(_) => {
                 material_icons::icon_to_char(material_icons::Icon::Remove).to_string()
             }
+            ShipyardButtons::MoveUp(_) => {
+                material_icons::icon_to_char(material_icons::Icon::ArrowUpward).to_string()
+            }
+            ShipyardButtons::Cancel(_) => {
+                material_icons::icon_to_char(material_icons::Icon::Close).to_string()
+            }
         }
     }
 }
@@ -58,10 +66,14 @@ pub struct ShipyardPanelMarker;
 #[derive(Component)]
 pub struct ShipyardErrorMarker;
 
+#[derive(Component)]
+pub struct ShipyardQueueMarker;
+
 #[derive(Resource, Default)]
 pub struct ShipyadForStar {
     star: usize,
-    fighter_count: u32,
+    // how many ships of each kind the build buttons are for, in the order of `ShipKind::ALL`
+    counts: [u32; ShipKind::ALL.len()],
 }
 
 pub fn display_shipyard(
@@ -75,73 +87,78 @@ pub fn display_shipyard(
     mut selected_star: ResMut<SelectedStar>,
     mut for_star: ResMut<ShipyadForStar>,
     ship_assets: Res<ShipAssets>,
+    universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
 ) {
     match shipyard_events.iter().last() {
         Some(ShipyardEvent::OpenForStar(index)) => {
             for_star.star = *index;
-            for_star.fighter_count = 1;
+            for_star.counts = [1; ShipKind::ALL.len()];
             target.ignore_movement = true;
             selected_star.index = None;
             let button_handle = ui_handles.button_handle.clone_weak();
             let button = buttons.get(&button_handle).unwrap();
 
-            let build_colony_button = button.add_hidden_section(
-                &mut commands,
-                Val::Px(250.),
-                Val::Px(40.),
-                UiRect::all(Val::Undefined),
-                vec![
-                    TextSection {
-                        value: material_icons::icon_to_char(material_icons::Icon::Construction)
-                            .to_string(),
-                        style: TextStyle {
-                            font: ui_handles.font_material.clone_weak(),
-                            font_size: 15.0,
-                            color: crate::ui_helper::ColorScheme::TEXT,
-                        },
-                    },
-                    TextSection {
-                        value: " 1 colony ship".to_string(),
-                        style: TextStyle {
-                            font: ui_handles.font_main.clone_weak(),
-                            font_size: 20.0,
-                            color: crate::ui_helper::ColorScheme::TEXT,
-                        },
-                    },
-                ],
-                ShipyardButtons::BuildColonyShip,
-                20.,
-                true,
-            );
-
-            let build_fighter_button = button.add_hidden_section(
-                &mut commands,
-                Val::Px(250.),
-                Val::Px(40.),
-                UiRect::all(Val::Undefined),
-                vec![
-                    TextSection {
-                        value: material_icons::icon_to_char(material_icons::Icon::Construction)
-                            .to_string(),
-                        style: TextStyle {
-                            font: ui_handles.font_material.clone_weak(),
-                            font_size: 15.0,
-                            color: crate::ui_helper::ColorScheme::TEXT,
-                        },
-                    },
-                    TextSection {
-                        value: format!(" {} fighter", for_star.fighter_count),
-                        style: TextStyle {
-                            font: ui_handles.font_main.clone_weak(),
-                            font_size: 20.0,
-                            color: crate::ui_helper::ColorScheme::TEXT,
-                        },
-                    },
-                ],
-                ShipyardButtons::BuildFighter,
-                20.,
-                true,
-            );
+            // ships still to be researched are not offered
+            let rows = ShipKind::ALL
+                .iter()
+                .filter(|kind| universe.can_build(current.index, **kind))
+                .map(|kind| {
+                    let build_button = button.add_hidden_section(
+                        &mut commands,
+                        Val::Px(100.),
+                        Val::Px(30.),
+                        UiRect::all(Val::Undefined),
+                        vec![
+                            TextSection {
+                                value: material_icons::icon_to_char(
+                                    material_icons::Icon::Construction,
+                                )
+                                .to_string(),
+                                style: TextStyle {
+                                    font: ui_handles.font_material.clone_weak(),
+                                    font_size: 15.0,
+                                    color: crate::ui_helper::ColorScheme::TEXT,
+                                },
+                            },
+                            TextSection {
+                                value: format!(" {}", for_star.counts[*kind as usize]),
+                                style: TextStyle {
+                                    font: ui_handles.font_main.clone_weak(),
+                                    font_size: 20.0,
+                                    color: crate::ui_helper::ColorScheme::TEXT,
+                                },
+                            },
+                        ],
+                        ShipyardButtons::Build(*kind),
+                        20.,
+                        true,
+                    );
+                    let remove_button = button.add_hidden(
+                        &mut commands,
+                        Val::Px(30.),
+                        Val::Px(30.),
+                        UiRect::all(Val::Undefined),
+                        ui_handles.font_material.clone_weak(),
+                        ShipyardButtons::Remove(*kind),
+                        20.,
+                        crate::ui_helper::ColorScheme::TEXT,
+                        true,
+                    );
+                    let add_button = button.add_hidden(
+                        &mut commands,
+                        Val::Px(30.),
+                        Val::Px(30.),
+                        UiRect::all(Val::Undefined),
+                        ui_handles.font_material.clone_weak(),
+                        ShipyardButtons::Add(*kind),
+                        20.,
+                        crate::ui_helper::ColorScheme::TEXT,
+                        true,
+                    );
+                    (*kind, [remove_button, add_button, build_button])
+                })
+                .collect::<Vec<_>>();
 
             let exit_button = button.add_hidden(
                 &mut commands,
@@ -155,76 +172,47 @@ pub fn display_shipyard(
                 true,
             );
 
-            let add_fighter_button = button.add_hidden(
-                &mut commands,
-                Val::Px(40.),
-                Val::Px(30.),
-                UiRect::all(Val::Auto),
-                ui_handles.font_material.clone_weak(),
-                ShipyardButtons::AddFighter,
-                20.,
-                crate::ui_helper::ColorScheme::TEXT,
-                true,
-            );
-
-            let remove_fighter_button = button.add_hidden(
-                &mut commands,
-                Val::Px(40.),
-                Val::Px(30.),
-                UiRect::all(Val::Auto),
-                ui_handles.font_material.clone_weak(),
-                ShipyardButtons::RemoveFighter,
-                20.,
-                crate::ui_helper::ColorScheme::TEXT,
-                true,
-            );
-
-            let base = commands
-                .spawn((
-                    NodeBundle {
-                        style: Style {
-                            flex_direction: FlexDirection::Column,
-                            size: Size {
-                                width: Val::Percent(100.0),
-                                height: Val::Percent(100.0),
-                            },
-                            display: Display::None,
-                            ..Default::default()
-                        },
-
-                        ..Default::default()
+            let ship_rows = commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
                     },
-                    OneFrameDelay,
-                ))
+                    ..default()
+                })
                 .with_children(|parent| {
-                    parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                flex_direction: FlexDirection::Column,
-                                margin: UiRect::all(Val::Px(10.0)),
+                    for (kind, buttons) in &rows {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    ..default()
+                                },
                                 ..default()
-                            },
-                            ..default()
-                        })
-                        .with_children(|parent| {
-                            parent
-                                .spawn(NodeBundle {
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(ImageBundle {
+                                    image: UiImage(ship_assets.ship(*kind)),
                                     style: Style {
-                                        flex_direction: FlexDirection::Row,
+                                        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
+                                        margin: UiRect::right(Val::Px(10.0)),
                                         ..default()
                                     },
+                                    transform: Transform::from_rotation(Quat::from_rotation_z(
+                                        FRAC_PI_8 + PI,
+                                    )),
                                     ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn(TextBundle {
+                                });
+                                parent.spawn((
+                                    TextBundle {
                                         text: Text::from_section(
-                                            format!(
-                                                r#"Build 1 {}
-  credits: {}
-  resources: {}"#,
-                                                ShipKind::Colony,
-                                                ShipKind::Colony.cost_credits(),
-                                                ShipKind::Colony.cost_resources()
+                                            ship_label(
+                                                &universe,
+                                                current.index,
+                                                *kind,
+                                                for_star.counts[*kind as usize],
                                             ),
                                             TextStyle {
                                                 font: ui_handles.font_sub.clone_weak(),
@@ -234,108 +222,63 @@ pub fn display_shipyard(
                                         ),
                                         style: Style {
                                             size: Size {
-                                                width: Val::Px(200.0),
+                                                width: Val::Px(250.0),
                                                 height: Val::Px(70.0),
                                             },
                                             ..default()
                                         },
                                         ..default()
-                                    });
-                                    parent.spawn(ImageBundle {
-                                        image: UiImage(ship_assets.colony_ship.clone_weak()),
-                                        style: Style {
-                                            size: Size::new(Val::Px(50.0), Val::Px(50.0)),
-                                            ..default()
-                                        },
-                                        transform: Transform::from_rotation(Quat::from_rotation_z(
-                                            FRAC_PI_8 + PI,
-                                        )),
-                                        ..default()
-                                    });
-                                });
-                        })
-                        .push_children(&[build_colony_button]);
+                                    },
+                                    ButtonText(ShipyardButtons::Build(*kind)),
+                                ));
+                            })
+                            .push_children(buttons);
+                    }
+                })
+                .id();
+            // filled by `display_queue` while the panel is open
+            let queue = commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            size: Size::new(Val::Px(250.0), Val::Undefined),
+                            margin: UiRect::all(Val::Px(5.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ShipyardQueueMarker,
+                ))
+                .id();
+
+            let base = commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            size: Size {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                            },
+                            display: Display::None,
+                            ..Default::default()
+                        },
+
+                        ..Default::default()
+                    },
+                    OneFrameDelay,
+                ))
+                .with_children(|parent| {
                     parent
                         .spawn(NodeBundle {
                             style: Style {
-                                flex_direction: FlexDirection::Column,
-                                margin: UiRect {
-                                    left: Val::Px(10.0),
-                                    right: Val::Px(10.0),
-                                    top: Val::Px(25.0),
-                                    bottom: Val::Px(10.0),
-                                },
+                                flex_direction: FlexDirection::Row,
                                 ..default()
                             },
                             ..default()
                         })
-                        .with_children(|parent| {
-                            parent
-                                .spawn(NodeBundle {
-                                    style: Style {
-                                        flex_direction: FlexDirection::Row,
-                                        ..default()
-                                    },
-                                    ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        TextBundle {
-                                            text: Text::from_section(
-                                                format!(
-                                                    r#"Build {} {}
-  credits: {} ({})
-  resources: {} ({})"#,
-                                                    for_star.fighter_count,
-                                                    ShipKind::Fighter,
-                                                    ShipKind::Fighter.cost_credits()
-                                                        * for_star.fighter_count as f32,
-                                                    ShipKind::Fighter.cost_credits(),
-                                                    ShipKind::Fighter.cost_resources()
-                                                        * for_star.fighter_count as f32,
-                                                    ShipKind::Fighter.cost_resources(),
-                                                ),
-                                                TextStyle {
-                                                    font: ui_handles.font_sub.clone_weak(),
-                                                    font_size: 20.0,
-                                                    color: Color::WHITE,
-                                                },
-                                            ),
-                                            style: Style {
-                                                size: Size {
-                                                    width: Val::Px(200.0),
-                                                    height: Val::Px(70.0),
-                                                },
-                                                ..default()
-                                            },
-                                            ..default()
-                                        },
-                                        ButtonText(ShipyardButtons::BuildFighter),
-                                    ));
-                                    parent.spawn(ImageBundle {
-                                        image: UiImage(ship_assets.fighter.clone_weak()),
-                                        style: Style {
-                                            size: Size::new(Val::Px(50.0), Val::Px(50.0)),
-                                            ..default()
-                                        },
-                                        transform: Transform::from_rotation(Quat::from_rotation_z(
-                                            FRAC_PI_8 + PI,
-                                        )),
-                                        ..default()
-                                    });
-                                });
-                            parent
-                                .spawn(NodeBundle {
-                                    style: Style {
-                                        flex_direction: FlexDirection::Row,
-                                        margin: UiRect::bottom(Val::Px(5.0)),
-                                        ..default()
-                                    },
-                                    ..default()
-                                })
-                                .push_children(&[remove_fighter_button, add_fighter_button]);
-                        })
-                        .push_children(&[build_fighter_button]);
+                        .push_children(&[ship_rows, queue]);
                     parent.spawn((
                         NodeBundle {
                             style: Style {
@@ -377,7 +320,7 @@ pub fn display_shipyard(
             let panel_style = Style {
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
-                size: Size::new(Val::Px(300.0), Val::Px(400.0)),
+                size: Size::new(Val::Px(780.0), Val::Px(560.0)),
                 align_content: AlignContent::Stretch,
                 flex_direction: FlexDirection::Column,
                 ..Default::default()
@@ -400,7 +343,7 @@ pub fn display_shipyard(
                     NodeBundle {
                         style: Style {
                             margin: UiRect::all(Val::Auto),
-                            size: Size::new(Val::Px(600.0), Val::Px(600.0)),
+                            size: Size::new(Val::Px(880.0), Val::Px(640.0)),
                             align_items: AlignItems::Center,
                             justify_content: JustifyContent::Center,
                             ..default()
@@ -472,119 +415,173 @@ pub fn display_shipyard(
     }
 }
 
+fn ship_label(universe: &Universe, player: usize, kind: ShipKind, count: u32) -> String {
+    let price = universe.price(player, kind, count);
+    let unit = universe.price(player, kind, 1);
+    format!(
+        r#"Build {} {}
+  credits: {} ({})
+  resources: {} ({})"#,
+        count, kind, price.credits, unit.credits, price.resources, unit.resources,
+    )
+}
+
 pub fn button_system(
     interaction_query: Query<(&Interaction, &ButtonId<ShipyardButtons>), Changed<Interaction>>,
     mut shipyard_events: EventWriter<ShipyardEvent>,
     mut universe: ResMut<Universe>,
-    mut fleets_to_spawn: ResMut<FleetsToSpawn>,
+    mut pending: ResMut<PendingQueueChanges>,
     mut for_star: ResMut<ShipyadForStar>,
-    mut fighters_texts: Query<(&mut Text, &ButtonText<ShipyardButtons>)>,
+    mut ships_texts: Query<(&mut Text, &ButtonText<ShipyardButtons>)>,
+    current: Res<CurrentPlayer>,
 ) {
+    let player = current.index;
     for (interaction, button_id) in interaction_query.iter() {
         if *interaction == Interaction::Clicked {
-            match button_id.0 {
-                ShipyardButtons::Exit => shipyard_events.send(ShipyardEvent::Close),
-                ShipyardButtons::BuildColonyShip => {
-                    if universe.players[0].savings < ShipKind::Colony.cost_credits() {
-                        shipyard_events.send(ShipyardEvent::InsufficentSavings);
-                        return;
-                    }
-                    if universe.players[0].resources < ShipKind::Colony.cost_resources() {
-                        shipyard_events.send(ShipyardEvent::InsufficentResources);
-                        return;
-                    }
-                    universe.players[0].savings -= ShipKind::Colony.cost_credits();
-                    universe.players[0].resources -= ShipKind::Colony.cost_resources();
-                    fleets_to_spawn.0.push(Fleet {
-                        order: Order::Orbit(for_star.star),
-                        ship: Ship {
-                            kind: ShipKind::Colony,
-                        },
-                        size: FleetSize(1),
-                        owner: Owner(0),
-                    });
+            let change = match button_id.0 {
+                ShipyardButtons::Exit => {
                     shipyard_events.send(ShipyardEvent::Close);
+                    continue;
                 }
-                ShipyardButtons::BuildFighter => {
-                    if universe.players[0].savings
-                        < ShipKind::Fighter.cost_credits() * for_star.fighter_count as f32
-                    {
-                        shipyard_events.send(ShipyardEvent::InsufficentSavings);
-                        return;
-                    }
-                    if universe.players[0].resources
-                        < ShipKind::Fighter.cost_resources() * for_star.fighter_count as f32
-                    {
-                        shipyard_events.send(ShipyardEvent::InsufficentResources);
-                        return;
-                    }
-                    universe.players[0].savings -=
-                        ShipKind::Fighter.cost_credits() * for_star.fighter_count as f32;
-                    universe.players[0].resources -=
-                        ShipKind::Fighter.cost_resources() * for_star.fighter_count as f32;
-                    fleets_to_spawn.0.push(Fleet {
-                        order: Order::Orbit(for_star.star),
-                        ship: Ship {
-                            kind: ShipKind::Fighter,
-                        },
-                        size: FleetSize(for_star.fighter_count),
-                        owner: Owner(0),
-                    });
-                    shipyard_events.send(ShipyardEvent::Close);
+                ShipyardButtons::Build(kind) => QueueChange::Build(Build {
+                    star: for_star.star,
+                    kind,
+                    size: for_star.counts[kind as usize],
+                }),
+                ShipyardButtons::MoveUp(position) => QueueChange::MoveUp(for_star.star, position),
+                ShipyardButtons::Cancel(position) => QueueChange::Cancel(for_star.star, position),
+                ShipyardButtons::Add(kind) => {
+                    for_star.counts[kind as usize] += 1;
+                    update_label(&mut ships_texts, &universe, player, kind, &for_star);
+                    continue;
                 }
-                ShipyardButtons::AddFighter => {
-                    for_star.fighter_count += 1;
-                    for (mut text, button) in &mut fighters_texts {
-                        if matches!(button, ButtonText(ShipyardButtons::BuildFighter)) {
-                            if text.sections.len() == 1 {
-                                text.sections[0].value = format!(
-                                    r#"Build {} {}
-  credits: {} ({})
-  resources: {} ({})"#,
-                                    for_star.fighter_count,
-                                    ShipKind::Fighter,
-                                    ShipKind::Fighter.cost_credits()
-                                        * for_star.fighter_count as f32,
-                                    ShipKind::Fighter.cost_credits(),
-                                    ShipKind::Fighter.cost_resources()
-                                        * for_star.fighter_count as f32,
-                                    ShipKind::Fighter.cost_resources(),
-                                );
-                            } else {
-                                text.sections[1].value =
-                                    format!(" {} fighter", for_star.fighter_count);
-                            }
-                        }
+                ShipyardButtons::Remove(kind) => {
+                    if for_star.counts[kind as usize] > 1 {
+                        for_star.counts[kind as usize] -= 1;
+                        update_label(&mut ships_texts, &universe, player, kind, &for_star);
                     }
+                    continue;
                 }
-                ShipyardButtons::RemoveFighter => {
-                    if for_star.fighter_count > 1 {
-                        for_star.fighter_count -= 1;
-                        for (mut text, button) in &mut fighters_texts {
-                            if matches!(button, ButtonText(ShipyardButtons::BuildFighter)) {
-                                if text.sections.len() == 1 {
-                                    text.sections[0].value = format!(
-                                        r#"Build {} {}
-  credits: {} ({})
-  resources: {} ({})"#,
-                                        for_star.fighter_count,
-                                        ShipKind::Fighter,
-                                        ShipKind::Fighter.cost_credits()
-                                            * for_star.fighter_count as f32,
-                                        ShipKind::Fighter.cost_credits(),
-                                        ShipKind::Fighter.cost_resources()
-                                            * for_star.fighter_count as f32,
-                                        ShipKind::Fighter.cost_resources(),
-                                    );
-                                } else {
-                                    text.sections[1].value =
-                                        format!(" {} fighter", for_star.fighter_count);
-                                }
-                            }
-                        }
-                    }
+            };
+            match universe.apply_queue_change(player, &change) {
+                // the host replays the changes made on this screen
+                Ok(()) if current.remote => pending.0.push(change),
+                Ok(()) => (),
+                Err(OrderError::InsufficientResources) => {
+                    shipyard_events.send(ShipyardEvent::InsufficentResources)
                 }
+                Err(_) => shipyard_events.send(ShipyardEvent::InsufficentSavings),
             }
         }
     }
 }
+
+fn update_label(
+    ships_texts: &mut Query<(&mut Text, &ButtonText<ShipyardButtons>)>,
+    universe: &Universe,
+    player: usize,
+    kind: ShipKind,
+    for_star: &ShipyadForStar,
+) {
+    let count = for_star.counts[kind as usize];
+    for (mut text, button) in ships_texts {
+        if matches!(button, ButtonText(ShipyardButtons::Build(k)) if *k == kind) {
+            if text.sections.len() == 1 {
+                text.sections[0].value = ship_label(universe, player, kind, count);
+            } else {
+                text.sections[1].value = format!(" {}", count);
+            }
+        }
+    }
+}
+
+pub fn display_queue(
+    mut commands: Commands,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    queue_node: Query<(Entity, ChangeTrackers<ShipyardQueueMarker>)>,
+    universe: Res<Universe>,
+    for_star: Res<ShipyadForStar>,
+) {
+    let Ok((entity, tracker)) = queue_node.get_single() else {
+        return;
+    };
+    if !(tracker.is_added() || universe.is_changed()) {
+        return;
+    }
+    commands.entity(entity).despawn_descendants();
+
+    let text_style = TextStyle {
+        font: ui_handles.font_sub.clone_weak(),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    let queue = universe.queue(for_star.star);
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+    let rows = queue
+        .iter()
+        .enumerate()
+        .map(|(position, item)| {
+            let actions = [
+                ShipyardButtons::MoveUp(position),
+                ShipyardButtons::Cancel(position),
+            ]
+            .map(|action| {
+                button.add(
+                    &mut commands,
+                    Val::Px(30.),
+                    Val::Px(30.),
+                    UiRect::all(Val::Px(2.0)),
+                    ui_handles.font_material.clone_weak(),
+                    action,
+                    20.,
+                    crate::ui_helper::ColorScheme::TEXT,
+                )
+            });
+            let label = commands
+                .spawn(TextBundle::from_section(
+                    format!(
+                        "{} {}, {} turn(s)",
+                        item.size,
+                        item.kind,
+                        universe.turns_left(for_star.star, position)
+                    ),
+                    text_style.clone(),
+                ))
+                .id();
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&actions)
+                .push_children(&[label])
+                .id()
+        })
+        .collect::<Vec<_>>();
+
+    commands.entity(entity).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!(
+                "Queue, {:.1} work per turn",
+                universe.production_rate(for_star.star)
+            ),
+            text_style.clone(),
+        ));
+        if queue.is_empty() {
+            parent.spawn(TextBundle::from_section(
+                "Nothing being built",
+                TextStyle {
+                    color: crate::ui_helper::ColorScheme::TEXT_DARK,
+                    ..text_style
+                },
+            ));
+        }
+    });
+    commands.entity(entity).push_children(&rows);
+}
@@ -0,0 +1,376 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{
+    prelude::{DrawMode, FillMode, GeometryBuilder, PathBuilder, StrokeMode},
+    shapes,
+};
+
+use crate::{
+    assets::UiAssets,
+    game::{
+        stats::{Snapshot, StatsHistory},
+        turns::CurrentPlayer,
+        z_levels, Universe,
+    },
+    ui_helper::button::ButtonId,
+    GameState,
+};
+
+use super::{ScreenTag, SelectedStar, LEFT_PANEL_WIDTH};
+
+const CHART_WIDTH: f32 = 600.0;
+const CHART_HEIGHT: f32 = 300.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Series {
+    #[default]
+    Population,
+    Revenue,
+    Savings,
+    Resources,
+    Colonies,
+    Fleet,
+}
+
+impl Series {
+    const ALL: [Series; 6] = [
+        Series::Population,
+        Series::Revenue,
+        Series::Savings,
+        Series::Resources,
+        Series::Colonies,
+        Series::Fleet,
+    ];
+
+    fn value(&self, snapshot: &Snapshot) -> f32 {
+        match self {
+            Series::Population => snapshot.population,
+            Series::Revenue => snapshot.revenue,
+            Series::Savings => snapshot.savings,
+            Series::Resources => snapshot.resources,
+            Series::Colonies => snapshot.colonies as f32,
+            Series::Fleet => snapshot.fleet_strength as f32,
+        }
+    }
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Series::Population => write!(f, "Population"),
+            Series::Revenue => write!(f, "Revenue"),
+            Series::Savings => write!(f, "Savings"),
+            Series::Resources => write!(f, "Resources"),
+            Series::Colonies => write!(f, "Colonies"),
+            Series::Fleet => write!(f, "Fleet"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatsButtons {
+    Series(Series),
+    Close,
+}
+
+impl From<StatsButtons> for String {
+    fn from(button: StatsButtons) -> Self {
+        match button {
+            StatsButtons::Series(series) => series.to_string(),
+            StatsButtons::Close => {
+                material_icons::icon_to_char(material_icons::Icon::Close).to_string()
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct StatsState {
+    pub open: bool,
+    series: Series,
+}
+
+#[derive(Component)]
+pub struct StatsPanel;
+
+#[derive(Component)]
+pub struct StatsChart;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn display_stats(
+    mut commands: Commands,
+    state: Res<StatsState>,
+    history: Res<StatsHistory>,
+    universe: Res<Universe>,
+    current: Res<CurrentPlayer>,
+    game_state: Res<State<GameState>>,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    camera: Query<(Entity, &Transform), With<Camera2d>>,
+    displayed: Query<Entity, Or<(With<StatsPanel>, With<StatsChart>)>>,
+) {
+    if !(state.is_changed() || history.is_changed() || current.is_changed()) {
+        return;
+    }
+    for entity in &displayed {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+
+    // the other players don't show their books until the game is over
+    let players = if *game_state.current() == GameState::Lost {
+        (0..universe.pirates).collect::<Vec<_>>()
+    } else {
+        vec![current.index]
+    };
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+
+    let mut series_buttons = Series::ALL
+        .iter()
+        .map(|series| {
+            button.add(
+                &mut commands,
+                Val::Px(110.),
+                Val::Px(30.),
+                UiRect::all(Val::Px(2.0)),
+                ui_handles.font_sub.clone_weak(),
+                StatsButtons::Series(*series),
+                20.,
+                if state.series == *series {
+                    crate::ui_helper::ColorScheme::TEXT
+                } else {
+                    crate::ui_helper::ColorScheme::TEXT_DARK
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    series_buttons.push(button.add(
+        &mut commands,
+        Val::Px(30.),
+        Val::Px(30.),
+        UiRect::all(Val::Px(2.0)),
+        ui_handles.font_material.clone_weak(),
+        StatsButtons::Close,
+        20.,
+        crate::ui_helper::ColorScheme::TEXT,
+    ));
+
+    let base = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&series_buttons);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for player in &players {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                universe.players[*player].name.clone(),
+                                TextStyle {
+                                    font: ui_handles.font_sub.clone_weak(),
+                                    font_size: 20.0,
+                                    color: universe.owner_color(*player),
+                                },
+                            ),
+                            style: Style {
+                                margin: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    }
+                });
+        })
+        .id();
+
+    let stats_panel = commands
+        .spawn(bevy_ninepatch::NinePatchBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            nine_patch_data: bevy_ninepatch::NinePatchData::with_single_content(
+                ui_handles.panel_handle.1.clone_weak(),
+                ui_handles.panel_handle.0.clone_weak(),
+                base,
+            ),
+            ..default()
+        })
+        .id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(LEFT_PANEL_WIDTH),
+                        top: Val::Px(20.0),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(CHART_WIDTH + 200.0), Val::Undefined),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            StatsPanel,
+            ScreenTag,
+        ))
+        .push_children(&[stats_panel]);
+
+    // the chart is drawn in the world, attached to the camera so that it stays in place
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+    let origin = Vec2::new(LEFT_PANEL_WIDTH / 2.0, -40.0);
+    let text_style = TextStyle {
+        font: ui_handles.font_main.clone_weak(),
+        font_size: 20.0,
+        color: Color::ANTIQUE_WHITE,
+    };
+
+    let first = history.samples.first().map_or(1, |sample| sample.turn);
+    let last = history.samples.last().map_or(1, |sample| sample.turn);
+    let max = history
+        .samples
+        .iter()
+        .flat_map(|sample| {
+            players
+                .iter()
+                .filter_map(|player| sample.snapshots.get(*player))
+        })
+        .map(|snapshot| state.series.value(snapshot))
+        .fold(0.0, f32::max);
+    let point = |turn: u32, value: f32| {
+        Vec2::new(
+            -CHART_WIDTH / 2.0 + CHART_WIDTH * (turn - first) as f32 / (last - first).max(1) as f32,
+            -CHART_HEIGHT / 2.0 + CHART_HEIGHT * value / max.max(1.0),
+        )
+    };
+
+    let chart = commands
+        .spawn((
+            GeometryBuilder::build_as(
+                &shapes::Rectangle {
+                    extents: Vec2::new(CHART_WIDTH + 120.0, CHART_HEIGHT + 80.0),
+                    origin: shapes::RectangleOrigin::Center,
+                },
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(Color::rgba(0.05, 0.05, 0.1, 0.9)),
+                    outline_mode: StrokeMode::new(Color::rgb(0.5, 0.5, 0.5), 2.0),
+                },
+                Transform::from_translation(
+                    origin.extend(z_levels::STATS_CHART - camera_transform.translation.z),
+                ),
+            ),
+            StatsChart,
+            ScreenTag,
+        ))
+        .with_children(|parent| {
+            let mut path_builder = PathBuilder::new();
+            path_builder.move_to(Vec2::new(-CHART_WIDTH / 2.0, CHART_HEIGHT / 2.0));
+            path_builder.line_to(Vec2::new(-CHART_WIDTH / 2.0, -CHART_HEIGHT / 2.0));
+            path_builder.line_to(Vec2::new(CHART_WIDTH / 2.0, -CHART_HEIGHT / 2.0));
+            parent.spawn(GeometryBuilder::build_as(
+                &path_builder.build(),
+                DrawMode::Stroke(StrokeMode::new(Color::rgb(0.75, 0.75, 0.75), 1.5)),
+                Transform::from_translation(Vec3::new(0.0, 0.0, 0.1)),
+            ));
+
+            for player in &players {
+                let mut path_builder = PathBuilder::new();
+                let mut started = false;
+                for sample in &history.samples {
+                    let Some(snapshot) = sample.snapshots.get(*player) else {
+                        continue;
+                    };
+                    let point = point(sample.turn, state.series.value(snapshot));
+                    if started {
+                        path_builder.line_to(point);
+                    } else {
+                        path_builder.move_to(point);
+                        started = true;
+                    }
+                }
+                parent.spawn(GeometryBuilder::build_as(
+                    &path_builder.build(),
+                    DrawMode::Stroke(StrokeMode::new(universe.owner_color(*player), 2.0)),
+                    Transform::from_translation(Vec3::new(0.0, 0.0, 0.2)),
+                ));
+            }
+
+            for (label, position) in [
+                (
+                    format!("{:.0}", max),
+                    Vec2::new(-CHART_WIDTH / 2.0 - 30.0, CHART_HEIGHT / 2.0),
+                ),
+                (
+                    "0".to_string(),
+                    Vec2::new(-CHART_WIDTH / 2.0 - 30.0, -CHART_HEIGHT / 2.0),
+                ),
+                (
+                    format!("Turn {}", first),
+                    Vec2::new(-CHART_WIDTH / 2.0, -CHART_HEIGHT / 2.0 - 20.0),
+                ),
+                (
+                    format!("Turn {}", last),
+                    Vec2::new(CHART_WIDTH / 2.0, -CHART_HEIGHT / 2.0 - 20.0),
+                ),
+            ] {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(label, text_style.clone())
+                        .with_alignment(TextAlignment::CENTER),
+                    transform: Transform::from_translation(position.extend(0.3)),
+                    ..default()
+                });
+            }
+        })
+        .id();
+    commands.entity(camera).add_child(chart);
+}
+
+pub fn button_system(
+    interaction_query: Query<(&Interaction, &ButtonId<StatsButtons>), Changed<Interaction>>,
+    mut state: ResMut<StatsState>,
+    mut selected_star: ResMut<SelectedStar>,
+) {
+    for (interaction, button_id) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            match button_id.0 {
+                StatsButtons::Series(series) => state.series = series,
+                StatsButtons::Close => state.open = false,
+            }
+            selected_star.bypass_change_detection().ignore_next_click = true;
+        }
+    }
+}
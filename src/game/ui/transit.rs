@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::{
+    prelude::{DrawMode, GeometryBuilder, PathBuilder, StrokeMode},
+    shapes,
+};
+
+use crate::{
+    assets::{loader::ShipAssets, UiAssets},
+    game::{
+        fleet::{FleetSize, Order, Owner, Ship},
+        turns::CurrentPlayer,
+        world::{CameraController, RATIO_ZOOM_DISTANCE},
+        z_levels, Universe,
+    },
+    ui_helper::button::ButtonId,
+};
+
+use super::{ScreenTag, SelectedStar, StarAction, LEFT_PANEL_WIDTH};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TransitButtons {
+    Recall,
+    Close,
+}
+
+impl From<TransitButtons> for String {
+    fn from(button: TransitButtons) -> Self {
+        match button {
+            TransitButtons::Recall => "Recall".to_string(),
+            TransitButtons::Close => {
+                material_icons::icon_to_char(material_icons::Icon::Close).to_string()
+            }
+        }
+    }
+}
+
+// a fleet of the current player picked while in flight
+#[derive(Resource, Default)]
+pub struct TransitState {
+    pub fleet: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct TransitPanel;
+
+#[derive(Component)]
+pub struct TransitPath;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn display_transit(
+    mut commands: Commands,
+    mut state: ResMut<TransitState>,
+    (universe, current): (Res<Universe>, Res<CurrentPlayer>),
+    controller: Res<CameraController>,
+    ui_handles: Res<UiAssets>,
+    ship_assets: Res<ShipAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    fleets: Query<(&Order, &Ship, &FleetSize, &Owner, ChangeTrackers<Order>)>,
+    displayed: Query<Entity, Or<(With<TransitPanel>, With<TransitPath>)>>,
+) {
+    // the fleet may have arrived or been destroyed since it was picked
+    let selected = state
+        .fleet
+        .and_then(|entity| fleets.get(entity).ok())
+        .filter(|(order, _, _, owner, _)| order.in_flight() && owner.0 == current.index);
+    if state.fleet.is_some() && selected.is_none() {
+        state.fleet = None;
+    } else if !(state.is_changed()
+        || controller.is_changed()
+        || selected
+            .as_ref()
+            .is_some_and(|(.., tracker)| tracker.is_changed()))
+    {
+        return;
+    }
+    for entity in &displayed {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (Some(entity), Some((order, ship, size, owner, _))) = (state.fleet, selected) else {
+        return;
+    };
+    let speed = universe.speed(owner.0, ship.kind);
+    let Order::Move { from, to, step, .. } = order else {
+        return;
+    };
+
+    let to_screen = |position: Vec2| position * controller.zoom_level / RATIO_ZOOM_DISTANCE;
+    let mut path_builder = PathBuilder::new();
+    path_builder.move_to(to_screen(order.departure(&universe.galaxy)));
+    for stop in &order.route()[1..] {
+        path_builder.line_to(to_screen(universe.galaxy[*stop].position));
+    }
+    commands.spawn((
+        GeometryBuilder::build_as(
+            &path_builder.build(),
+            DrawMode::Stroke(StrokeMode::new(Color::rgb(0.75, 0.75, 0.75), 1.5)),
+            Transform::from_translation(Vec2::ZERO.extend(z_levels::STAR_SELECTION)),
+        ),
+        TransitPath,
+        ScreenTag,
+    ));
+    commands.spawn((
+        GeometryBuilder::build_as(
+            &shapes::Circle {
+                radius: 6.0 * controller.zoom_level.powf(0.7),
+                center: Vec2::ZERO,
+            },
+            DrawMode::Stroke(StrokeMode::new(Color::GREEN, 1.5)),
+            Transform::from_translation(
+                to_screen(order.position(&universe.galaxy, speed)).extend(z_levels::STAR_SELECTION),
+            ),
+        ),
+        TransitPath,
+        ScreenTag,
+    ));
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+    let mut actions = vec![];
+    // once recalled, the fleet already heads back to where it came from
+    if from != to {
+        actions.push(button.add(
+            &mut commands,
+            Val::Px(100.),
+            Val::Px(30.),
+            UiRect::all(Val::Px(2.0)),
+            ui_handles.font_sub.clone_weak(),
+            TransitButtons::Recall,
+            20.,
+            crate::ui_helper::ColorScheme::TEXT,
+        ));
+    }
+    actions.push(button.add(
+        &mut commands,
+        Val::Px(30.),
+        Val::Px(30.),
+        UiRect::all(Val::Px(2.0)),
+        ui_handles.font_material.clone_weak(),
+        TransitButtons::Close,
+        20.,
+        crate::ui_helper::ColorScheme::TEXT,
+    ));
+
+    let text_style = TextStyle {
+        font: ui_handles.font_sub.clone_weak(),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    let base = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // dragged to a star like the fleets orbiting the selected one
+                    parent.spawn((
+                        ImageBundle {
+                            image: UiImage(ship_assets.ship(ship.kind)),
+                            style: Style {
+                                size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                                ..default()
+                            },
+                            transform: Transform::from_rotation(Quat::from_rotation_z(
+                                std::f32::consts::PI,
+                            )),
+                            ..default()
+                        },
+                        Interaction::None,
+                        ButtonId(StarAction::Ship(entity)),
+                    ));
+                    parent.spawn(TextBundle::from_section(
+                        format!(" {} {}", size, ship),
+                        text_style.clone(),
+                    ));
+                });
+            parent.spawn(TextBundle::from_section(
+                if from == to {
+                    format!("back to {}", universe.galaxy[*to].name)
+                } else {
+                    format!(
+                        "{} to {}",
+                        universe.galaxy[*from].name, universe.galaxy[*to].name
+                    )
+                },
+                text_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "leg {}/{}, {} turn(s) left",
+                    step,
+                    order.leg_turns(&universe.galaxy, speed),
+                    order.eta(&universe.galaxy, speed)
+                ),
+                text_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Drag the ship to redirect",
+                TextStyle {
+                    color: crate::ui_helper::ColorScheme::TEXT_DARK,
+                    ..text_style
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&actions);
+        })
+        .id();
+
+    let panel = commands
+        .spawn(bevy_ninepatch::NinePatchBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            nine_patch_data: bevy_ninepatch::NinePatchData::with_single_content(
+                ui_handles.panel_handle.1.clone_weak(),
+                ui_handles.panel_handle.0.clone_weak(),
+                base,
+            ),
+            ..default()
+        })
+        .id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(LEFT_PANEL_WIDTH + 20.0),
+                        bottom: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            TransitPanel,
+            ScreenTag,
+        ))
+        .push_children(&[panel]);
+}
+
+pub fn button_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &ButtonId<TransitButtons>), Changed<Interaction>>,
+    mut state: ResMut<TransitState>,
+    mut selected_star: ResMut<SelectedStar>,
+    fleets: Query<(&Order, &Ship, &Owner)>,
+    universe: Res<Universe>,
+) {
+    for (interaction, button_id) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            match button_id.0 {
+                TransitButtons::Recall => {
+                    if let Some(entity) = state.fleet {
+                        if let Ok((order @ Order::Move { from, .. }, ship, owner)) =
+                            fleets.get(entity)
+                        {
+                            commands.entity(entity).insert(order.redirect(
+                                *from,
+                                &universe.galaxy,
+                                universe.speed(owner.0, ship.kind),
+                            ));
+                        }
+                    }
+                }
+                TransitButtons::Close => state.fleet = None,
+            }
+            selected_star.bypass_change_detection().ignore_next_click = true;
+        }
+    }
+}
@@ -310,7 +310,7 @@ pub fn display_messages(
                     controller_target.zoom_level = 8.0;
                     controller_target.position = universe.galaxy[index].position;
                 }
-                Message::ColonyDestroyed { index, .. } => {
+                Message::ColonyDestroyed { index, .. } | Message::ColonyCaptured { index, .. } => {
                     turn_icon.single_mut().1.is_visible = true;
                     turn_icon.single_mut().2.sections[0].value =
                         material_icons::icon_to_char(material_icons::Icon::PublicOff).to_string();
@@ -320,6 +320,17 @@ pub fn display_messages(
                     controller_target.zoom_level = 8.0;
                     controller_target.position = universe.galaxy[index].position;
                 }
+                Message::ShipsBuilt { index, .. } => {
+                    turn_icon.single_mut().1.is_visible = true;
+                    turn_icon.single_mut().2.sections[0].value =
+                        material_icons::icon_to_char(material_icons::Icon::Construction)
+                            .to_string();
+                    if selected_star.index != Some(index) {
+                        selected_star.index = Some(index);
+                    }
+                    controller_target.zoom_level = 8.0;
+                    controller_target.position = universe.galaxy[index].position;
+                }
                 Message::StarExplored { index, .. } => {
                     turn_icon.single_mut().1.is_visible = true;
                     turn_icon.single_mut().2.sections[0].value =
@@ -344,7 +355,7 @@ pub fn display_messages(
                         controller_target.position = universe.galaxy[index].position;
                     }
                 }
-                Message::Fight { index, .. } => {
+                Message::Battle { index, .. } => {
                     turn_icon.single_mut().1.is_visible = true;
                     turn_icon.single_mut().2.sections[0].value =
                         material_icons::icon_to_char(material_icons::Icon::GppMaybe).to_string();
@@ -355,13 +366,29 @@ pub fn display_messages(
                     controller_target.zoom_level = 8.0;
                     controller_target.position = universe.galaxy[index].position;
                 }
-                Message::Win => {
+                Message::PiratesSighted { index, .. } | Message::PirateRaid { index, .. } => {
+                    turn_icon.single_mut().1.is_visible = true;
+                    turn_icon.single_mut().2.sections[0].value =
+                        material_icons::icon_to_char(material_icons::Icon::Warning).to_string();
+
+                    if selected_star.index != Some(index) {
+                        selected_star.index = Some(index);
+                    }
+                    controller_target.zoom_level = 8.0;
+                    controller_target.position = universe.galaxy[index].position;
+                }
+                Message::TechResearched { .. } => {
+                    turn_icon.single_mut().1.is_visible = true;
+                    turn_icon.single_mut().2.sections[0].value =
+                        material_icons::icon_to_char(material_icons::Icon::Science).to_string();
+                }
+                Message::Win | Message::Victory { .. } => {
                     turn_icon.single_mut().1.is_visible = true;
                     turn_icon.single_mut().2.sections[0].value =
                         material_icons::icon_to_char(material_icons::Icon::MilitaryTech)
                             .to_string();
                 }
-                Message::Lose { .. } => {
+                Message::Lose { .. } | Message::RivalVictory { .. } => {
                     turn_icon.single_mut().1.is_visible = true;
                     turn_icon.single_mut().2.sections[0].value =
                         material_icons::icon_to_char(material_icons::Icon::Healing).to_string();
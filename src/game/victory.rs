@@ -0,0 +1,190 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, seq::IteratorRandom};
+use serde::{Deserialize, Serialize};
+
+use super::{galaxy::StarSize, Universe};
+
+// eliminating every other colony always wins, the other rules are chosen at setup
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct VictoryRules {
+    pub turn_limit: Option<u32>,
+    // share of the stars of the galaxy to own
+    pub domination: Option<f32>,
+    pub economic: Option<f32>,
+    // how many special stars are placed in the galaxy, all of them must be held
+    pub special_stars: Option<u32>,
+}
+
+impl VictoryRules {
+    pub const TURN_LIMIT: u32 = 200;
+    pub const DOMINATION: f32 = 0.6;
+    pub const ECONOMIC: f32 = 5000.0;
+    pub const SPECIAL_STARS: u32 = 3;
+
+    pub fn toggle(&mut self, condition: VictoryCondition) {
+        match condition {
+            VictoryCondition::Elimination => (),
+            VictoryCondition::TurnLimit => {
+                self.turn_limit = self.turn_limit.xor(Some(Self::TURN_LIMIT))
+            }
+            VictoryCondition::Domination => {
+                self.domination = self.domination.xor(Some(Self::DOMINATION))
+            }
+            VictoryCondition::Economic => self.economic = self.economic.xor(Some(Self::ECONOMIC)),
+            VictoryCondition::SpecialStars => {
+                self.special_stars = self.special_stars.xor(Some(Self::SPECIAL_STARS))
+            }
+        }
+    }
+
+    pub fn is_enabled(&self, condition: VictoryCondition) -> bool {
+        match condition {
+            VictoryCondition::Elimination => true,
+            VictoryCondition::TurnLimit => self.turn_limit.is_some(),
+            VictoryCondition::Domination => self.domination.is_some(),
+            VictoryCondition::Economic => self.economic.is_some(),
+            VictoryCondition::SpecialStars => self.special_stars.is_some(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum VictoryCondition {
+    Elimination,
+    TurnLimit,
+    Domination,
+    Economic,
+    SpecialStars,
+}
+
+impl VictoryCondition {
+    pub const OPTIONAL: [VictoryCondition; 4] = [
+        VictoryCondition::TurnLimit,
+        VictoryCondition::Domination,
+        VictoryCondition::Economic,
+        VictoryCondition::SpecialStars,
+    ];
+
+    // told to the winner, wrapped for the message panel
+    pub fn achievement(&self) -> &'static str {
+        match self {
+            VictoryCondition::Elimination => "You destroyed all\nenemy colonies.",
+            VictoryCondition::TurnLimit => "You had the best score\nwhen time ran out.",
+            VictoryCondition::Domination => "You control most\nof the galaxy.",
+            VictoryCondition::Economic => "Your savings reached\ntheir target.",
+            VictoryCondition::SpecialStars => "You hold all the\nspecial stars.",
+        }
+    }
+}
+
+impl fmt::Display for VictoryCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VictoryCondition::Elimination => write!(f, "elimination"),
+            VictoryCondition::TurnLimit => write!(f, "score"),
+            VictoryCondition::Domination => write!(f, "domination"),
+            VictoryCondition::Economic => write!(f, "economy"),
+            VictoryCondition::SpecialStars => write!(f, "special stars"),
+        }
+    }
+}
+
+// giants are never a starting star, and are the ones worth fighting for
+pub fn choose_special_stars(universe: &Universe, rand: &mut StdRng) -> Vec<usize> {
+    let count = match universe.victory.special_stars {
+        Some(count) => count as usize,
+        None => return vec![],
+    };
+    let mut chosen = (0..universe.galaxy.len())
+        .filter(|i| universe.galaxy[*i].size == StarSize::Giant)
+        .choose_multiple(rand, count);
+    if chosen.len() < count {
+        chosen.extend(
+            (0..universe.galaxy.len())
+                .filter(|i| universe.star_details[*i].owner == usize::MAX && !chosen.contains(i))
+                .choose_multiple(rand, count - chosen.len()),
+        );
+    }
+    chosen
+}
+
+impl Universe {
+    pub fn owned_stars(&self, player: usize) -> usize {
+        self.star_details
+            .iter()
+            .filter(|details| details.owner == player)
+            .count()
+    }
+
+    // used to rank players when the turn limit is reached
+    pub fn score(&self, player: usize) -> f32 {
+        self.player_population(player)
+            + self.owned_stars(player) as f32 * 10.0
+            + self.players[player].savings.max(0.0) / 10.0
+            + self.held_special_stars(player) as f32 * 50.0
+    }
+
+    pub fn held_special_stars(&self, player: usize) -> usize {
+        self.special_stars
+            .iter()
+            .filter(|star| self.star_details[**star].owner == player)
+            .count()
+    }
+
+    // number of stars to own for a domination victory
+    pub fn domination_target(&self) -> Option<usize> {
+        self.victory
+            .domination
+            .map(|share| (self.galaxy.len() as f32 * share).ceil() as usize)
+    }
+
+    pub fn is_special_star(&self, star: usize) -> bool {
+        self.special_stars.contains(&star)
+    }
+
+    // the player who won the game through one of the optional rules
+    pub fn victory(&self, turn: u32) -> Option<(usize, VictoryCondition)> {
+        let in_game = |player: &usize| self.owned_stars(*player) > 0;
+        let rules = &self.victory;
+
+        if let Some(needed) = self.domination_target() {
+            if let Some(player) = (0..self.pirates)
+                .filter(in_game)
+                .find(|player| self.owned_stars(*player) >= needed)
+            {
+                return Some((player, VictoryCondition::Domination));
+            }
+        }
+        if let Some(target) = rules.economic {
+            if let Some(player) = (0..self.pirates)
+                .filter(in_game)
+                .filter(|player| self.players[*player].savings >= target)
+                .max_by(|a, b| {
+                    self.players[*a]
+                        .savings
+                        .total_cmp(&self.players[*b].savings)
+                })
+            {
+                return Some((player, VictoryCondition::Economic));
+            }
+        }
+        if rules.special_stars.is_some() && !self.special_stars.is_empty() {
+            if let Some(player) = (0..self.pirates)
+                .filter(in_game)
+                .find(|player| self.held_special_stars(*player) == self.special_stars.len())
+            {
+                return Some((player, VictoryCondition::SpecialStars));
+            }
+        }
+        if let Some(limit) = rules.turn_limit {
+            if turn >= limit {
+                return (0..self.pirates)
+                    .filter(in_game)
+                    .max_by(|a, b| self.score(*a).total_cmp(&self.score(*b)))
+                    .map(|player| (player, VictoryCondition::TurnLimit));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,86 @@
+use super::{
+    galaxy::{StarColor, StarSize},
+    research::Research,
+    simulation::FleetState,
+    StarDetails, StarState, Universe,
+};
+
+impl Universe {
+    // the galaxy as a player knows it: each star as they last saw it, and of the other players
+    // nothing but their name
+    pub fn seen_by(&self, player: usize) -> Universe {
+        let mut universe = self.clone();
+        let vision = &self.players[player].vision;
+        for ((star, details), state) in universe
+            .galaxy
+            .iter_mut()
+            .zip(universe.star_details.iter_mut())
+            .zip(vision.iter())
+        {
+            match state {
+                StarState::Owned(owner) if *owner == player => (),
+                StarState::Owned(owner) => {
+                    *details = StarDetails {
+                        population: 0.0,
+                        resources: 0.0,
+                        owner: *owner,
+                        owned_since: u32::MAX,
+                    }
+                }
+                StarState::Uninhabited => {
+                    details.population = 0.0;
+                    details.owner = usize::MAX;
+                    details.owned_since = u32::MAX;
+                }
+                StarState::Unknown => {
+                    *details = StarDetails {
+                        population: 0.0,
+                        resources: 0.0,
+                        owner: usize::MAX,
+                        owned_since: u32::MAX,
+                    };
+                    // only where it is
+                    star.name = String::new();
+                    star.color = StarColor::Yellow;
+                    star.size = StarSize::Subgiant;
+                }
+            }
+        }
+        let start = self.players[player].start;
+        for (i, other) in universe.players.iter_mut().enumerate() {
+            if i == player {
+                continue;
+            }
+            // anything computed from their start uses the conditions of the player instead
+            other.start = start;
+            other.vision = vec![StarState::Unknown; vision.len()];
+            other.savings = 0.0;
+            other.resources = 0.0;
+            other.research = Research::default();
+        }
+        for queue in universe.queues.iter_mut() {
+            queue.retain(|item| item.owner == player);
+        }
+        // the whole galaxy could be generated again from its seed
+        universe.seed = 0;
+        universe
+            .special_stars
+            .retain(|star| vision[*star] != StarState::Unknown);
+        universe
+    }
+
+    // fleets of the player, and of the others around stars where they have a colony or ships
+    pub fn fleets_seen_by(&self, fleets: &[FleetState], player: usize) -> Vec<FleetState> {
+        let watched = |star: usize| {
+            self.star_details[star].owner == player
+                || fleets
+                    .iter()
+                    .any(|fleet| fleet.owner == player && fleet.order.waiting_at() == Some(star))
+        };
+        fleets
+            .iter()
+            .filter(|fleet| fleet.owner == player || fleet.order.waiting_at().is_some_and(watched))
+            .cloned()
+            .collect()
+    }
+}
@@ -6,6 +6,10 @@ use bevy::{
     sprite::MaterialMesh2dBundle,
 };
 use bevy_easings::{EaseValue, Lerp};
+use bevy_prototype_lyon::{
+    prelude::{DrawMode, GeometryBuilder, StrokeMode},
+    shapes,
+};
 
 use crate::{
     assets::{GalaxyAssets, UiAssets},
@@ -14,8 +18,10 @@ use crate::{
 };
 
 use super::{
-    fleet::Order,
+    fleet::{Order, Owner},
     galaxy::{GalaxyCreator, Star, StarColor},
+    turns::CurrentPlayer,
+    ui::SelectedStar,
     StarState,
 };
 
@@ -29,7 +35,8 @@ struct ScreenTag;
 pub struct Plugin;
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(CURRENT_STATE).with_system(setup))
+        app.init_resource::<DrawnView>()
+            .add_system_set(SystemSet::on_enter(CURRENT_STATE).with_system(setup))
             .add_system_set(
                 SystemSet::on_update(CURRENT_STATE)
                     .with_system(update_camera)
@@ -37,7 +44,8 @@ impl bevy::app::Plugin for Plugin {
                     .with_system(camera_keyboard_controls)
                     .with_system(camera_mouse_controls)
                     .with_system(camera_touch_controls)
-                    .with_system(hide_stars),
+                    .with_system(hide_stars)
+                    .with_system(refresh_view),
             )
             .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down));
     }
@@ -63,10 +71,26 @@ struct System {
 
 #[derive(Component)]
 struct StarName;
+
+fn star_name_transform(star: &Star) -> Transform {
+    Transform::from_scale(Vec3::splat(0.1 / <StarSize as Into<f32>>::into(star.size)))
+        .with_translation(Vec3::new(
+            -(star.name.len() as f32) / 2.0,
+            -2.2,
+            z_levels::STAR_NAME,
+        ))
+}
+
 #[derive(Component)]
 pub struct StarHat(pub usize);
 #[derive(Component)]
 pub struct StarMask(pub usize);
+#[derive(Component)]
+struct SpecialRing(usize);
+
+// the player whose knowledge of the galaxy is on screen, `None` to redraw it
+#[derive(Resource, Default)]
+pub struct DrawnView(pub Option<usize>);
 
 #[derive(Resource)]
 struct TempMaterials {
@@ -83,9 +107,13 @@ fn setup(
     mut camera: Query<&mut Transform, With<Camera2d>>,
     time: Res<Time>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    current: Res<CurrentPlayer>,
+    mut drawn: ResMut<DrawnView>,
 ) {
     info!("Loading screen");
 
+    drawn.0 = Some(current.index);
+
     let blue = materials.get(&galaxy_assets.blue_star).unwrap().clone();
     let blue_star = materials.add(blue);
     let yellow = materials.get(&galaxy_assets.yellow_star).unwrap().clone();
@@ -101,7 +129,7 @@ fn setup(
     universe.star_entities = universe
         .galaxy
         .iter()
-        .zip(universe.players[0].vision.iter())
+        .zip(universe.players[current.index].vision.iter())
         .enumerate()
         .map(|(index, (star, visibility))| {
             commands
@@ -142,14 +170,7 @@ fn setup(
                                     color: Color::WHITE,
                                 },
                             ),
-                            transform: Transform::from_scale(Vec3::splat(
-                                0.1 / <StarSize as Into<f32>>::into(star.size),
-                            ))
-                            .with_translation(Vec3::new(
-                                -(star.name.len() as f32) / 2.0,
-                                -2.2,
-                                z_levels::STAR_NAME,
-                            )),
+                            transform: star_name_transform(star),
                             ..default()
                         },
                         StarName,
@@ -165,22 +186,44 @@ fn setup(
                                 )
                                 .with_rotation(Quat::from_rotation_z(hat_angle)),
                             visibility: Visibility {
-                                is_visible: *visibility != StarState::Unknown,
+                                is_visible: *visibility == StarState::Owned(current.index),
                             },
                             ..default()
                         },
                         StarHat(index),
                     ));
+                    let mask = match visibility {
+                        StarState::Owned(owner) if *owner != current.index => Some(*owner),
+                        _ => None,
+                    };
                     parent.spawn((
                         SpriteBundle {
+                            sprite: Sprite {
+                                color: mask
+                                    .map(|owner| universe.owner_color(owner))
+                                    .unwrap_or_default(),
+                                ..default()
+                            },
                             texture: galaxy_assets.mask.clone_weak(),
                             transform: Transform::from_scale(Vec3::new(0.045, 0.035, 0.0))
                                 .with_translation(Vec2::ZERO.extend(z_levels::STAR_DECORATION)),
-                            visibility: Visibility { is_visible: false },
+                            visibility: Visibility {
+                                is_visible: mask.is_some(),
+                            },
                             ..default()
                         },
                         StarMask(index),
                     ));
+                    let mut ring = GeometryBuilder::build_as(
+                        &shapes::Circle {
+                            radius: 3.2,
+                            center: Vec2::ZERO,
+                        },
+                        DrawMode::Stroke(StrokeMode::new(Color::GOLD, 0.3)),
+                        Transform::from_translation(Vec2::ZERO.extend(z_levels::STAR_DECORATION)),
+                    );
+                    ring.visibility.is_visible = universe.is_special_star(index);
+                    parent.spawn((ring, SpecialRing(index)));
                 })
                 .id()
         })
@@ -192,7 +235,7 @@ fn setup(
     });
     commands.insert_resource(CameraControllerTarget {
         zoom_level: 8.0,
-        position: universe.galaxy[universe.players[0].start].position,
+        position: universe.galaxy[universe.players[current.index].start].position,
         ignore_movement: false,
     });
     *camera.single_mut() = Camera2dBundle::default().transform;
@@ -238,11 +281,7 @@ fn update_camera(
         }
         for (mut transform, order) in &mut fleets {
             transform.scale = Vec3::splat(controller.zoom_level.powf(0.7));
-            let star = match order {
-                Order::Orbit(around) => around,
-                Order::Move { from, .. } => from,
-            };
-            transform.translation = (universe.galaxy[*star].position * controller.zoom_level
+            transform.translation = (order.departure(&universe.galaxy) * controller.zoom_level
                 / RATIO_ZOOM_DISTANCE)
                 .extend(z_levels::SHIP);
         }
@@ -486,6 +525,7 @@ fn hide_stars(
     current: ResMut<CurrentGame>,
     temp_materials: Option<Res<TempMaterials>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    player: Res<CurrentPlayer>,
 ) {
     if let Some(temp_materials) = temp_materials {
         let duration = 10.0;
@@ -509,7 +549,7 @@ fn hide_stars(
             for (entity, visible) in universe
                 .star_entities
                 .iter()
-                .zip(universe.players[0].vision.iter())
+                .zip(universe.players[player.index].vision.iter())
             {
                 if *visible == StarState::Unknown {
                     *stars.get_mut(*entity).unwrap() = galaxy_assets.unknown.clone_weak();
@@ -518,3 +558,91 @@ fn hide_stars(
         }
     }
 }
+
+// redraw the galaxy as the human now in front of the screen knows it
+#[allow(clippy::type_complexity)]
+fn refresh_view(
+    current: Res<CurrentPlayer>,
+    mut drawn: ResMut<DrawnView>,
+    universe: Res<Universe>,
+    galaxy_assets: Res<GalaxyAssets>,
+    temp_materials: Option<Res<TempMaterials>>,
+    mut stars: Query<&mut Handle<ColorMaterial>>,
+    mut hats: Query<(&mut Visibility, &StarHat)>,
+    mut masks: Query<(&mut Visibility, &mut Sprite, &StarMask), Without<StarHat>>,
+    mut fleets: Query<(&mut Visibility, &Owner), (Without<StarHat>, Without<StarMask>)>,
+    mut rings: Query<
+        (&mut Visibility, &SpecialRing),
+        (Without<StarHat>, Without<StarMask>, Without<Owner>),
+    >,
+    mut selected_star: ResMut<SelectedStar>,
+    mut target: ResMut<CameraControllerTarget>,
+    controller: Res<CameraController>,
+    mut systems: Query<(&mut System, &mut Transform, &Children)>,
+    mut star_names: Query<(&mut Text, &mut Transform), (With<StarName>, Without<System>)>,
+) {
+    if drawn.0 == Some(current.index) {
+        return;
+    }
+    let new_viewer = drawn.0.is_some();
+    drawn.0 = Some(current.index);
+    let player = &universe.players[current.index];
+
+    for ((entity, star), state) in universe
+        .star_entities
+        .iter()
+        .zip(universe.galaxy.iter())
+        .zip(player.vision.iter())
+    {
+        // players on another computer learn what stars are as they discover them
+        if let Ok((mut system, mut transform, children)) = systems.get_mut(*entity) {
+            if system.star.name != star.name || system.star.size != star.size {
+                system.star = star.clone();
+                transform.scale = Vec3::splat(
+                    <StarSize as Into<f32>>::into(star.size) * controller.zoom_level.powf(0.7),
+                );
+                for child in children {
+                    if let Ok((mut text, mut transform)) = star_names.get_mut(*child) {
+                        text.sections[0].value = star.name.clone();
+                        *transform = star_name_transform(star);
+                    }
+                }
+            }
+        }
+        let Ok(mut material) = stars.get_mut(*entity) else {
+            continue;
+        };
+        *material = match (star.color, state, &temp_materials) {
+            (StarColor::Blue, StarState::Unknown, Some(temp)) => temp.blue_star.clone_weak(),
+            (StarColor::Orange, StarState::Unknown, Some(temp)) => temp.orange_star.clone_weak(),
+            (StarColor::Yellow, StarState::Unknown, Some(temp)) => temp.yellow_star.clone_weak(),
+            (_, StarState::Unknown, None) => galaxy_assets.unknown.clone_weak(),
+            (StarColor::Blue, _, _) => galaxy_assets.blue_star.clone_weak(),
+            (StarColor::Orange, _, _) => galaxy_assets.orange_star.clone_weak(),
+            (StarColor::Yellow, _, _) => galaxy_assets.yellow_star.clone_weak(),
+        };
+    }
+    for (mut visibility, hat) in &mut hats {
+        visibility.is_visible = player.vision[hat.0] == StarState::Owned(current.index);
+    }
+    for (mut visibility, mut sprite, mask) in &mut masks {
+        match player.vision[mask.0] {
+            StarState::Owned(owner) if owner != current.index => {
+                visibility.is_visible = true;
+                sprite.color = universe.owner_color(owner);
+            }
+            _ => visibility.is_visible = false,
+        }
+    }
+    for (mut visibility, owner) in &mut fleets {
+        visibility.is_visible = owner.0 == current.index;
+    }
+    for (mut visibility, ring) in &mut rings {
+        visibility.is_visible = universe.is_special_star(ring.0);
+    }
+
+    if new_viewer {
+        *selected_star = SelectedStar::default();
+        target.position = universe.galaxy[player.start].position;
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(clippy::needless_update, clippy::too_many_arguments)]
 
-use assets::names::{Names, NamesLoader};
+use assets::{
+    balance::{Balance, BalanceLoader},
+    names::{Names, NamesLoader},
+};
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::core_pipeline::bloom::BloomSettings;
 use bevy::{app::AppExit, log::LogPlugin, prelude::*};
@@ -69,6 +72,9 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     builder.add_asset::<Names>().add_asset_loader(NamesLoader);
+    builder
+        .add_asset::<Balance>()
+        .add_asset_loader(BalanceLoader);
 
     builder.add_plugin(ShapePlugin);
 
@@ -91,6 +97,11 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         .add_plugin(crate::game::turns::Plugin)
         .add_plugin(crate::game::fleet::Plugin)
         .add_plugin(crate::lost::Plugin);
+    #[cfg(not(target_arch = "wasm32"))]
+    builder
+        .add_plugin(crate::game::save::Plugin)
+        .add_plugin(crate::game::network::Plugin)
+        .add_plugin(crate::game::external::Plugin);
     #[cfg(feature = "debug-graph")]
     bevy_mod_debugdump::print_schedule(&mut builder);
 
@@ -100,6 +111,21 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+// plays games between bots without a window, to compare them
+#[cfg(not(target_arch = "wasm32"))]
+pub fn tournament() -> Result<(), Box<dyn std::error::Error>> {
+    let tournament = crate::game::tournament::Tournament::from_args()?;
+
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugin(LogPlugin::default())
+        .insert_resource(tournament)
+        .add_plugin(crate::game::tournament::Plugin)
+        .run();
+
+    Ok(())
+}
+
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 pub enum GameState {
     Splash,
@@ -1,35 +1,290 @@
 use bevy::prelude::*;
 
-use crate::assets::UiAssets;
+use crate::{
+    assets::UiAssets,
+    game::{
+        setup::Rematch,
+        stats::{self, StatsHistory},
+        turns::{CurrentPlayer, LoseCondition, Message, Turns},
+        ui::StatsState,
+        victory::VictoryCondition,
+        CurrentGame, Universe,
+    },
+    ui_helper::{button::ButtonId, ColorScheme},
+};
 
 const CURRENT_STATE: crate::GameState = crate::GameState::Lost;
 
 #[derive(Component)]
 struct ScreenTag;
 
-#[derive(Resource)]
-struct Screen {
-    done: Timer,
-}
-
 pub struct Plugin;
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Screen {
-            done: Timer::from_seconds(20.0, TimerMode::Once),
-        })
-        .add_system_set(SystemSet::on_enter(CURRENT_STATE).with_system(setup))
-        .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down))
-        .add_system_set(SystemSet::on_update(CURRENT_STATE).with_system(done));
+        app.add_system_set(SystemSet::on_enter(CURRENT_STATE).with_system(setup))
+            .add_system_set(SystemSet::on_exit(CURRENT_STATE).with_system(tear_down))
+            .add_system_set(
+                SystemSet::on_update(CURRENT_STATE)
+                    .with_system(button_system)
+                    .with_system(hide_behind_charts),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LostButton {
+    NewGame,
+    Rematch,
+    Charts,
+    Menu,
+}
+
+impl From<LostButton> for String {
+    fn from(button: LostButton) -> String {
+        match button {
+            LostButton::NewGame => "New Game".to_string(),
+            LostButton::Rematch => "Rematch".to_string(),
+            LostButton::Charts => "Charts".to_string(),
+            LostButton::Menu => "Menu".to_string(),
+        }
     }
 }
 
-fn setup(mut commands: Commands, _ui_handles: Res<UiAssets>) {
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    ui_handles: Res<UiAssets>,
+    buttons: Res<Assets<crate::ui_helper::button::Button>>,
+    universe: Res<Universe>,
+    turns: Res<Turns>,
+    current: Res<CurrentPlayer>,
+    history: Res<StatsHistory>,
+    current_game: Res<CurrentGame>,
+    time: Res<Time>,
+) {
     info!("Loading screen");
 
-    commands.insert_resource(Screen {
-        done: Timer::from_seconds(20.0, TimerMode::Once),
-    });
+    commands.insert_resource(StatsState::default());
+
+    // the messages of the last human to play tell how the game ended for them
+    let (title, cause) = turns
+        .messages
+        .iter()
+        .find_map(|message| match message {
+            Message::Win => Some((
+                "Victory",
+                VictoryCondition::Elimination.achievement().to_string(),
+            )),
+            Message::Victory { condition } => {
+                Some(("Victory", condition.achievement().to_string()))
+            }
+            Message::Lose {
+                condition: LoseCondition::Deficit,
+            } => Some(("Defeat", "You have lost too many credits.".to_string())),
+            Message::Lose {
+                condition: LoseCondition::NoMoreColonies,
+            } => Some((
+                "Defeat",
+                "All your colonies have been destroyed.".to_string(),
+            )),
+            Message::RivalVictory {
+                condition,
+                player_name,
+            } => Some(("Defeat", format!("{} won by {}.", player_name, condition))),
+            _ => None,
+        })
+        .unwrap_or(("Game over", String::new()));
+    let cause = cause.replace('\n', " ");
+    let play_time = current_game.play_time(&time).as_secs();
+
+    let button_handle = ui_handles.button_handle.clone_weak();
+    let button = buttons.get(&button_handle).unwrap();
+    let action_buttons = [
+        Some(LostButton::NewGame),
+        // a game joined over the network can only be replayed by its host
+        (!current.remote).then_some(LostButton::Rematch),
+        Some(LostButton::Charts),
+        Some(LostButton::Menu),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|action| {
+        button.add(
+            &mut commands,
+            Val::Px(150.),
+            Val::Px(40.),
+            UiRect::all(Val::Px(10.0)),
+            ui_handles.font_main.clone_weak(),
+            action,
+            25.,
+            ColorScheme::TEXT,
+        )
+    })
+    .collect::<Vec<_>>();
+
+    let title_style = TextStyle {
+        font: ui_handles.font_main.clone_weak(),
+        font_size: 60.0,
+        color: ColorScheme::TEXT,
+    };
+    let text_style = TextStyle {
+        font: ui_handles.font_sub.clone_weak(),
+        font_size: 25.0,
+        color: ColorScheme::TEXT,
+    };
+    let column = |width: f32| Style {
+        size: Size::new(Val::Px(width), Val::Undefined),
+        flex_shrink: 0.,
+        ..default()
+    };
+
+    let base = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, title_style));
+            parent.spawn(TextBundle::from_section(cause, text_style.clone()));
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "{} turns, played in {}:{:02}:{:02}",
+                        turns.count,
+                        play_time / 3600,
+                        play_time / 60 % 60,
+                        play_time % 60
+                    ),
+                    text_style.clone(),
+                )
+                .with_style(Style {
+                    margin: UiRect {
+                        bottom: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                }),
+            );
+
+            let mut row =
+                |cells: Vec<(String, Color)>| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (i, (value, color)) in cells.into_iter().enumerate() {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        value,
+                                        TextStyle {
+                                            color,
+                                            ..text_style.clone()
+                                        },
+                                    )
+                                    .with_style(column(if i == 1 { 180.0 } else { 120.0 })),
+                                );
+                            }
+                        });
+                };
+            row([
+                "Rank",
+                "Empire",
+                "Colonies",
+                "Population",
+                "Revenue",
+                "Fleet",
+                "Peak pop.",
+            ]
+            .into_iter()
+            .map(|header| (header.to_string(), ColorScheme::TEXT_DARK))
+            .collect());
+            for (rank, standing) in stats::standings(&universe, &history, turns.count)
+                .into_iter()
+                .enumerate()
+            {
+                row(vec![
+                    (format!("{}", rank + 1), ColorScheme::TEXT),
+                    (
+                        if standing.eliminated {
+                            format!("{} (out)", standing.name)
+                        } else {
+                            standing.name
+                        },
+                        standing.color,
+                    ),
+                    (format!("{}", standing.last.colonies), ColorScheme::TEXT),
+                    (
+                        format!("{:.0}", standing.last.population),
+                        ColorScheme::TEXT,
+                    ),
+                    (format!("{:.1}", standing.last.revenue), ColorScheme::TEXT),
+                    (
+                        format!("{}", standing.last.fleet_strength),
+                        ColorScheme::TEXT,
+                    ),
+                    (
+                        format!("{:.0}", standing.peak.population),
+                        ColorScheme::TEXT,
+                    ),
+                ]);
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect {
+                            top: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ..default()
+                })
+                .push_children(&action_buttons);
+        })
+        .id();
+
+    let panel = commands
+        .spawn(bevy_ninepatch::NinePatchBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            nine_patch_data: bevy_ninepatch::NinePatchData::with_single_content(
+                ui_handles.panel_handle.1.clone_weak(),
+                ui_handles.panel_handle.0.clone_weak(),
+                base,
+            ),
+            ..default()
+        })
+        .id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ScreenTag,
+        ))
+        .push_children(&[panel]);
 }
 
 fn tear_down(mut commands: Commands, query: Query<Entity, With<ScreenTag>>) {
@@ -40,8 +295,35 @@ fn tear_down(mut commands: Commands, query: Query<Entity, With<ScreenTag>>) {
     }
 }
 
-fn done(time: Res<Time>, mut screen: ResMut<Screen>, mut state: ResMut<State<crate::GameState>>) {
-    if screen.done.tick(time.delta()).finished() {
-        state.set(crate::GameState::Menu).unwrap();
+fn button_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &ButtonId<LostButton>), Changed<Interaction>>,
+    mut state: ResMut<State<crate::GameState>>,
+    mut stats: ResMut<StatsState>,
+) {
+    for (interaction, button_id) in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            let _ = match button_id.0 {
+                LostButton::NewGame => state.set(crate::GameState::Setup),
+                LostButton::Rematch => {
+                    commands.insert_resource(Rematch);
+                    state.set(crate::GameState::Game)
+                }
+                LostButton::Charts => {
+                    stats.open = true;
+                    Ok(())
+                }
+                LostButton::Menu => state.set(crate::GameState::Menu),
+            };
+        }
+    }
+}
+
+fn hide_behind_charts(stats: Res<StatsState>, mut screen: Query<&mut Visibility, With<ScreenTag>>) {
+    if !stats.is_changed() {
+        return;
+    }
+    for mut visibility in &mut screen {
+        visibility.is_visible = !stats.open;
     }
 }
@@ -2,11 +2,18 @@ use bevy::prelude::*;
 
 use bevy_easings::Ease;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::game::{
+    network::{self, Client},
+    save::{SaveGame, SaveSlot},
+};
 use crate::{
     assets::{CloneWeak, UiAssets},
     game::turns::TurnState,
     ui_helper::ColorScheme,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::input::{keyboard::KeyboardInput, ButtonState};
 
 const CURRENT_STATE: crate::GameState = crate::GameState::Menu;
 
@@ -17,12 +24,22 @@ struct ScreenTag;
 struct Screen {
     first_load: bool,
     menu_selected: Option<i32>,
+    // address of the game to join, typed in the menu
+    #[cfg(not(target_arch = "wasm32"))]
+    host: String,
+    // why the last save couldn't be loaded
+    #[cfg(not(target_arch = "wasm32"))]
+    load_error: Option<String>,
 }
 impl Default for Screen {
     fn default() -> Self {
         Screen {
             first_load: true,
             menu_selected: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            host: network::host_address(),
+            #[cfg(not(target_arch = "wasm32"))]
+            load_error: None,
         }
     }
 }
@@ -40,12 +57,25 @@ impl bevy::app::Plugin for Plugin {
                     .with_system(button_system)
                     .with_system(display_menu_item_selector),
             );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system_set(
+            SystemSet::on_update(CURRENT_STATE)
+                .with_system(edit_host)
+                .with_system(display_host)
+                .with_system(display_load_error),
+        );
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum MenuButton {
+    #[cfg(not(target_arch = "wasm32"))]
+    Continue,
     NewGame,
+    #[cfg(not(target_arch = "wasm32"))]
+    Load,
+    #[cfg(not(target_arch = "wasm32"))]
+    Join,
     #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
     Quit,
 }
@@ -53,7 +83,13 @@ enum MenuButton {
 impl From<MenuButton> for String {
     fn from(button: MenuButton) -> String {
         match button {
+            #[cfg(not(target_arch = "wasm32"))]
+            MenuButton::Continue => "Continue".to_string(),
             MenuButton::NewGame => "New Game".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            MenuButton::Load => "Load".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            MenuButton::Join => "Join".to_string(),
             #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
             MenuButton::Quit => "quit".to_string(),
         }
@@ -61,7 +97,13 @@ impl From<MenuButton> for String {
 }
 
 const MENU_BUTTONS: &[MenuButton] = &[
+    #[cfg(not(target_arch = "wasm32"))]
+    MenuButton::Continue,
     MenuButton::NewGame,
+    #[cfg(not(target_arch = "wasm32"))]
+    MenuButton::Load,
+    #[cfg(not(target_arch = "wasm32"))]
+    MenuButton::Join,
     #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
     MenuButton::Quit,
 ];
@@ -81,6 +123,11 @@ fn setup(
 
     commands.insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.075)));
 
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        screen.load_error = None;
+    }
+
     mouse_button_input.clear();
     keyboard_input.clear();
     gamepad_input.clear();
@@ -295,11 +342,118 @@ fn setup(
         commands.entity(panel).insert(panel_style);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(50.),
+                    bottom: Val::Percent(8.),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::from_section(
+                format!("Join {}", screen.host),
+                TextStyle {
+                    font: font_details.clone(),
+                    color: ColorScheme::TEXT,
+                    font_size: height / 30.0,
+                },
+            ),
+            ..default()
+        },
+        HostText,
+        ScreenTag,
+    ));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(50.),
+                    bottom: Val::Percent(4.),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font: font_details.clone(),
+                    color: ColorScheme::TEXT_HIGHLIGHT,
+                    font_size: height / 30.0,
+                },
+            ),
+            ..default()
+        },
+        LoadErrorText,
+        ScreenTag,
+    ));
+
     let _ = turn_state.set(TurnState::Out);
 
     screen.first_load = false;
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct HostText;
+
+// an IP address, typed with the digits and the dot
+#[cfg(not(target_arch = "wasm32"))]
+fn edit_host(mut keyboard_events: EventReader<KeyboardInput>, mut screen: ResMut<Screen>) {
+    for event in keyboard_events.iter() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let typed = match event.key_code {
+            Some(KeyCode::Key0 | KeyCode::Numpad0) => '0',
+            Some(KeyCode::Key1 | KeyCode::Numpad1) => '1',
+            Some(KeyCode::Key2 | KeyCode::Numpad2) => '2',
+            Some(KeyCode::Key3 | KeyCode::Numpad3) => '3',
+            Some(KeyCode::Key4 | KeyCode::Numpad4) => '4',
+            Some(KeyCode::Key5 | KeyCode::Numpad5) => '5',
+            Some(KeyCode::Key6 | KeyCode::Numpad6) => '6',
+            Some(KeyCode::Key7 | KeyCode::Numpad7) => '7',
+            Some(KeyCode::Key8 | KeyCode::Numpad8) => '8',
+            Some(KeyCode::Key9 | KeyCode::Numpad9) => '9',
+            Some(KeyCode::Period | KeyCode::NumpadDecimal) => '.',
+            Some(KeyCode::Back) => {
+                screen.host.pop();
+                continue;
+            }
+            _ => continue,
+        };
+        screen.host.push(typed);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn display_host(screen: Res<Screen>, mut text: Query<&mut Text, With<HostText>>) {
+    if screen.is_changed() {
+        if let Ok(mut text) = text.get_single_mut() {
+            text.sections[0].value = format!("Join {}", screen.host);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct LoadErrorText;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn display_load_error(screen: Res<Screen>, mut text: Query<&mut Text, With<LoadErrorText>>) {
+    if screen.is_changed() {
+        if let Ok(mut text) = text.get_single_mut() {
+            text.sections[0].value = screen.load_error.clone().unwrap_or_default();
+        }
+    }
+}
+
 #[derive(Component)]
 struct PlayerName;
 
@@ -312,7 +466,9 @@ fn tear_down(mut commands: Commands, query: Query<Entity, With<ScreenTag>>) {
 }
 
 fn gamepad_input_system(
+    mut commands: Commands,
     mut state: ResMut<State<crate::GameState>>,
+    mut turn_state: ResMut<State<TurnState>>,
     mut screen: ResMut<Screen>,
     gamepads: Res<Gamepads>,
     gamepad_input: Res<Input<GamepadButton>>,
@@ -356,21 +512,26 @@ fn gamepad_input_system(
         }
 
         if gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)) {
-            match screen.menu_selected {
-                Some(0) => {
-                    let _ = state.set(crate::GameState::Setup);
-                }
-                Some(2) => {
-                    let _ = state.set(crate::GameState::Exit);
-                }
-                _ => (),
+            if let Some(button) = screen
+                .menu_selected
+                .and_then(|i| MENU_BUTTONS.get(i as usize))
+            {
+                activate(
+                    *button,
+                    &mut screen,
+                    &mut commands,
+                    &mut state,
+                    &mut turn_state,
+                );
             }
         }
     }
 }
 
 fn keyboard_input_system(
+    mut commands: Commands,
     mut state: ResMut<State<crate::GameState>>,
+    mut turn_state: ResMut<State<TurnState>>,
     mut screen: ResMut<Screen>,
     keyboard_input: Res<Input<KeyCode>>,
     mut wnds: ResMut<Windows>,
@@ -405,20 +566,25 @@ fn keyboard_input_system(
     } else if keyboard_input.just_pressed(KeyCode::Space)
         || keyboard_input.just_pressed(KeyCode::Return)
     {
-        match screen.menu_selected {
-            Some(0) => {
-                let _ = state.set(crate::GameState::Setup);
-            }
-            Some(2) => {
-                let _ = state.set(crate::GameState::Exit);
-            }
-            _ => (),
+        if let Some(button) = screen
+            .menu_selected
+            .and_then(|i| MENU_BUTTONS.get(i as usize))
+        {
+            activate(
+                *button,
+                &mut screen,
+                &mut commands,
+                &mut state,
+                &mut turn_state,
+            );
         }
     }
 }
 
 fn button_system(
+    mut commands: Commands,
     mut state: ResMut<State<crate::GameState>>,
+    mut turn_state: ResMut<State<TurnState>>,
     mut screen: ResMut<Screen>,
     mut interaction_query: Query<
         (
@@ -431,25 +597,72 @@ fn button_system(
 ) {
     for (_button, interaction, button_id) in interaction_query.iter_mut() {
         match *interaction {
-            Interaction::Clicked => match button_id.0 {
-                #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
-                MenuButton::Quit => {
-                    let _ = state.set(crate::GameState::Exit);
-                }
-                MenuButton::NewGame => {
-                    let _ = state.set(crate::GameState::Setup);
-                }
-            },
-            Interaction::Hovered => match button_id.0 {
-                MenuButton::NewGame => screen.menu_selected = Some(0),
-                #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
-                MenuButton::Quit => screen.menu_selected = Some(1),
-            },
+            Interaction::Clicked => activate(
+                button_id.0,
+                &mut screen,
+                &mut commands,
+                &mut state,
+                &mut turn_state,
+            ),
+            Interaction::Hovered => {
+                screen.menu_selected = MENU_BUTTONS
+                    .iter()
+                    .position(|button| *button == button_id.0)
+                    .map(|i| i as i32);
+            }
             Interaction::None => (),
         }
     }
 }
 
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn activate(
+    button: MenuButton,
+    screen: &mut Screen,
+    commands: &mut Commands,
+    state: &mut State<crate::GameState>,
+    turn_state: &mut State<TurnState>,
+) {
+    match button {
+        #[cfg(not(target_arch = "wasm32"))]
+        MenuButton::Continue => load(SaveSlot::Auto, screen, commands, state, turn_state),
+        MenuButton::NewGame => {
+            let _ = state.set(crate::GameState::Setup);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        MenuButton::Load => load(SaveSlot::Manual, screen, commands, state, turn_state),
+        #[cfg(not(target_arch = "wasm32"))]
+        // the game starts once the host sends the current turn
+        MenuButton::Join => commands.insert_resource(Client::join(screen.host.clone())),
+        #[cfg(not(any(target_arch = "wasm32", target_os = "ios")))]
+        MenuButton::Quit => {
+            let _ = state.set(crate::GameState::Exit);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load(
+    slot: SaveSlot,
+    screen: &mut Screen,
+    commands: &mut Commands,
+    state: &mut State<crate::GameState>,
+    turn_state: &mut State<TurnState>,
+) {
+    match SaveGame::load(slot) {
+        Ok(save) => {
+            screen.load_error = None;
+            save.restore(commands);
+            let _ = turn_state.overwrite_set(TurnState::Player);
+            let _ = state.set(crate::GameState::Game);
+        }
+        Err(error) => {
+            warn!("error loading {:?} save: {}", slot, error);
+            screen.load_error = Some(format!("Could not load the game: {}", error));
+        }
+    }
+}
+
 #[derive(Component)]
 struct MenuItemSelector(usize);
 